@@ -116,6 +116,10 @@ pub struct CompileOptions {
     #[arg(long)]
     pub debug_comptime_in_file: Option<String>,
 
+    /// Allow comptime code to read environment variables with `std::meta::env_var`
+    #[arg(long)]
+    pub allow_comptime_env_vars: bool,
+
     /// Outputs the paths to any modified artifacts
     #[arg(long, hide = true)]
     pub show_artifact_paths: bool,
@@ -131,6 +135,15 @@ pub struct CompileOptions {
     /// A less aggressive inliner should generate smaller programs
     #[arg(long, hide = true, allow_hyphen_values = true, default_value_t = i64::MAX)]
     pub inliner_aggressiveness: i64,
+
+    /// Replace calls to unconstrained functions with constant arguments by the values they return
+    #[arg(long, hide = true)]
+    pub enable_inline_const_brillig_calls: bool,
+
+    /// Maximum number of constants replacing calls to unconstrained functions may add to the program,
+    /// where an array counts as all of its elements. There is no limit by default
+    #[arg(long, hide = true)]
+    pub inline_const_brillig_calls_constants_limit: Option<usize>,
 }
 
 pub fn parse_expression_width(input: &str) -> Result<ExpressionWidth, std::io::Error> {
@@ -290,6 +303,7 @@ pub fn check_crate(
 ) -> CompilationResult<()> {
     let mut errors = vec![];
     let error_on_unused_imports = true;
+    context.allow_comptime_env_vars = options.allow_comptime_env_vars;
     let diagnostics = CrateDefMap::collect_defs(
         crate_id,
         context,
@@ -589,6 +603,10 @@ pub fn compile_no_check(
         emit_ssa: if options.emit_ssa { Some(context.package_build_path.clone()) } else { None },
         skip_underconstrained_check: options.skip_underconstrained_check,
         inliner_aggressiveness: options.inliner_aggressiveness,
+        enable_inline_const_brillig_calls: options.enable_inline_const_brillig_calls,
+        inline_const_brillig_calls_constants_limit: options
+            .inline_const_brillig_calls_constants_limit,
+        inline_const_brillig_calls_cache: None,
     };
 
     let SsaProgramArtifact { program, debug, warnings, names, brillig_names, error_types, .. } =
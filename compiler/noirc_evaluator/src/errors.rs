@@ -43,6 +43,8 @@ pub enum RuntimeError {
     },
     #[error("Could not determine loop bound at compile-time")]
     UnknownLoopBound { call_stack: CallStack },
+    #[error("Unrolling loops exceeded the maximum of {max_instructions} instructions")]
+    UnrollLimitExceeded { max_instructions: usize, call_stack: CallStack },
     #[error("Argument is not constant")]
     AssertConstantFailed { call_stack: CallStack },
     #[error("The static_assert message is not constant")]
@@ -157,6 +159,7 @@ impl RuntimeError {
             | RuntimeError::TypeConversion { call_stack, .. }
             | RuntimeError::UnInitialized { call_stack, .. }
             | RuntimeError::UnknownLoopBound { call_stack }
+            | RuntimeError::UnrollLimitExceeded { call_stack, .. }
             | RuntimeError::AssertConstantFailed { call_stack }
             | RuntimeError::StaticAssertDynamicMessage { call_stack }
             | RuntimeError::StaticAssertDynamicPredicate { call_stack }
@@ -12,6 +12,7 @@ use std::{
     fs::File,
     io::Write,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
 use crate::errors::{RuntimeError, SsaReport};
@@ -31,16 +32,21 @@ use noirc_errors::debug_info::{DebugFunctions, DebugInfo, DebugTypes, DebugVaria
 
 use noirc_frontend::ast::Visibility;
 use noirc_frontend::{hir_def::function::FunctionSignature, monomorphization::ast::Program};
+use opt::inline_const_brillig_calls::ConstantsBudget;
+pub use opt::inline_const_brillig_calls::InlineConstBrilligCallsCache;
+pub use opt::inlining::InlinerAggressiveness;
+pub use parser::{optimize_ssa_text, SsaErrorWithSource};
 use ssa_gen::Ssa;
 use tracing::{span, Level};
 
 use crate::acir::{Artifacts, GeneratedAcir};
 
+#[cfg(test)]
+mod alpha_equivalence;
 mod checks;
 pub(super) mod function_builder;
 pub mod ir;
 mod opt;
-#[cfg(test)]
 pub(crate) mod parser;
 pub mod ssa_gen;
 
@@ -66,7 +72,19 @@ pub struct SsaEvaluatorOptions {
     pub skip_underconstrained_check: bool,
 
     /// The higher the value, the more inlined brillig functions will be.
+    /// See [`InlinerAggressiveness`] for how values are interpreted.
     pub inliner_aggressiveness: i64,
+
+    /// Replace calls to brillig functions with constant arguments by the values they return
+    pub enable_inline_const_brillig_calls: bool,
+
+    /// Maximum number of constants replacing brillig calls may add to the program, counting
+    /// each element of an array, or `None` for no limit
+    pub inline_const_brillig_calls_constants_limit: Option<usize>,
+
+    /// Reuse the results of replacing constant brillig calls from previous compilations of
+    /// identical programs, for tools which compile the same program again and again
+    pub inline_const_brillig_calls_cache: Option<Arc<Mutex<InlineConstBrilligCallsCache>>>,
 }
 
 pub(crate) struct ArtifactsAndWarnings(Artifacts, Vec<SsaReport>);
@@ -83,49 +101,20 @@ pub(crate) fn optimize_into_acir(
     let ssa_gen_span = span!(Level::TRACE, "ssa_generation");
     let ssa_gen_span_guard = ssa_gen_span.enter();
 
-    let mut ssa = SsaBuilder::new(
+    let builder = SsaBuilder::new(
         program,
         options.enable_ssa_logging,
         options.force_brillig_output,
         options.print_codegen_timings,
         &options.emit_ssa,
     )?
-    .run_pass(Ssa::defunctionalize, "After Defunctionalization:")
-    .run_pass(Ssa::remove_paired_rc, "After Removing Paired rc_inc & rc_decs:")
-    .run_pass(Ssa::separate_runtime, "After Runtime Separation:")
-    .run_pass(Ssa::resolve_is_unconstrained, "After Resolving IsUnconstrained:")
-    .run_pass(|ssa| ssa.inline_functions(options.inliner_aggressiveness), "After Inlining (1st):")
-    // Run mem2reg with the CFG separated into blocks
-    .run_pass(Ssa::mem2reg, "After Mem2Reg (1st):")
-    .run_pass(Ssa::simplify_cfg, "After Simplifying (1st):")
-    .run_pass(Ssa::as_slice_optimization, "After `as_slice` optimization")
-    .try_run_pass(
-        Ssa::evaluate_static_assert_and_assert_constant,
-        "After `static_assert` and `assert_constant`:",
-    )?
-    .run_pass(Ssa::loop_invariant_code_motion, "After Loop Invariant Code Motion:")
-    .try_run_pass(Ssa::unroll_loops_iteratively, "After Unrolling:")?
-    .run_pass(Ssa::simplify_cfg, "After Simplifying (2nd):")
-    .run_pass(Ssa::flatten_cfg, "After Flattening:")
-    .run_pass(Ssa::remove_bit_shifts, "After Removing Bit Shifts:")
-    // Run mem2reg once more with the flattened CFG to catch any remaining loads/stores
-    .run_pass(Ssa::mem2reg, "After Mem2Reg (2nd):")
-    // Run the inlining pass again to handle functions with `InlineType::NoPredicates`.
-    // Before flattening is run, we treat functions marked with the `InlineType::NoPredicates` as an entry point.
-    // This pass must come immediately following `mem2reg` as the succeeding passes
-    // may create an SSA which inlining fails to handle.
-    .run_pass(
-        |ssa| ssa.inline_functions_with_no_predicates(options.inliner_aggressiveness),
-        "After Inlining (2nd):",
+    .with_inline_const_brillig_calls(options.enable_inline_const_brillig_calls)
+    .with_inline_const_brillig_calls_constants_limit(
+        options.inline_const_brillig_calls_constants_limit,
     )
-    .run_pass(Ssa::remove_if_else, "After Remove IfElse:")
-    .run_pass(Ssa::fold_constants, "After Constant Folding:")
-    .run_pass(Ssa::remove_enable_side_effects, "After EnableSideEffectsIf removal:")
-    .run_pass(Ssa::fold_constants_using_constraints, "After Constraint Folding:")
-    .run_pass(Ssa::dead_instruction_elimination, "After Dead Instruction Elimination:")
-    .run_pass(Ssa::simplify_cfg, "After Simplifying:")
-    .run_pass(Ssa::array_set_optimization, "After Array Set Optimizations:")
-    .finish();
+    .with_inline_const_brillig_calls_cache(options.inline_const_brillig_calls_cache.clone());
+    let mut ssa =
+        optimize_ssa_before_brillig(builder, options.inliner_aggressiveness.into())?.finish();
 
     let ssa_level_warnings = if options.skip_underconstrained_check {
         vec![]
@@ -144,17 +133,13 @@ pub(crate) fn optimize_into_acir(
     let ssa_gen_span = span!(Level::TRACE, "ssa_generation");
     let ssa_gen_span_guard = ssa_gen_span.enter();
 
-    let ssa = SsaBuilder {
-        ssa,
-        print_ssa_passes: options.enable_ssa_logging,
-        print_codegen_timings: options.print_codegen_timings,
-    }
-    .run_pass(
-        |ssa| ssa.fold_constants_with_brillig(&brillig),
-        "After Constant Folding with Brillig:",
-    )
-    .run_pass(Ssa::dead_instruction_elimination, "After Dead Instruction Elimination:")
-    .finish();
+    let ssa = SsaBuilder::from_ssa(ssa, options.enable_ssa_logging, options.print_codegen_timings)
+        .run_pass(
+            |ssa| ssa.fold_constants_with_brillig(&brillig),
+            "After Constant Folding with Brillig:",
+        )
+        .run_pass(Ssa::dead_instruction_elimination, "After Dead Instruction Elimination:")
+        .finish();
 
     drop(ssa_gen_span_guard);
 
@@ -164,6 +149,168 @@ pub(crate) fn optimize_into_acir(
     Ok(ArtifactsAndWarnings(artifacts, ssa_level_warnings))
 }
 
+/// Runs the SSA passes that come before generating brillig, starting from freshly generated SSA.
+fn optimize_ssa_before_brillig(
+    builder: SsaBuilder,
+    inliner_aggressiveness: InlinerAggressiveness,
+) -> Result<SsaBuilder, RuntimeError> {
+    let keep_brillig_functions = builder.keep_brillig_functions;
+    let cache = builder.inline_const_brillig_calls_cache.clone();
+    let constants_limit = builder.inline_const_brillig_calls_constants_limit;
+    let mut builder = builder
+        .run_pass(Ssa::defunctionalize, "After Defunctionalization:")
+        .run_pass(Ssa::remove_paired_rc, "After Removing Paired rc_inc & rc_decs:")
+        .run_pass(Ssa::separate_runtime, "After Runtime Separation:")
+        .run_pass(Ssa::resolve_is_unconstrained, "After Resolving IsUnconstrained:")
+        .run_pass(|ssa| ssa.inline_functions(inliner_aggressiveness), "After Inlining (1st):");
+    if builder.inline_const_brillig_calls {
+        builder = builder
+            .run_pass(
+                |ssa| {
+                    let mut constants_budget = constants_limit
+                        .map_or_else(ConstantsBudget::unlimited, ConstantsBudget::new);
+                    let ssa = match cache {
+                        Some(cache) => ssa.inline_const_brillig_calls_with_cache(
+                            inliner_aggressiveness,
+                            &mut constants_budget,
+                            keep_brillig_functions,
+                            &mut cache.lock().expect("Expected the cache not to be poisoned"),
+                        ),
+                        None => ssa.inline_const_brillig_calls_with_options(
+                            inliner_aggressiveness,
+                            &mut constants_budget,
+                            keep_brillig_functions,
+                        ),
+                    };
+                    tracing::debug!(
+                        constants_used = constants_budget.used(),
+                        constants_limit,
+                        "Inlined const brillig calls"
+                    );
+                    ssa
+                },
+                "After Inlining Const Brillig Calls:",
+            )
+            .run_pass(Ssa::dedup_constants, "After Deduplicating Constants:");
+    }
+    optimize_ssa_after_inline_const_brillig_calls(builder, inliner_aggressiveness, None)
+}
+
+/// Runs the SSA passes that come after `inline_const_brillig_calls`.
+///
+/// These are split from the rest of the pipeline because `inline_const_brillig_calls`
+/// also runs them over each brillig function it specializes for a constant call.
+/// If `max_unrolled_instructions` is given, unrolling fails once it creates more instructions
+/// than that in a function.
+fn optimize_ssa_after_inline_const_brillig_calls(
+    builder: SsaBuilder,
+    inliner_aggressiveness: InlinerAggressiveness,
+    max_unrolled_instructions: Option<usize>,
+) -> Result<SsaBuilder, RuntimeError> {
+    let keep_brillig_functions = builder.keep_brillig_functions;
+    let builder = builder
+        // Run mem2reg with the CFG separated into blocks
+        .run_pass(Ssa::mem2reg, "After Mem2Reg (1st):")
+        .run_pass(Ssa::simplify_cfg, "After Simplifying (1st):")
+        .run_pass(Ssa::as_slice_optimization, "After `as_slice` optimization")
+        .try_run_pass(
+            Ssa::evaluate_static_assert_and_assert_constant,
+            "After `static_assert` and `assert_constant`:",
+        )?
+        .run_pass(Ssa::loop_invariant_code_motion, "After Loop Invariant Code Motion:")
+        .try_run_pass(
+            |ssa| match max_unrolled_instructions {
+                Some(max_instructions) => {
+                    Ssa::unroll_loops_iteratively_with_limit(ssa, max_instructions)
+                }
+                None => Ssa::unroll_loops_iteratively(ssa),
+            },
+            "After Unrolling:",
+        )?
+        .run_pass(Ssa::simplify_cfg, "After Simplifying (2nd):")
+        .run_pass(Ssa::flatten_cfg, "After Flattening:")
+        .run_pass(Ssa::remove_bit_shifts, "After Removing Bit Shifts:")
+        // Run mem2reg once more with the flattened CFG to catch any remaining loads/stores
+        .run_pass(Ssa::mem2reg, "After Mem2Reg (2nd):")
+        // Run the inlining pass again to handle functions with `InlineType::NoPredicates`.
+        // Before flattening is run, we treat functions marked with the `InlineType::NoPredicates` as an entry point.
+        // This pass must come immediately following `mem2reg` as the succeeding passes
+        // may create an SSA which inlining fails to handle.
+        .run_pass(
+            |ssa| {
+                ssa.inline_functions_with_no_predicates_with_options(
+                    inliner_aggressiveness,
+                    keep_brillig_functions,
+                )
+            },
+            "After Inlining (2nd):",
+        )
+        .run_pass(Ssa::remove_if_else, "After Remove IfElse:")
+        .run_pass(Ssa::fold_constants, "After Constant Folding:")
+        .run_pass(Ssa::remove_enable_side_effects, "After EnableSideEffectsIf removal:")
+        .run_pass(Ssa::fold_constants_using_constraints, "After Constraint Folding:")
+        .run_pass(Ssa::dead_instruction_elimination, "After Dead Instruction Elimination:")
+        .run_pass(Ssa::simplify_cfg, "After Simplifying:")
+        .run_pass(Ssa::array_set_optimization, "After Array Set Optimizations:");
+    Ok(builder)
+}
+
+/// Runs the SSA pass with the given name, which is the name of the `Ssa` method running it.
+///
+/// This allows testing a single pass on SSA parsed from text. Passes that take an inliner
+/// aggressiveness run with the default one, and the `_with_options` variants of passes keep
+/// the brillig functions which are no longer called.
+#[cfg(test)]
+pub(crate) fn run_pass(ssa: Ssa, pass: &str) -> Result<Ssa, RuntimeError> {
+    let inliner_aggressiveness = InlinerAggressiveness::default();
+    let ssa = match pass {
+        "array_set_optimization" => ssa.array_set_optimization(),
+        "as_slice_optimization" => ssa.as_slice_optimization(),
+        "dead_instruction_elimination" => ssa.dead_instruction_elimination(),
+        "dedup_constants" => ssa.dedup_constants(),
+        "defunctionalize" => ssa.defunctionalize(),
+        "evaluate_static_assert_and_assert_constant" => {
+            ssa.evaluate_static_assert_and_assert_constant()?
+        }
+        "flatten_cfg" => ssa.flatten_cfg(),
+        "fold_constants" => ssa.fold_constants(),
+        "fold_constants_using_constraints" => ssa.fold_constants_using_constraints(),
+        "inline_const_brillig_calls" => ssa
+            .inline_const_brillig_calls(inliner_aggressiveness, &mut ConstantsBudget::unlimited()),
+        "inline_const_brillig_calls_with_options" => ssa.inline_const_brillig_calls_with_options(
+            inliner_aggressiveness,
+            &mut ConstantsBudget::unlimited(),
+            true,
+        ),
+        "inline_functions" => ssa.inline_functions(inliner_aggressiveness),
+        "inline_functions_with_no_predicates" => {
+            ssa.inline_functions_with_no_predicates(inliner_aggressiveness)
+        }
+        "inline_functions_with_no_predicates_with_options" => {
+            ssa.inline_functions_with_no_predicates_with_options(inliner_aggressiveness, true)
+        }
+        "loop_invariant_code_motion" => ssa.loop_invariant_code_motion(),
+        "mem2reg" => ssa.mem2reg(),
+        "remove_bit_shifts" => ssa.remove_bit_shifts(),
+        "remove_enable_side_effects" => ssa.remove_enable_side_effects(),
+        "remove_if_else" => ssa.remove_if_else(),
+        "remove_paired_rc" => ssa.remove_paired_rc(),
+        "resolve_is_unconstrained" => ssa.resolve_is_unconstrained(),
+        "separate_runtime" => ssa.separate_runtime(),
+        "simplify_cfg" => ssa.simplify_cfg(),
+        "unroll_loops_iteratively" => Ssa::unroll_loops_iteratively(ssa)?,
+        _ => {
+            let message = format!("Unknown SSA pass `{pass}`");
+            let call_stack = ir::dfg::CallStack::new();
+            return Err(RuntimeError::InternalError(crate::errors::InternalError::General {
+                message,
+                call_stack,
+            }));
+        }
+    };
+    Ok(ssa)
+}
+
 // Helper to time SSA passes
 fn time<T>(name: &str, print_timings: bool, f: impl FnOnce() -> T) -> T {
     let start_time = chrono::Utc::now().time();
@@ -413,9 +560,76 @@ struct SsaBuilder {
     ssa: Ssa,
     print_ssa_passes: bool,
     print_codegen_timings: bool,
+    /// Where the SSA is printed after each pass if `print_ssa_passes` is true.
+    output: Box<dyn Write>,
+    /// Whether `inline_const_brillig_calls` and the inlining that follows it keep the brillig
+    /// functions which are no longer called.
+    keep_brillig_functions: bool,
+    /// Whether `inline_const_brillig_calls` is run at all.
+    inline_const_brillig_calls: bool,
+    /// Where `inline_const_brillig_calls` looks up and stores its results, if anywhere.
+    inline_const_brillig_calls_cache: Option<Arc<Mutex<InlineConstBrilligCallsCache>>>,
+    /// How many constants `inline_const_brillig_calls` may add to the program, if limited.
+    inline_const_brillig_calls_constants_limit: Option<usize>,
 }
 
 impl SsaBuilder {
+    /// Bundles an existing `Ssa`, for example one parsed from text, with the given printing
+    /// settings so that passes can be run over it as in `optimize_into_acir`.
+    fn from_ssa(ssa: Ssa, print_ssa_passes: bool, print_codegen_timings: bool) -> SsaBuilder {
+        let output = Box::new(std::io::stdout());
+        SsaBuilder {
+            ssa,
+            print_ssa_passes,
+            print_codegen_timings,
+            output,
+            keep_brillig_functions: false,
+            inline_const_brillig_calls: false,
+            inline_const_brillig_calls_cache: None,
+            inline_const_brillig_calls_constants_limit: None,
+        }
+    }
+
+    /// Sets whether `inline_const_brillig_calls` is run, which it isn't by default.
+    fn with_inline_const_brillig_calls(mut self, enabled: bool) -> SsaBuilder {
+        self.inline_const_brillig_calls = enabled;
+        self
+    }
+
+    /// Sets the cache `inline_const_brillig_calls` reuses results from, if any.
+    fn with_inline_const_brillig_calls_cache(
+        mut self,
+        cache: Option<Arc<Mutex<InlineConstBrilligCallsCache>>>,
+    ) -> SsaBuilder {
+        self.inline_const_brillig_calls_cache = cache;
+        self
+    }
+
+    /// Sets how many constants `inline_const_brillig_calls` may add to the program, if limited.
+    fn with_inline_const_brillig_calls_constants_limit(
+        mut self,
+        limit: Option<usize>,
+    ) -> SsaBuilder {
+        self.inline_const_brillig_calls_constants_limit = limit;
+        self
+    }
+
+    /// Prints the SSA to `output` rather than to stdout.
+    #[cfg(test)]
+    fn with_output(mut self, output: impl Write + 'static) -> SsaBuilder {
+        self.output = Box::new(output);
+        self
+    }
+
+    /// Keeps every brillig function when running `inline_const_brillig_calls` and the inlining
+    /// that follows it, so that the resulting SSA can be compared with the functions that were
+    /// inlined.
+    #[cfg(test)]
+    fn keep_brillig_functions(mut self) -> SsaBuilder {
+        self.keep_brillig_functions = true;
+        self
+    }
+
     fn new(
         program: Program,
         print_ssa_passes: bool,
@@ -433,7 +647,7 @@ impl SsaBuilder {
             let ssa_path = emit_ssa.with_extension("ssa.json");
             write_to_file(&serde_json::to_vec(&ssa).unwrap(), &ssa_path);
         }
-        Ok(SsaBuilder { print_ssa_passes, print_codegen_timings, ssa }.print("Initial SSA:"))
+        Ok(SsaBuilder::from_ssa(ssa, print_ssa_passes, print_codegen_timings).print("Initial SSA:"))
     }
 
     fn finish(self) -> Ssa {
@@ -450,11 +664,10 @@ impl SsaBuilder {
     }
 
     /// The same as `run_pass` but for passes that may fail
-    fn try_run_pass(
-        mut self,
-        pass: fn(Ssa) -> Result<Ssa, RuntimeError>,
-        msg: &str,
-    ) -> Result<Self, RuntimeError> {
+    fn try_run_pass<F>(mut self, pass: F, msg: &str) -> Result<Self, RuntimeError>
+    where
+        F: FnOnce(Ssa) -> Result<Ssa, RuntimeError>,
+    {
         self.ssa = time(msg, self.print_codegen_timings, || pass(self.ssa))?;
         Ok(self.print(msg))
     }
@@ -462,7 +675,7 @@ impl SsaBuilder {
     fn print(mut self, msg: &str) -> Self {
         if self.print_ssa_passes {
             self.ssa.normalize_ids();
-            println!("{msg}\n{}", self.ssa);
+            writeln!(self.output, "{msg}\n{}", self.ssa).expect("could not print the SSA");
         }
         self
     }
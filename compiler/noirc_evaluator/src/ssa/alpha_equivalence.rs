@@ -0,0 +1,160 @@
+//! Structural comparison of SSA programs for tests.
+//!
+//! Comparing the printed form of two programs is brittle since value and block ids shift
+//! whenever a pass creates or removes temporary values. [`Ssa::alpha_equivalent`] instead
+//! compares programs up to a consistent renaming of those ids.
+use super::{ir::function::Function, ssa_gen::Ssa};
+
+impl Ssa {
+    /// Returns true if both programs are equal up to a consistent renaming of their
+    /// value and block ids, and have the same entry points and error types.
+    ///
+    /// Both programs are copied and their ids normalized, so that blocks are numbered in
+    /// reverse post-order and values in the order they are defined, before being compared.
+    /// Unreachable blocks are ignored.
+    pub(crate) fn alpha_equivalent(&self, other: &Ssa) -> bool {
+        let this = self.normalized_copy();
+        let other = other.normalized_copy();
+        this.to_string() == other.to_string()
+            && this.entry_point_to_generated_index == other.entry_point_to_generated_index
+            && this.error_selector_to_type == other.error_selector_to_type
+    }
+
+    fn normalized_copy(&self) -> Ssa {
+        // `Ssa::new` expects the main function to come first
+        let main = self.main();
+        let others = self.functions.values().filter(|function| function.id() != self.main_id);
+        let functions = std::iter::once(main)
+            .chain(others)
+            .map(|function| Function::clone_with_id(function.id(), function))
+            .collect();
+
+        let mut ssa = Ssa::new(functions, self.error_selector_to_type.clone());
+        ssa.entry_point_to_generated_index = self.entry_point_to_generated_index.clone();
+        ssa.normalize_ids();
+        ssa
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::circuit::ErrorSelector;
+    use noirc_frontend::hir_def::types::Type as HirType;
+
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{instruction::BinaryOp, map::Id, types::Type},
+        ssa_gen::Ssa,
+    };
+
+    #[test]
+    fn renamed_functions_are_equivalent() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0(v0: Field, v1: u1):
+                jmpif v1 then: b2, else: b1
+              b2():
+                v3 = add v0, Field 1
+                return v3
+              b1():
+                return v0
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+
+        // Build the same function, creating the blocks and values in a different order
+        // so that they are given different ids
+        let mut builder = FunctionBuilder::new("main".into(), Id::test_new(0));
+        let one = builder.field_constant(1u128);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::bool());
+        let then_block = builder.insert_block();
+        let else_block = builder.insert_block();
+        builder.terminate_with_jmpif(v1, then_block, else_block);
+
+        builder.switch_to_block(then_block);
+        let v3 = builder.insert_binary(v0, BinaryOp::Add, one);
+        builder.terminate_with_return(vec![v3]);
+
+        builder.switch_to_block(else_block);
+        builder.terminate_with_return(vec![v0]);
+        let built = builder.finish();
+
+        assert_ne!(ssa.to_string(), built.to_string());
+        assert!(ssa.alpha_equivalent(&built));
+        assert!(built.alpha_equivalent(&ssa));
+    }
+
+    #[test]
+    fn ssa_after_a_pass_is_equivalent_to_expected() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0(v0: Field):
+                v1 = add Field 1, Field 2
+                v2 = add v0, v1
+                return v2
+            }
+            ";
+        let expected = "
+            acir(inline) fn main f0 {
+              b0(v0: Field):
+                v1 = add v0, Field 3
+                return v1
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap().fold_constants();
+        let expected = Ssa::from_str(expected).unwrap();
+
+        assert!(ssa.alpha_equivalent(&expected));
+    }
+
+    #[test]
+    fn different_functions_are_not_equivalent() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0(v0: Field, v1: Field):
+                v2 = sub v0, v1
+                return v2
+            }
+            ";
+        // The operands are swapped, which is not a consistent renaming
+        let swapped = "
+            acir(inline) fn main f0 {
+              b0(v0: Field, v1: Field):
+                v2 = sub v1, v0
+                return v2
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let swapped = Ssa::from_str(swapped).unwrap();
+
+        assert!(!ssa.alpha_equivalent(&swapped));
+    }
+
+    #[test]
+    fn programs_with_different_entry_points_or_error_types_are_not_equivalent() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0(v0: Field):
+                v2 = call f1(v0) -> Field
+                return v2
+            }
+            acir(fold) fn double f1 {
+              b0(v0: Field):
+                v1 = mul v0, Field 2
+                return v1
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+
+        let mut without_entry_point = Ssa::from_str(src).unwrap();
+        without_entry_point.entry_point_to_generated_index.remove(&Id::test_new(1));
+        assert!(!ssa.alpha_equivalent(&without_entry_point));
+
+        let mut with_error_type = Ssa::from_str(src).unwrap();
+        with_error_type.error_selector_to_type.insert(ErrorSelector::new(1), HirType::Bool);
+        assert!(!ssa.alpha_equivalent(&with_error_type));
+
+        assert!(ssa.alpha_equivalent(&Ssa::from_str(src).unwrap()));
+    }
+}
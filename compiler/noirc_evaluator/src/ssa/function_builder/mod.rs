@@ -66,6 +66,12 @@ impl FunctionBuilder {
         self.current_function.set_runtime(runtime);
     }
 
+    /// Prevents `inline_const_brillig_calls` from replacing calls to the current function
+    /// with the constants they return.
+    pub(crate) fn set_no_const_inlining(&mut self) {
+        self.current_function.set_no_const_inlining(true);
+    }
+
     /// Finish the current function and create a new function.
     ///
     /// A FunctionBuilder can always only work on one function at a time, so care
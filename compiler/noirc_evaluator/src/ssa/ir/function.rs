@@ -74,6 +74,11 @@ pub(crate) struct Function {
 
     runtime: RuntimeType,
 
+    /// If set, calls to this function are never replaced by `inline_const_brillig_calls`,
+    /// even when all of their arguments are constant.
+    #[serde(default)]
+    no_const_inlining: bool,
+
     /// The DataFlowGraph holds the majority of data pertaining to the function
     /// including its blocks, instructions, and values.
     pub(crate) dfg: DataFlowGraph,
@@ -86,20 +91,35 @@ impl Function {
     pub(crate) fn new(name: String, id: FunctionId) -> Self {
         let mut dfg = DataFlowGraph::default();
         let entry_block = dfg.make_block();
-        Self { name, id, entry_block, dfg, runtime: RuntimeType::Acir(InlineType::default()) }
+        Self {
+            name,
+            id,
+            entry_block,
+            dfg,
+            runtime: RuntimeType::Acir(InlineType::default()),
+            no_const_inlining: false,
+        }
     }
 
     /// Creates a new function as a clone of the one passed in with the passed in id.
     pub(crate) fn clone_with_id(id: FunctionId, another: &Function) -> Self {
         let dfg = another.dfg.clone();
         let entry_block = another.entry_block;
-        Self { name: another.name.clone(), id, entry_block, dfg, runtime: another.runtime }
+        Self {
+            name: another.name.clone(),
+            id,
+            entry_block,
+            dfg,
+            runtime: another.runtime,
+            no_const_inlining: another.no_const_inlining,
+        }
     }
 
     /// Takes the signature (function name & runtime) from a function but does not copy the body.
     pub(crate) fn clone_signature(id: FunctionId, another: &Function) -> Self {
         let mut new_function = Function::new(another.name.clone(), id);
         new_function.runtime = another.runtime;
+        new_function.no_const_inlining = another.no_const_inlining;
         new_function
     }
 
@@ -124,6 +144,17 @@ impl Function {
         self.runtime = runtime;
     }
 
+    /// Whether calls to this function must be left as they are by `inline_const_brillig_calls`.
+    pub(crate) fn no_const_inlining(&self) -> bool {
+        self.no_const_inlining
+    }
+
+    /// Prevents `inline_const_brillig_calls` from replacing calls to this function, for example
+    /// to keep it around for debugging or to keep its error selectors deterministic.
+    pub(crate) fn set_no_const_inlining(&mut self, no_const_inlining: bool) {
+        self.no_const_inlining = no_const_inlining;
+    }
+
     pub(crate) fn is_no_predicates(&self) -> bool {
         match self.runtime() {
             RuntimeType::Acir(inline_type) => matches!(inline_type, InlineType::NoPredicates),
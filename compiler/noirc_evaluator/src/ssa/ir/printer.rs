@@ -16,12 +16,36 @@ use super::{
 };
 
 /// Helper function for Function's Display impl to pretty-print the function with the given formatter.
+///
+/// With the alternate flag (`{:#}`), the results of every instruction are padded to the same
+/// width so that the `=` signs of the whole function line up, which makes large functions
+/// easier to diff. The output can still be parsed back.
 pub(crate) fn display_function(function: &Function, f: &mut Formatter) -> Result {
+    let results_width = if f.alternate() { results_width(function) } else { 0 };
+
     writeln!(f, "{} fn {} {} {{", function.runtime(), function.name(), function.id())?;
-    display_block_with_successors(function, function.entry_block(), &mut HashSet::new(), f)?;
+    let mut visited = HashSet::new();
+    display_block_with_successors(
+        function,
+        function.entry_block(),
+        &mut visited,
+        results_width,
+        f,
+    )?;
     write!(f, "}}")
 }
 
+/// The width of the widest list of instruction results in the given function, as printed.
+fn results_width(function: &Function) -> usize {
+    let blocks = function.reachable_blocks();
+    let instructions = blocks.into_iter().flat_map(|block| function.dfg[block].instructions());
+    instructions
+        .map(|instruction| value_list(function, function.dfg.instruction_results(*instruction)))
+        .map(|results| results.len())
+        .max()
+        .unwrap_or(0)
+}
+
 /// Displays a block followed by all of its successors recursively.
 /// This uses a HashSet to keep track of the visited blocks. Otherwise
 /// there would be infinite recursion for any loops in the IR.
@@ -29,14 +53,15 @@ pub(crate) fn display_block_with_successors(
     function: &Function,
     block_id: BasicBlockId,
     visited: &mut HashSet<BasicBlockId>,
+    results_width: usize,
     f: &mut Formatter,
 ) -> Result {
-    display_block(function, block_id, f)?;
+    display_block(function, block_id, results_width, f)?;
     visited.insert(block_id);
 
     for successor in function.dfg[block_id].successors() {
         if !visited.contains(&successor) {
-            display_block_with_successors(function, successor, visited, f)?;
+            display_block_with_successors(function, successor, visited, results_width, f)?;
         }
     }
     Ok(())
@@ -46,6 +71,7 @@ pub(crate) fn display_block_with_successors(
 pub(crate) fn display_block(
     function: &Function,
     block_id: BasicBlockId,
+    results_width: usize,
     f: &mut Formatter,
 ) -> Result {
     let block = &function.dfg[block_id];
@@ -53,7 +79,7 @@ pub(crate) fn display_block(
     writeln!(f, "  {}({}):", block_id, value_list_with_types(function, block.parameters()))?;
 
     for instruction in block.instructions() {
-        display_instruction(function, *instruction, f)?;
+        display_instruction(function, *instruction, results_width, f)?;
     }
 
     display_terminator(function, block.terminator(), f)
@@ -125,10 +151,11 @@ pub(crate) fn display_terminator(
     }
 }
 
-/// Display an arbitrary instruction
+/// Display an arbitrary instruction, padding its results to `results_width`
 pub(crate) fn display_instruction(
     function: &Function,
     instruction: InstructionId,
+    results_width: usize,
     f: &mut Formatter,
 ) -> Result {
     // instructions are always indented within a function
@@ -136,7 +163,7 @@ pub(crate) fn display_instruction(
 
     let results = function.dfg.instruction_results(instruction);
     if !results.is_empty() {
-        write!(f, "{} = ", value_list(function, results))?;
+        write!(f, "{:results_width$} = ", value_list(function, results))?;
     }
 
     display_instruction_inner(function, &function.dfg[instruction], results, f)
@@ -170,7 +197,12 @@ fn display_instruction_inner(
         }
         Instruction::Call { func, arguments } => {
             let arguments = value_list(function, arguments);
-            writeln!(f, "call {}({}){}", show(*func), arguments, result_types(function, results))
+            let result_types = result_types(function, results);
+            if let Value::ForeignFunction(name) = &function.dfg[function.dfg.resolve(*func)] {
+                writeln!(f, "foreign {name:?}({arguments}){result_types}")
+            } else {
+                writeln!(f, "call {}({}){}", show(*func), arguments, result_types)
+            }
         }
         Instruction::Allocate => {
             writeln!(f, "allocate{}", result_types(function, results))
@@ -206,8 +238,12 @@ fn display_instruction_inner(
         Instruction::DecrementRc { value } => {
             writeln!(f, "dec_rc {}", show(*value))
         }
-        Instruction::RangeCheck { value, max_bit_size, .. } => {
-            writeln!(f, "range_check {} to {} bits", show(*value), *max_bit_size,)
+        Instruction::RangeCheck { value, max_bit_size, assert_message } => {
+            write!(f, "range_check {} to {} bits", show(*value), *max_bit_size)?;
+            if let Some(assert_message) = assert_message {
+                write!(f, ", {assert_message:?}")?;
+            }
+            writeln!(f)
         }
         Instruction::IfElse { then_condition, then_value, else_value } => {
             let then_condition = show(*then_condition);
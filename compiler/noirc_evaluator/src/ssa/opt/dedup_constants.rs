@@ -0,0 +1,197 @@
+//! This file contains the pass giving identical constant arrays a single value in each function.
+//!
+//! Numeric constants are already deduplicated by the DFG, which creates a single value for each
+//! constant and type. Constant arrays, however, are built by `make_array` instructions, and
+//! `inline_const_brillig_calls` inserts one of those for each array returned by a call it
+//! replaces, so the same array can end up being built many times, possibly inside loops.
+//!
+//! For each ACIR function, this pass:
+//! - keeps the first `make_array` of each distinct constant array, in reverse post-order,
+//!   and moves it to the start of the entry block so that it dominates every use,
+//! - removes the other `make_array`s of that array, replacing their results with the kept one.
+//!
+//! Arrays whose elements are other constant arrays are handled as well, since their elements
+//! are deduplicated first. Brillig functions are left untouched: their arrays are reference
+//! counted and may be mutated in place, so sharing them could change the program's behavior.
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use crate::ssa::{
+    ir::{
+        function::{Function, RuntimeType},
+        instruction::Instruction,
+        post_order::PostOrder,
+        types::Type,
+        value::ValueId,
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// See [`dedup_constants`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn dedup_constants(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            function.dedup_constants();
+        }
+        self
+    }
+}
+
+impl Function {
+    /// Goes through each block and rebuilds its instruction list, leaving out the constant
+    /// arrays, which are then put back once each at the start of the entry block.
+    pub(crate) fn dedup_constants(&mut self) {
+        if !matches!(self.runtime(), RuntimeType::Acir(_)) {
+            return;
+        }
+
+        let mut constant_arrays: HashMap<(im::Vector<ValueId>, Type), ValueId> = HashMap::default();
+        let mut kept_arrays = HashSet::default();
+        let mut hoisted_instructions = Vec::new();
+
+        // Visiting blocks in reverse post-order means the arrays an array is made of were
+        // already visited, as their definitions dominate it.
+        let mut blocks = PostOrder::with_function(self).into_vec();
+        blocks.reverse();
+
+        for block in blocks {
+            let instructions = self.dfg[block].take_instructions();
+            let mut new_instructions = Vec::with_capacity(instructions.len());
+
+            for instruction_id in instructions {
+                let Instruction::MakeArray { elements, typ } = &self.dfg[instruction_id] else {
+                    new_instructions.push(instruction_id);
+                    continue;
+                };
+
+                let elements: im::Vector<_> =
+                    elements.iter().map(|element| self.dfg.resolve(*element)).collect();
+                let is_constant = elements.iter().all(|element| {
+                    self.dfg.get_numeric_constant(*element).is_some()
+                        || kept_arrays.contains(element)
+                });
+                if !is_constant {
+                    new_instructions.push(instruction_id);
+                    continue;
+                }
+
+                let typ = typ.clone();
+                let result = self.dfg.instruction_results(instruction_id)[0];
+                if let Some(existing) = constant_arrays.get(&(elements.clone(), typ.clone())) {
+                    self.dfg.set_value_from_id(result, *existing);
+                } else {
+                    self.dfg[instruction_id] =
+                        Instruction::MakeArray { elements: elements.clone(), typ: typ.clone() };
+                    constant_arrays.insert((elements, typ), result);
+                    kept_arrays.insert(result);
+                    hoisted_instructions.push(instruction_id);
+                }
+            }
+
+            *self.dfg[block].instructions_mut() = new_instructions;
+        }
+
+        let entry_block = self.entry_block();
+        hoisted_instructions.extend(self.dfg[entry_block].take_instructions());
+        *self.dfg[entry_block].instructions_mut() = hoisted_instructions;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ssa::{
+        opt::{
+            assert_normalized_ssa_equals, inline_const_brillig_calls::ConstantsBudget,
+            inlining::InlinerAggressiveness,
+        },
+        Ssa,
+    };
+
+    #[test]
+    fn collapses_identical_arrays_returned_by_inlined_calls() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v1 = call f1() -> [Field; 2]
+                v2 = call f1() -> [Field; 2]
+                v3 = call f1() -> [Field; 2]
+                return v1, v2, v3
+            }
+            brillig(inline) fn pair f1 {
+              b0():
+                v2 = make_array [Field 1, Field 2] : [Field; 2]
+                return v2
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        // Each replaced call built the array again
+        let main = ssa.main();
+        assert_eq!(main.dfg[main.entry_block()].instructions().len(), 3);
+
+        let ssa = ssa.dedup_constants();
+        let expected = "
+            acir(inline) fn main f0 {
+              b0():
+                v2 = make_array [Field 1, Field 2] : [Field; 2]
+                return v2, v2, v2
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+
+    #[test]
+    fn hoists_identical_arrays_of_different_blocks_to_entry_block() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0(v0: u1):
+                jmpif v0 then: b1, else: b2
+              b1():
+                v3 = make_array [Field 1, Field 2] : [Field; 2]
+                v4 = make_array [v3, v3] : [[Field; 2]; 2]
+                jmp b3(v4)
+              b2():
+                v5 = make_array [Field 1, Field 2] : [Field; 2]
+                v6 = make_array [v5, v5] : [[Field; 2]; 2]
+                jmp b3(v6)
+              b3(v7: [[Field; 2]; 2]):
+                return v7
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap().dedup_constants();
+
+        let expected = "
+            acir(inline) fn main f0 {
+              b0(v0: u1):
+                v3 = make_array [Field 1, Field 2] : [Field; 2]
+                v4 = make_array [v3, v3] : [[Field; 2]; 2]
+                jmpif v0 then: b1, else: b2
+              b1():
+                jmp b3(v4)
+              b2():
+                jmp b3(v4)
+              b3(v7: [[Field; 2]; 2]):
+                return v7
+            }
+            ";
+        assert!(ssa.alpha_equivalent(&Ssa::from_str(expected).unwrap()));
+    }
+
+    #[test]
+    fn does_not_dedup_arrays_in_brillig_functions() {
+        let src = "
+            brillig(inline) fn main f0 {
+              b0():
+                v2 = make_array [Field 1, Field 2] : [Field; 2]
+                v3 = make_array [Field 1, Field 2] : [Field; 2]
+                return v2, v3
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap().dedup_constants();
+        assert_normalized_ssa_equals(ssa, src);
+    }
+}
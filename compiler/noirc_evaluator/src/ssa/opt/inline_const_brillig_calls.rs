@@ -0,0 +1,1866 @@
+//! This pass tries to inline calls to brillig functions that have all constant arguments.
+//!
+//! Each such call is specialized: a copy of the called brillig function has its parameters
+//! replaced by the constant arguments and is then run through the rest of the SSA pipeline
+//! as an ACIR function. If that reduces the function to a single block that returns constants,
+//! the call is replaced by those constants.
+//!
+//! Brillig functions for which every call could be replaced this way are removed afterwards.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    hash::{Hash, Hasher},
+    time::{Duration, Instant},
+};
+
+use acvm::{acir::circuit::ErrorSelector, FieldElement};
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use iter_extended::vecmap;
+use noirc_frontend::{hir_def::types::Type as HirType, monomorphization::ast::InlineType};
+
+use crate::{
+    errors::RuntimeError,
+    ssa::{
+        ir::{
+            basic_block::BasicBlockId,
+            dfg::{CallStack, DataFlowGraph},
+            function::{Function, FunctionId, RuntimeType},
+            instruction::{Instruction, InstructionId, TerminatorInstruction},
+            map::AtomicCounter,
+            types::Type,
+            value::{Value, ValueId},
+        },
+        opt::inlining::InlinerAggressiveness,
+        optimize_ssa_after_inline_const_brillig_calls,
+        ssa_gen::Ssa,
+        SsaBuilder,
+    },
+};
+
+/// The maximum number of times calls are looked at again after some of them were replaced.
+const MAX_ITERATIONS: usize = 10;
+
+/// The maximum number of instructions unrolling loops may create in a specialized function.
+/// A constant argument can make a loop run for a huge number of iterations, in which case
+/// the call is left as it is rather than trying to unroll the loop for a very long time.
+const MAX_UNROLLED_INSTRUCTIONS: usize = 100_000;
+
+/// Limits the total amount of constant data `inline_const_brillig_calls` materializes
+/// at call sites, counted in numeric constants (an array counts as all of its elements).
+///
+/// Once the budget is exhausted, calls that could otherwise be replaced are left as they are.
+pub(crate) struct ConstantsBudget {
+    limit: usize,
+    used: usize,
+}
+
+impl ConstantsBudget {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self { limit, used: 0 }
+    }
+
+    pub(crate) fn unlimited() -> Self {
+        Self::new(usize::MAX)
+    }
+
+    /// How many numeric constants were materialized so far.
+    pub(crate) fn used(&self) -> usize {
+        self.used
+    }
+
+    fn remaining(&self) -> usize {
+        self.limit - self.used
+    }
+
+    /// Records that `amount` more constants will be materialized if that fits in the budget.
+    /// Returns false, recording nothing, if it doesn't.
+    fn try_spend(&mut self, amount: usize) -> bool {
+        if amount > self.remaining() {
+            return false;
+        }
+        self.used += amount;
+        true
+    }
+}
+
+/// Remembers the results of `inline_const_brillig_calls` so that running it again on an
+/// identical program, for example when recompiling a program that changed elsewhere,
+/// doesn't have to specialize every brillig call again.
+#[derive(Default)]
+pub struct InlineConstBrilligCallsCache {
+    results: HashMap<CacheKey, CachedResult>,
+    hits: usize,
+}
+
+impl InlineConstBrilligCallsCache {
+    /// How many times a cached output was returned instead of running the pass.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+/// The content of the input program (see [`Ssa::content`]), the inliner aggressiveness, the
+/// constants budget that was left and whether unused brillig functions are kept: together
+/// these determine the output of the pass.
+type CacheKey = (Vec<u8>, i64, usize, bool);
+
+struct CachedResult {
+    ssa: Ssa,
+    constants_used: usize,
+}
+
+/// A constant argument of a call, in a form that can be compared across functions.
+#[derive(PartialEq, Eq, Hash)]
+enum ConstantArgument {
+    Numeric(FieldElement, Type),
+    Array(Vec<ConstantArgument>, Type),
+}
+
+impl ConstantArgument {
+    /// Panics if `value` is not a numeric constant or a constant array.
+    fn new(dfg: &DataFlowGraph, value: ValueId) -> Self {
+        if let Some((constant, typ)) = dfg.get_numeric_constant_with_type(value) {
+            ConstantArgument::Numeric(constant, typ)
+        } else if let Some((elements, typ)) = dfg.get_array_constant(value) {
+            let elements = elements.iter().map(|element| Self::new(dfg, *element)).collect();
+            ConstantArgument::Array(elements, typ)
+        } else {
+            unreachable!("A constant should be either a numeric constant or an array constant")
+        }
+    }
+}
+
+/// For each brillig function, the constant arguments of the calls to it that couldn't be
+/// replaced, so that these aren't specialized again on later iterations of the pass.
+///
+/// The entries of a function are cleared whenever some of the calls it makes are replaced,
+/// as specializing it might succeed from then on.
+type FailedCalls = HashMap<FunctionId, HashSet<Vec<ConstantArgument>>>;
+
+/// Records everything hashed into it instead of hashing it, so that the content of programs
+/// can be compared exactly rather than through hashes that may collide.
+#[derive(Default)]
+struct ContentRecorder(Vec<u8>);
+
+impl Hasher for ContentRecorder {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        fxhash::hash64(&self.0)
+    }
+}
+
+/// Hashes `value`, after resolving it, together with what it is: its type and the instruction
+/// or block defining it, or the constant, function or intrinsic it refers to.
+fn hash_value(dfg: &DataFlowGraph, value: ValueId, hasher: &mut impl Hasher) {
+    let value = dfg.resolve(value);
+    value.hash(hasher);
+    dfg[value].hash(hasher);
+}
+
+/// Counts what happened to the instructions looked at by `inline_const_brillig_calls`,
+/// for profiling. Instructions are looked at again on every iteration of the pass, so
+/// the same instruction can be counted more than once.
+#[derive(Debug, Default)]
+struct InlineConstBrilligCallsStats {
+    /// Calls that were replaced by constants.
+    optimized: usize,
+    /// Calls to brillig functions that were left as they are, including the ones that
+    /// could be optimized but didn't fit in the constants budget.
+    cannot_optimize: usize,
+    /// Instructions that aren't calls to brillig functions.
+    not_a_brillig_call: usize,
+    /// Calls, also counted in `cannot_optimize`, that weren't specialized again because
+    /// the same call with the same constant arguments already couldn't be replaced.
+    already_failed: usize,
+    /// Total time spent optimizing specialized brillig functions.
+    optimize_time: Duration,
+}
+
+impl Ssa {
+    /// Same as `inline_const_brillig_calls_with_options`, but if the pass already ran on an
+    /// identical program with the same settings, a copy of that run's output is returned
+    /// from `cache`.
+    pub(crate) fn inline_const_brillig_calls_with_cache(
+        self,
+        inliner_aggressiveness: InlinerAggressiveness,
+        constants_budget: &mut ConstantsBudget,
+        keep_brillig_functions: bool,
+        cache: &mut InlineConstBrilligCallsCache,
+    ) -> Self {
+        let key = (
+            self.content(),
+            inliner_aggressiveness.threshold(),
+            constants_budget.remaining(),
+            keep_brillig_functions,
+        );
+        if let Some(cached) = cache.results.get(&key) {
+            cache.hits += 1;
+            constants_budget.used += cached.constants_used;
+            return cached.ssa.clone_program();
+        }
+
+        let used_before = constants_budget.used();
+        let ssa = self.inline_const_brillig_calls_with_options(
+            inliner_aggressiveness,
+            constants_budget,
+            keep_brillig_functions,
+        );
+        let constants_used = constants_budget.used() - used_before;
+
+        cache.results.insert(key, CachedResult { ssa: ssa.clone_program(), constants_used });
+        ssa
+    }
+
+    /// Returns a canonical encoding of the structure of the program together with its error
+    /// types and entry points: two programs with the same content are the same program.
+    ///
+    /// This covers everything the pass depends on or carries over to its output: the
+    /// reachable blocks of each function with their instructions, the values these use and
+    /// the call stacks of instructions and terminators, as well as the function attributes.
+    fn content(&self) -> Vec<u8> {
+        let mut recorder = ContentRecorder::default();
+        for (func_id, function) in &self.functions {
+            func_id.hash(&mut recorder);
+            function.hash_content(&mut recorder);
+        }
+        self.main_id.hash(&mut recorder);
+        self.entry_point_to_generated_index.hash(&mut recorder);
+        self.error_selector_to_type.hash(&mut recorder);
+        recorder.0
+    }
+
+    fn clone_program(&self) -> Ssa {
+        let functions: BTreeMap<_, _> = self
+            .functions
+            .iter()
+            .map(|(id, function)| (*id, Function::clone_with_id(*id, function)))
+            .collect();
+        let max_id = *functions.keys().next_back().expect("Expected at least 1 SSA function");
+
+        Ssa {
+            functions,
+            main_id: self.main_id,
+            next_id: AtomicCounter::starting_after(max_id),
+            entry_point_to_generated_index: self.entry_point_to_generated_index.clone(),
+            error_selector_to_type: self.error_selector_to_type.clone(),
+        }
+    }
+
+    /// Replaces calls to brillig functions whose arguments are all constants with the
+    /// constant values those calls return, then removes the brillig functions that are
+    /// no longer called.
+    ///
+    /// Replacing a call can make the arguments of another call constant, and a brillig
+    /// function can only be specialized once the calls it makes are gone, so this is repeated
+    /// until no more calls can be replaced (or until `MAX_ITERATIONS` is reached).
+    ///
+    /// The constants put in place of calls are taken out of `constants_budget`, which
+    /// afterwards tells how much of it was used.
+    pub(crate) fn inline_const_brillig_calls(
+        self,
+        inliner_aggressiveness: InlinerAggressiveness,
+        constants_budget: &mut ConstantsBudget,
+    ) -> Self {
+        self.inline_const_brillig_calls_with_options(
+            inliner_aggressiveness,
+            constants_budget,
+            false,
+        )
+    }
+
+    /// Same as `inline_const_brillig_calls`, but if `keep_brillig_functions` is true no
+    /// brillig function is removed, even one whose calls were all replaced. This is meant for
+    /// debugging, to compare what got inlined with the functions that were there.
+    #[tracing::instrument(
+        level = "trace",
+        skip(self, constants_budget),
+        fields(
+            optimized = tracing::field::Empty,
+            cannot_optimize = tracing::field::Empty,
+            not_a_brillig_call = tracing::field::Empty,
+            already_failed = tracing::field::Empty,
+            optimize_time = tracing::field::Empty,
+        )
+    )]
+    pub(crate) fn inline_const_brillig_calls_with_options(
+        self,
+        inliner_aggressiveness: InlinerAggressiveness,
+        constants_budget: &mut ConstantsBudget,
+        keep_brillig_functions: bool,
+    ) -> Self {
+        let (ssa, stats) = self.inline_const_brillig_calls_with_stats(
+            inliner_aggressiveness,
+            constants_budget,
+            keep_brillig_functions,
+        );
+
+        let span = tracing::Span::current();
+        span.record("optimized", stats.optimized);
+        span.record("cannot_optimize", stats.cannot_optimize);
+        span.record("not_a_brillig_call", stats.not_a_brillig_call);
+        span.record("already_failed", stats.already_failed);
+        span.record("optimize_time", tracing::field::debug(stats.optimize_time));
+
+        ssa
+    }
+
+    fn inline_const_brillig_calls_with_stats(
+        mut self,
+        inliner_aggressiveness: InlinerAggressiveness,
+        constants_budget: &mut ConstantsBudget,
+        keep_brillig_functions: bool,
+    ) -> (Self, InlineConstBrilligCallsStats) {
+        let mut stats = InlineConstBrilligCallsStats::default();
+        let mut iteration = 0;
+
+        // Replacing calls never changes the successors of a block, so the reachable blocks
+        // of each function are found once instead of on every iteration.
+        let reachable_blocks: BTreeMap<FunctionId, BTreeSet<BasicBlockId>> = self
+            .functions
+            .iter()
+            .map(|(func_id, func)| (*func_id, func.reachable_blocks()))
+            .collect();
+
+        // Collect all brillig functions so that later we can find them when processing a call instruction.
+        // Only the ones in which calls were replaced are collected again on later iterations.
+        let mut brillig_functions = BTreeMap::<FunctionId, Function>::new();
+        for (func_id, func) in &self.functions {
+            if let RuntimeType::Brillig(..) = func.runtime() {
+                let cloned_function = Function::clone_with_id(*func_id, func);
+                brillig_functions.insert(*func_id, cloned_function);
+            };
+        }
+
+        let mut failed_calls = FailedCalls::default();
+
+        let brillig_functions_we_could_not_inline = loop {
+            // Keep track of which brillig functions we couldn't completely inline: we'll remove the ones we could.
+            // Every remaining call is visited on each iteration, so only the last iteration's set matters.
+            // This is an ordered set so that compiling the same program always yields the same SSA.
+            let mut brillig_functions_we_could_not_inline = BTreeSet::new();
+
+            let mut changed_functions = Vec::new();
+            for (func_id, func) in self.functions.iter_mut() {
+                let optimized_any_call = func.inline_const_brillig_calls(
+                    &reachable_blocks[func_id],
+                    &brillig_functions,
+                    &mut brillig_functions_we_could_not_inline,
+                    &mut failed_calls,
+                    inliner_aggressiveness,
+                    &mut self.error_selector_to_type,
+                    constants_budget,
+                    &mut stats,
+                );
+                if optimized_any_call {
+                    changed_functions.push(*func_id);
+                }
+            }
+
+            iteration += 1;
+            if changed_functions.is_empty() || iteration >= MAX_ITERATIONS {
+                break brillig_functions_we_could_not_inline;
+            }
+
+            for func_id in changed_functions {
+                if let Some(brillig_function) = brillig_functions.get_mut(&func_id) {
+                    *brillig_function = Function::clone_with_id(func_id, &self.functions[&func_id]);
+                    failed_calls.remove(&func_id);
+                }
+            }
+        };
+
+        if keep_brillig_functions {
+            return (self, stats);
+        }
+
+        // A function might not be called at all but still be referenced as a value, for example
+        // when it's passed as an argument to another function. Those need to be kept too.
+        let referenced_functions = self.functions_referenced_as_values(&reachable_blocks);
+
+        // Remove the brillig functions that are no longer called
+        for func_id in brillig_functions.keys() {
+            // We never want to remove the main function (it could be `unconstrained` or it
+            // could have been turned into brillig if `--force-brillig` was given).
+            if self.main_id == *func_id {
+                continue;
+            }
+
+            // We also don't want to remove entry points
+            if self.entry_point_to_generated_index.contains_key(func_id) {
+                continue;
+            }
+
+            if brillig_functions_we_could_not_inline.contains(func_id)
+                || referenced_functions.contains(func_id)
+            {
+                continue;
+            }
+
+            self.functions.remove(func_id);
+        }
+
+        self.remove_unreachable_brillig_functions(&reachable_blocks);
+
+        (self, stats)
+    }
+
+    /// Removes the brillig functions that can no longer be reached from `main` or from an
+    /// entry point, following both calls and functions referenced as values.
+    ///
+    /// Removing a function above can leave the functions it called without any caller,
+    /// and those aren't removed above when calls to them couldn't be inlined.
+    fn remove_unreachable_brillig_functions(
+        &mut self,
+        reachable_blocks: &BTreeMap<FunctionId, BTreeSet<BasicBlockId>>,
+    ) {
+        let mut reachable_functions = BTreeSet::new();
+        let mut to_visit: Vec<FunctionId> = std::iter::once(self.main_id)
+            .chain(self.entry_point_to_generated_index.keys().copied())
+            .collect();
+
+        while let Some(func_id) = to_visit.pop() {
+            if !reachable_functions.insert(func_id) {
+                continue;
+            }
+            if let Some(function) = self.functions.get(&func_id) {
+                to_visit.extend(referenced_functions(function, &reachable_blocks[&func_id]));
+            }
+        }
+
+        self.functions.retain(|func_id, function| {
+            reachable_functions.contains(func_id)
+                || !matches!(function.runtime(), RuntimeType::Brillig(_))
+        });
+    }
+
+    /// Returns the IDs of all functions that appear as a value in an instruction or terminator
+    /// of any function in this SSA, given the reachable blocks of each function.
+    fn functions_referenced_as_values(
+        &self,
+        reachable_blocks: &BTreeMap<FunctionId, BTreeSet<BasicBlockId>>,
+    ) -> BTreeSet<FunctionId> {
+        self.functions
+            .iter()
+            .flat_map(|(func_id, function)| {
+                referenced_functions(function, &reachable_blocks[func_id])
+            })
+            .collect()
+    }
+}
+
+/// Returns the IDs of all functions that appear as a value, called or not, in an instruction
+/// or terminator of the given blocks of `function`.
+fn referenced_functions(
+    function: &Function,
+    blocks: &BTreeSet<BasicBlockId>,
+) -> BTreeSet<FunctionId> {
+    let mut referenced_functions = BTreeSet::new();
+
+    let dfg = &function.dfg;
+    let mut check_value = |value: ValueId| {
+        if let Value::Function(func_id) = &dfg[dfg.resolve(value)] {
+            referenced_functions.insert(*func_id);
+        }
+    };
+
+    for block_id in blocks {
+        let block = &dfg[*block_id];
+        for instruction_id in block.instructions() {
+            dfg[*instruction_id].for_each_value(&mut check_value);
+        }
+        if let Some(terminator) = block.terminator() {
+            terminator.for_each_value(&mut check_value);
+        }
+    }
+
+    referenced_functions
+}
+
+/// Result of trying to optimize an instruction (any instruction) in this pass.
+enum OptimizeResult {
+    /// Nothing was done because the instruction wasn't a call to a brillig function.
+    NotABrilligCall,
+    /// The instruction was a call to a brillig function, but we couldn't optimize it.
+    CannotOptimize(FunctionId),
+    /// The instruction was a call to a brillig function and we were able to optimize it,
+    /// returning the optimized function, the constant values it returned and the error
+    /// types known after optimizing it.
+    Optimized(Box<Function>, Vec<ValueId>, BTreeMap<ErrorSelector, HirType>),
+}
+
+impl Function {
+    /// Hashes this function's attributes and reachable blocks into `hasher`.
+    /// See [`Ssa::content`].
+    fn hash_content(&self, hasher: &mut impl Hasher) {
+        self.name().hash(hasher);
+        self.runtime().hash(hasher);
+        self.no_const_inlining().hash(hasher);
+        self.entry_block().hash(hasher);
+
+        let dfg = &self.dfg;
+        for block in self.reachable_blocks() {
+            block.hash(hasher);
+            for parameter in dfg[block].parameters() {
+                hash_value(dfg, *parameter, hasher);
+            }
+
+            for instruction_id in dfg[block].instructions() {
+                let instruction = &dfg[*instruction_id];
+                instruction.map_values(|value| dfg.resolve(value)).hash(hasher);
+                instruction.for_each_value(|value| hash_value(dfg, value, hasher));
+                for result in dfg.instruction_results(*instruction_id) {
+                    hash_value(dfg, *result, hasher);
+                }
+                dfg.get_call_stack(*instruction_id).hash(hasher);
+            }
+
+            let terminator = dfg[block].unwrap_terminator();
+            terminator.map_values(|value| dfg.resolve(value)).hash(hasher);
+            terminator.for_each_value(|value| hash_value(dfg, value, hasher));
+        }
+    }
+
+    /// Replaces the calls in this function to the given brillig functions that can be turned into
+    /// constants, looking only at the given reachable blocks. Returns whether any call was replaced.
+    #[allow(clippy::too_many_arguments)]
+    fn inline_const_brillig_calls(
+        &mut self,
+        reachable_blocks: &BTreeSet<BasicBlockId>,
+        brillig_functions: &BTreeMap<FunctionId, Function>,
+        brillig_functions_we_could_not_inline: &mut BTreeSet<FunctionId>,
+        failed_calls: &mut FailedCalls,
+        inliner_aggressiveness: InlinerAggressiveness,
+        error_selector_to_type: &mut BTreeMap<ErrorSelector, HirType>,
+        constants_budget: &mut ConstantsBudget,
+        stats: &mut InlineConstBrilligCallsStats,
+    ) -> bool {
+        let mut optimized_any_call = false;
+
+        for block_id in reachable_blocks.iter().copied() {
+            for instruction_id in self.dfg[block_id].take_instructions() {
+                let optimize_result = self.optimize_const_brillig_call(
+                    instruction_id,
+                    brillig_functions,
+                    failed_calls,
+                    inliner_aggressiveness,
+                    error_selector_to_type,
+                    constants_budget,
+                    stats,
+                );
+                match optimize_result {
+                    OptimizeResult::NotABrilligCall => {
+                        self.dfg[block_id].instructions_mut().push(instruction_id);
+                        stats.not_a_brillig_call += 1;
+                    }
+                    OptimizeResult::CannotOptimize(func_id) => {
+                        self.dfg[block_id].instructions_mut().push(instruction_id);
+                        brillig_functions_we_could_not_inline.insert(func_id);
+                        stats.cannot_optimize += 1;
+                    }
+                    OptimizeResult::Optimized(function, return_values, new_error_types) => {
+                        // Replace the instruction results with the constant values we got.
+                        // Any array rebuilt in this function keeps the location of the call.
+                        let current_results = self.dfg.instruction_results(instruction_id).to_vec();
+                        assert_eq!(return_values.len(), current_results.len());
+                        let call_stack = self.dfg.get_call_stack(instruction_id);
+
+                        for (current_result_id, return_value_id) in
+                            current_results.iter().zip(return_values)
+                        {
+                            let new_return_value_id = function.copy_constant_to_function(
+                                return_value_id,
+                                self,
+                                block_id,
+                                &call_stack,
+                            );
+                            self.dfg.set_value_from_id(*current_result_id, new_return_value_id);
+                        }
+
+                        // Optimizing the specialized function might have introduced error selectors
+                        // that the rest of the program doesn't know about yet.
+                        for (selector, typ) in new_error_types {
+                            error_selector_to_type.entry(selector).or_insert(typ);
+                        }
+
+                        optimized_any_call = true;
+                        stats.optimized += 1;
+                    }
+                }
+            }
+        }
+
+        optimized_any_call
+    }
+
+    /// Tries to optimize an instruction if it's a call that points to a brillig function,
+    /// and all its arguments are constant. The returned constants are taken out of
+    /// `constants_budget`, and a call that can't be optimized is recorded in `failed_calls`.
+    #[allow(clippy::too_many_arguments)]
+    fn optimize_const_brillig_call(
+        &self,
+        instruction_id: InstructionId,
+        brillig_functions: &BTreeMap<FunctionId, Function>,
+        failed_calls: &mut FailedCalls,
+        inliner_aggressiveness: InlinerAggressiveness,
+        error_selector_to_type: &BTreeMap<ErrorSelector, HirType>,
+        constants_budget: &mut ConstantsBudget,
+        stats: &mut InlineConstBrilligCallsStats,
+    ) -> OptimizeResult {
+        let instruction = &self.dfg[instruction_id];
+        let Instruction::Call { func: func_id, arguments } = instruction else {
+            return OptimizeResult::NotABrilligCall;
+        };
+
+        let func_value = &self.dfg[*func_id];
+        let Value::Function(func_id) = func_value else {
+            return OptimizeResult::NotABrilligCall;
+        };
+
+        let Some(function) = brillig_functions.get(func_id) else {
+            return OptimizeResult::NotABrilligCall;
+        };
+
+        if function.no_const_inlining() {
+            return OptimizeResult::CannotOptimize(*func_id);
+        }
+
+        // This trivially holds for a function without parameters, such as one returning
+        // configuration values, in which case there's nothing to substitute below.
+        if !arguments.iter().all(|argument| is_numeric_or_array_constant(&self.dfg, *argument)) {
+            return OptimizeResult::CannotOptimize(*func_id);
+        }
+
+        // The function is optimized in isolation, so any call it makes other than to an
+        // intrinsic (to itself, to another function or to an oracle) can't be resolved.
+        if calls_non_intrinsic_functions(function) {
+            return OptimizeResult::CannotOptimize(*func_id);
+        }
+
+        let constant_arguments =
+            vecmap(arguments, |argument| ConstantArgument::new(&self.dfg, *argument));
+        if failed_calls.get(func_id).is_some_and(|failed| failed.contains(&constant_arguments)) {
+            stats.already_failed += 1;
+            return OptimizeResult::CannotOptimize(*func_id);
+        }
+
+        let result = self.specialize_const_brillig_call(
+            function,
+            arguments,
+            inliner_aggressiveness,
+            error_selector_to_type,
+            constants_budget,
+            &mut stats.optimize_time,
+        );
+        if let OptimizeResult::CannotOptimize(_) = result {
+            failed_calls.entry(*func_id).or_default().insert(constant_arguments);
+        }
+        result
+    }
+
+    /// Specializes `function` for the given constant `arguments` and optimizes it, returning
+    /// the constants it returns if that fits in `constants_budget`. The time spent optimizing
+    /// the specialized function is added to `optimize_time`.
+    fn specialize_const_brillig_call(
+        &self,
+        function: &Function,
+        arguments: &[ValueId],
+        inliner_aggressiveness: InlinerAggressiveness,
+        error_selector_to_type: &BTreeMap<ErrorSelector, HirType>,
+        constants_budget: &mut ConstantsBudget,
+        optimize_time: &mut Duration,
+    ) -> OptimizeResult {
+        let func_id = function.id();
+
+        // The function we have is already a copy of the original function, but we need to clone
+        // it again because there might be multiple calls to the same brillig function.
+        let mut function = Function::clone_with_id(func_id, function);
+
+        // Find the entry block and remove its parameters
+        let entry_block_id = function.entry_block();
+        let entry_block = &mut function.dfg[entry_block_id];
+        let entry_block_parameters = entry_block.take_parameters();
+        let entry_block_instructions = entry_block.take_instructions();
+
+        assert_eq!(arguments.len(), entry_block_parameters.len());
+
+        // Replace the ValueId of parameters with the ValueId of arguments
+        for (parameter_id, argument_id) in entry_block_parameters.iter().zip(arguments) {
+            // Lookup the argument in the current function and insert it in the function copy
+            let new_argument_id = self.copy_constant_to_function(
+                *argument_id,
+                &mut function,
+                entry_block_id,
+                &CallStack::new(),
+            );
+            function.dfg.set_value_from_id(*parameter_id, new_argument_id);
+        }
+
+        // Any array argument was inserted as a `make_array` at the start of the entry block,
+        // so the original instructions go after them.
+        function.dfg[entry_block_id].instructions_mut().extend(entry_block_instructions);
+
+        // Try to fully optimize the function. If we can't, we can't inline its constant value.
+        let start = Instant::now();
+        let optimized = optimize(function, inliner_aggressiveness, error_selector_to_type);
+        *optimize_time += start.elapsed();
+        let Ok((mut function, error_selector_to_type)) = optimized else {
+            return OptimizeResult::CannotOptimize(func_id);
+        };
+
+        // The only instructions we can bring back to the caller are the ones creating constant arrays
+        let only_makes_arrays =
+            function.dfg[entry_block_id].instructions().iter().all(|instruction_id| {
+                matches!(function.dfg[*instruction_id], Instruction::MakeArray { .. })
+            });
+        if !only_makes_arrays {
+            return OptimizeResult::CannotOptimize(func_id);
+        }
+
+        let entry_block = &mut function.dfg[entry_block_id];
+        let terminator = entry_block.take_terminator();
+        let TerminatorInstruction::Return { return_values, call_stack: _ } = terminator else {
+            return OptimizeResult::CannotOptimize(func_id);
+        };
+
+        // Sanity check: make sure all returned values are constant
+        if !return_values
+            .iter()
+            .all(|value_id| is_numeric_or_array_constant(&function.dfg, *value_id))
+        {
+            return OptimizeResult::CannotOptimize(func_id);
+        }
+
+        // A call that doesn't fit in the budget won't fit later either, as the budget only shrinks
+        let size =
+            return_values.iter().map(|value_id| constant_size(&function.dfg, *value_id)).sum();
+        if !constants_budget.try_spend(size) {
+            return OptimizeResult::CannotOptimize(func_id);
+        }
+
+        OptimizeResult::Optimized(Box::new(function), return_values, error_selector_to_type)
+    }
+
+    /// Copies a constant from this function to another one, inserting any `make_array`
+    /// instruction needed to rebuild it at the end of the given block with the given call stack.
+    /// Only works with numeric constants and constant arrays.
+    /// Panics if the constant is not numeric or a constant array.
+    fn copy_constant_to_function(
+        &self,
+        constant: ValueId,
+        function: &mut Function,
+        block_id: BasicBlockId,
+        call_stack: &CallStack,
+    ) -> ValueId {
+        if let Some((constant, typ)) = self.dfg.get_numeric_constant_with_type(constant) {
+            function.dfg.make_constant(constant, typ)
+        } else if let Some((elements, typ)) = self.dfg.get_array_constant(constant) {
+            let elements = elements
+                .iter()
+                .map(|element| {
+                    self.copy_constant_to_function(*element, function, block_id, call_stack)
+                })
+                .collect();
+            let instruction = Instruction::MakeArray { elements, typ };
+            function
+                .dfg
+                .insert_instruction_and_results(instruction, block_id, None, call_stack.clone())
+                .first()
+        } else {
+            unreachable!("A constant should be either a numeric constant or an array constant")
+        }
+    }
+}
+
+/// Returns true if the given value is a numeric constant or an array made (recursively)
+/// only of numeric constants.
+///
+/// This is stricter than `DataFlowGraph::is_constant`, which also considers function
+/// values to be constant: those can't be copied between functions by this pass.
+fn is_numeric_or_array_constant(dfg: &DataFlowGraph, value: ValueId) -> bool {
+    if dfg.get_numeric_constant(value).is_some() {
+        true
+    } else if let Some((elements, _)) = dfg.get_array_constant(value) {
+        elements.iter().all(|element| is_numeric_or_array_constant(dfg, *element))
+    } else {
+        false
+    }
+}
+
+/// Returns how many numeric constants make up the given numeric or array constant.
+fn constant_size(dfg: &DataFlowGraph, value: ValueId) -> usize {
+    if let Some((elements, _)) = dfg.get_array_constant(value) {
+        elements.iter().map(|element| constant_size(dfg, *element)).sum()
+    } else {
+        1
+    }
+}
+
+/// Returns true if the given function has a call instruction whose target isn't an intrinsic.
+fn calls_non_intrinsic_functions(function: &Function) -> bool {
+    function.reachable_blocks().iter().any(|block_id| {
+        function.dfg[*block_id].instructions().iter().any(|instruction_id| {
+            let Instruction::Call { func, .. } = &function.dfg[*instruction_id] else {
+                return false;
+            };
+            !matches!(function.dfg[*func], Value::Intrinsic(_))
+        })
+    })
+}
+
+/// Optimizes a function by running the same passes as `optimize_into_acir`
+/// after the `inline_const_brillig_calls` pass.
+/// The function is changed to be an ACIR function so the function can potentially
+/// be optimized into a single return terminator.
+///
+/// Only the given specialized copy has its runtime changed: the function it was copied from,
+/// which could be a brillig `main` when compiling with `--force-brillig`, keeps its runtime.
+///
+/// Returns the optimized function together with the error types of the SSA it was
+/// optimized in, which include any error selector introduced while optimizing it.
+fn optimize(
+    mut function: Function,
+    inliner_aggressiveness: InlinerAggressiveness,
+    error_selector_to_type: &BTreeMap<ErrorSelector, HirType>,
+) -> Result<(Function, BTreeMap<ErrorSelector, HirType>), RuntimeError> {
+    function.set_runtime(RuntimeType::Acir(InlineType::InlineAlways));
+
+    let function_id = function.id();
+    let mut functions = BTreeMap::new();
+    functions.insert(function_id, function);
+
+    let ssa = Ssa {
+        functions,
+        main_id: function_id,
+        next_id: AtomicCounter::starting_after(function_id),
+        entry_point_to_generated_index: BTreeMap::new(),
+        error_selector_to_type: error_selector_to_type.clone(),
+    };
+
+    let builder = SsaBuilder::from_ssa(ssa, false, false);
+    let mut ssa = optimize_ssa_after_inline_const_brillig_calls(
+        builder,
+        inliner_aggressiveness,
+        Some(MAX_UNROLLED_INSTRUCTIONS),
+    )?
+    .finish();
+    let function = ssa.functions.remove(&function_id).unwrap();
+    Ok((function, ssa.error_selector_to_type))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Arc, time::Instant};
+
+    use im::vector;
+    use iter_extended::vecmap;
+    use noirc_errors::Location;
+    use noirc_frontend::{hir_def::types::Type as HirType, monomorphization::ast::InlineType};
+
+    use super::{ConstantsBudget, InlineConstBrilligCallsCache, InlinerAggressiveness};
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            dfg::CallStack,
+            function::RuntimeType,
+            instruction::{BinaryOp, ConstrainError, ErrorType, Instruction},
+            map::Id,
+            types::Type,
+        },
+        opt::assert_normalized_ssa_equals,
+        run_pass, Ssa,
+    };
+
+    #[test]
+    fn inlines_brillig_call_without_arguments() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v1 = call f1() -> Field
+                return v1
+            }
+
+            brillig(inline) fn one f1 {
+              b0():
+                v0 = add Field 2, Field 3
+                return v0
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        let expected = "
+            acir(inline) fn main f0 {
+              b0():
+                return Field 5
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+
+    #[test]
+    fn inlines_each_call_to_brillig_function_without_arguments() {
+        // Unlike in the test above, the result is only known once the loop is unrolled
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v1 = call f1() -> Field
+                v2 = call f1() -> Field
+                return v1, v2
+            }
+            brillig(inline) fn config f1 {
+              b0():
+                v0 = allocate -> &mut Field
+                store Field 0 at v0
+                jmp b1(u32 0)
+              b1(v1: u32):
+                v2 = lt v1, u32 3
+                jmpif v2 then: b3, else: b2
+              b2():
+                v3 = load v0 -> Field
+                return v3
+              b3():
+                v4 = load v0 -> Field
+                v5 = add v4, Field 2
+                store v5 at v0
+                v6 = add v1, u32 1
+                jmp b1(v6)
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        let expected = "
+            acir(inline) fn main f0 {
+              b0():
+                return Field 6, Field 6
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+
+    #[test]
+    fn does_not_inline_brillig_call_with_loop_exceeding_unroll_limit() {
+        // The constant argument makes the loop run for so many iterations that unrolling it
+        // would practically never finish
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v1 = call f1(u32 4294967295) -> Field
+                return v1
+            }
+            brillig(inline) fn sum f1 {
+              b0(v0: u32):
+                v1 = allocate -> &mut Field
+                store Field 0 at v1
+                jmp b1(u32 0)
+              b1(v2: u32):
+                v3 = lt v2, v0
+                jmpif v3 then: b3, else: b2
+              b2():
+                v4 = load v1 -> Field
+                return v4
+              b3():
+                v5 = load v1 -> Field
+                v6 = cast v2 as Field
+                v7 = add v5, v6
+                store v7 at v1
+                v8 = add v2, u32 1
+                jmp b1(v8)
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        // The call is left as it is, so the brillig function is kept too
+        assert_eq!(ssa.functions.len(), 2);
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 1);
+        assert!(matches!(main.dfg[instructions[0]], Instruction::Call { .. }));
+    }
+
+    #[test]
+    fn inlines_brillig_call_with_constant_arguments() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v3 = call f1(Field 2, Field 3) -> Field
+                return v3
+            }
+
+            brillig(inline) fn one f1 {
+              b0(v0: Field, v1: Field):
+                v2 = add v0, v1
+                return v2
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        let expected = "
+            acir(inline) fn main f0 {
+              b0():
+                return Field 5
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+
+    #[test]
+    fn inlines_brillig_call_with_array_return() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v2 = call f1(Field 2) -> [Field; 2]
+                return v2
+            }
+
+            brillig(inline) fn one f1 {
+              b0(v0: Field):
+                v2 = add v0, Field 1
+                v3 = make_array [v0, v2] : [Field; 2]
+                return v3
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        let expected = "
+            acir(inline) fn main f0 {
+              b0():
+                v2 = make_array [Field 2, Field 3] : [Field; 2]
+                return v2
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+
+    #[test]
+    fn does_not_inline_brillig_call_with_non_constant_arguments() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0(v0: Field):
+                v3 = call f1(v0, Field 3) -> Field
+                return v3
+            }
+            brillig(inline) fn one f1 {
+              b0(v0: Field, v1: Field):
+                v2 = add v0, v1
+                return v2
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+        assert_normalized_ssa_equals(ssa, src);
+    }
+
+    #[test]
+    fn inlines_dependent_brillig_calls() {
+        // `f2` can only be turned into a constant once the call it makes to `f3` is,
+        // which happens after main's call to it has already been visited once.
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v2 = call f1(Field 1) -> Field
+                v4 = call f2(v2) -> Field
+                return v4
+            }
+            brillig(inline) fn add_one f1 {
+              b0(v0: Field):
+                v2 = add v0, Field 1
+                return v2
+            }
+            brillig(inline) fn add_six f2 {
+              b0(v0: Field):
+                v3 = call f3(Field 2) -> Field
+                v4 = add v0, v3
+                return v4
+            }
+            brillig(inline) fn triple f3 {
+              b0(v0: Field):
+                v2 = mul v0, Field 3
+                return v2
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        let expected = "
+            acir(inline) fn main f0 {
+              b0():
+                return Field 8
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+
+    #[test]
+    fn keeps_brillig_function_passed_as_argument() {
+        // fn main f0 {
+        //   b0():
+        //     v2 = call f1(f2)
+        //     v3 = call f2()
+        //     return v2, v3
+        // }
+        // brillig fn apply f1 {
+        //   b0(v0: function):
+        //     v1 = call v0()
+        //     return v1
+        // }
+        // brillig fn two f2 {
+        //   b0():
+        //     return Field 2
+        // }
+        //
+        // The call to `f2` in main can be inlined, but `f2` is still referenced
+        // as an argument to `f1` so it must not be removed.
+        let main_id = Id::test_new(0);
+        let apply_id = Id::test_new(1);
+        let two_id = Id::test_new(2);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id);
+        let apply = builder.import_function(apply_id);
+        let two = builder.import_function(two_id);
+        let v2 = builder.insert_call(apply, vec![two], vec![Type::field()]).to_vec();
+        let v3 = builder.insert_call(two, Vec::new(), vec![Type::field()]).to_vec();
+        builder.terminate_with_return(vec![v2[0], v3[0]]);
+
+        builder.new_brillig_function("apply".into(), apply_id, InlineType::default());
+        let v0 = builder.add_parameter(Type::Function);
+        let v1 = builder.insert_call(v0, Vec::new(), vec![Type::field()]).to_vec();
+        builder.terminate_with_return(v1);
+
+        builder.new_brillig_function("two".into(), two_id, InlineType::default());
+        let two_constant = builder.field_constant(2_u128);
+        builder.terminate_with_return(vec![two_constant]);
+
+        let ssa = builder.finish();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        assert_eq!(ssa.functions.len(), 3);
+        assert!(ssa.functions.contains_key(&apply_id));
+        assert!(ssa.functions.contains_key(&two_id));
+
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 1);
+    }
+
+    #[test]
+    fn does_not_inline_call_to_function_marked_as_no_const_inlining() {
+        let main_id = Id::test_new(0);
+        let one_id = Id::test_new(1);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id);
+        let one = builder.import_function(one_id);
+        let v1 = builder.insert_call(one, Vec::new(), vec![Type::field()]).to_vec();
+        builder.terminate_with_return(v1);
+
+        builder.new_brillig_function("one".into(), one_id, InlineType::default());
+        builder.set_no_const_inlining();
+        let one_constant = builder.field_constant(1_u128);
+        builder.terminate_with_return(vec![one_constant]);
+
+        let ssa = builder.finish();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        // The call is kept, and so is the function it calls
+        assert_eq!(ssa.functions.len(), 2);
+        assert!(ssa.functions[&one_id].no_const_inlining());
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 1);
+        assert!(matches!(main.dfg[instructions[0]], Instruction::Call { .. }));
+    }
+
+    #[test]
+    fn stops_inlining_when_constants_budget_is_exhausted() {
+        // Each call returns two constants, so with a budget of five
+        // only the first two calls can be replaced.
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v2 = call f1(Field 1) -> [Field; 2]
+                v4 = call f1(Field 2) -> [Field; 2]
+                v6 = call f1(Field 3) -> [Field; 2]
+                return v2, v4, v6
+            }
+            brillig(inline) fn pair f1 {
+              b0(v0: Field):
+                v1 = make_array [v0, v0] : [Field; 2]
+                return v1
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+
+        let mut constants_budget = ConstantsBudget::new(5);
+        let ssa = ssa
+            .inline_const_brillig_calls(InlinerAggressiveness::WhenCheaper, &mut constants_budget);
+        assert_eq!(constants_budget.used(), 4);
+
+        // The last call is kept, and so is the function it calls
+        assert_eq!(ssa.functions.len(), 2);
+
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 3);
+        assert!(matches!(main.dfg[instructions[2]], Instruction::Call { .. }));
+    }
+
+    #[test]
+    fn keeps_error_types_of_brillig_function_with_failing_assertion() {
+        // fn main f0 {
+        //   b0():
+        //     v2 = call f1(Field 2)
+        //     return v2
+        // }
+        // brillig fn assert_one f1 {
+        //   b0(v0: Field):
+        //     constrain v0 == Field 1, <error of type Field>
+        //     return v0
+        // }
+        let main_id = Id::test_new(0);
+        let assert_one_id = Id::test_new(1);
+
+        let error_type = HirType::FieldElement;
+        let selector = ErrorType::Dynamic(error_type.clone()).selector();
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id);
+        let assert_one = builder.import_function(assert_one_id);
+        let two = builder.field_constant(2_u128);
+        let v2 = builder.insert_call(assert_one, vec![two], vec![Type::field()]).to_vec();
+        builder.terminate_with_return(v2);
+
+        builder.new_brillig_function("assert_one".into(), assert_one_id, InlineType::default());
+        let v0 = builder.add_parameter(Type::field());
+        let one = builder.field_constant(1_u128);
+        let error = ConstrainError::Dynamic(selector, false, vec![v0]);
+        builder.insert_constrain(v0, one, Some(error));
+        builder.record_error_type(selector, error_type);
+        builder.terminate_with_return(vec![v0]);
+
+        let ssa = builder.finish();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        // The assertion fails for the given argument so the call is kept...
+        assert_eq!(ssa.functions.len(), 2);
+        let main = ssa.main();
+        assert_eq!(main.dfg[main.entry_block()].instructions().len(), 1);
+
+        // ...and the type of the error it can fail with is still known
+        assert_eq!(ssa.error_selector_to_type.get(&selector), Some(&HirType::FieldElement));
+    }
+
+    #[test]
+    fn inlines_brillig_calls_in_function_with_many_blocks() {
+        // `main` is a chain of 201 blocks, each of the first 200 calling `double` with a
+        // different constant and adding the result to a running sum
+        let main_id = Id::test_new(0);
+        let double_id = Id::test_new(1);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id);
+        let double = builder.import_function(double_id);
+        let mut sum = builder.field_constant(0_u128);
+        for i in 0..200_u128 {
+            let argument = builder.field_constant(i);
+            let result = builder.insert_call(double, vec![argument], vec![Type::field()])[0];
+            sum = builder.insert_binary(sum, BinaryOp::Add, result);
+
+            let next_block = builder.insert_block();
+            builder.terminate_with_jmp(next_block, vec![]);
+            builder.switch_to_block(next_block);
+        }
+        builder.terminate_with_return(vec![sum]);
+
+        builder.new_brillig_function("double".into(), double_id, InlineType::default());
+        let v0 = builder.add_parameter(Type::field());
+        let two = builder.field_constant(2_u128);
+        let v2 = builder.insert_binary(v0, BinaryOp::Mul, two);
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        // Every call was replaced, so the brillig function is gone
+        assert_eq!(ssa.functions.len(), 1);
+
+        let main = ssa.main();
+        let blocks = main.reachable_blocks();
+        assert_eq!(blocks.len(), 201);
+        for block in blocks {
+            for instruction in main.dfg[block].instructions() {
+                assert!(!matches!(main.dfg[*instruction], Instruction::Call { .. }));
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_error_selectors_ordered_and_deterministic() {
+        // fn main f0 {
+        //   b0():
+        //     v2 = call f1(Field 2)
+        //     v4 = call f2(Field 2)
+        //     return v2, v4
+        // }
+        // brillig fn assert_one f1 {
+        //   b0(v0: Field):
+        //     constrain v0 == Field 1, <error of type Field>
+        //     return v0
+        // }
+        // brillig fn assert_two f2 {
+        //   b0(v0: Field):
+        //     constrain v0 == Field 2, <error of type bool>
+        //     return v0
+        // }
+        let main_id = Id::test_new(0);
+        let assert_one_id = Id::test_new(1);
+        let assert_two_id = Id::test_new(2);
+
+        let field_selector = ErrorType::Dynamic(HirType::FieldElement).selector();
+        let bool_selector = ErrorType::Dynamic(HirType::Bool).selector();
+
+        let build_ssa = || {
+            let mut builder = FunctionBuilder::new("main".into(), main_id);
+            let assert_one = builder.import_function(assert_one_id);
+            let assert_two = builder.import_function(assert_two_id);
+            let two = builder.field_constant(2_u128);
+            let v2 = builder.insert_call(assert_one, vec![two], vec![Type::field()])[0];
+            let v4 = builder.insert_call(assert_two, vec![two], vec![Type::field()])[0];
+            builder.terminate_with_return(vec![v2, v4]);
+
+            for (name, id, expected, selector, error_type) in [
+                ("assert_one", assert_one_id, 1_u128, field_selector, HirType::FieldElement),
+                ("assert_two", assert_two_id, 2_u128, bool_selector, HirType::Bool),
+            ] {
+                builder.new_brillig_function(name.into(), id, InlineType::default());
+                let v0 = builder.add_parameter(Type::field());
+                let expected = builder.field_constant(expected);
+                let error = ConstrainError::Dynamic(selector, false, vec![v0]);
+                builder.insert_constrain(v0, expected, Some(error));
+                builder.record_error_type(selector, error_type);
+                builder.terminate_with_return(vec![v0]);
+            }
+
+            builder.finish()
+        };
+        let run = || {
+            build_ssa().inline_const_brillig_calls(
+                InlinerAggressiveness::WhenCheaper,
+                &mut ConstantsBudget::unlimited(),
+            )
+        };
+
+        let first = run();
+        let second = run();
+
+        // Only the call to `assert_two` could be replaced...
+        assert_eq!(first.functions.len(), 2);
+
+        // ...but every selector is still there, once, in order
+        let mut expected_selectors = vec![field_selector, bool_selector];
+        expected_selectors.sort();
+        let selectors: Vec<_> = first.error_selector_to_type.keys().cloned().collect();
+        assert_eq!(selectors, expected_selectors);
+
+        assert_eq!(first.error_selector_to_type, second.error_selector_to_type);
+        assert_eq!(first.to_string(), second.to_string());
+    }
+
+    #[test]
+    fn returns_cached_result_for_identical_program() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v2 = call f1(Field 1) -> Field
+                v4 = call f2(v2) -> Field
+                return v4
+            }
+            brillig(inline) fn add_one f1 {
+              b0(v0: Field):
+                v2 = add v0, Field 1
+                return v2
+            }
+            brillig(inline) fn add_six f2 {
+              b0(v0: Field):
+                v3 = call f3(Field 2) -> Field
+                v4 = add v0, v3
+                return v4
+            }
+            brillig(inline) fn triple f3 {
+              b0(v0: Field):
+                v2 = mul v0, Field 3
+                return v2
+            }
+            ";
+        let mut cache = InlineConstBrilligCallsCache::default();
+        let run = |ssa: Ssa, cache: &mut InlineConstBrilligCallsCache| {
+            ssa.inline_const_brillig_calls_with_cache(
+                InlinerAggressiveness::WhenCheaper,
+                &mut ConstantsBudget::unlimited(),
+                false,
+                cache,
+            )
+        };
+
+        let first = run(Ssa::from_str(src).unwrap(), &mut cache);
+        assert_eq!(cache.hits(), 0);
+
+        let second = run(Ssa::from_str(src).unwrap(), &mut cache);
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(first.to_string(), second.to_string());
+        assert_eq!(first.error_selector_to_type, second.error_selector_to_type);
+
+        // Programs printed the same way but with different call stacks or function flags
+        // aren't the same program
+        let mut ssa = Ssa::from_str(src).unwrap();
+        let main = ssa.main_mut();
+        let call = main.dfg[main.entry_block()].instructions()[0];
+        main.dfg.add_location(call, Location::dummy());
+        run(ssa, &mut cache);
+        assert_eq!(cache.hits(), 1);
+
+        let mut ssa = Ssa::from_str(src).unwrap();
+        ssa.functions.get_mut(&Id::test_new(1)).unwrap().set_no_const_inlining(true);
+        run(ssa, &mut cache);
+        assert_eq!(cache.hits(), 1);
+    }
+
+    #[test]
+    fn cache_hit_is_faster_than_running_the_pass() {
+        // Many calls with different constant arguments, each of which is specialized and
+        // optimized when the pass runs
+        let calls = 50;
+        let results = vecmap(0..calls, |i| format!("v{}", i + 10));
+        let call_instructions = vecmap(results.iter().enumerate(), |(i, result)| {
+            format!("{result} = call f1(Field {i}) -> Field")
+        });
+        let src = format!(
+            "
+            acir(inline) fn main f0 {{
+              b0():
+                {}
+                return {}
+            }}
+            brillig(inline) fn square_plus_one f1 {{
+              b0(v0: Field):
+                v1 = mul v0, v0
+                v3 = add v1, Field 1
+                return v3
+            }}
+            ",
+            call_instructions.join("\n"),
+            results.join(", ")
+        );
+
+        let mut cache = InlineConstBrilligCallsCache::default();
+        let mut run = |ssa: Ssa| {
+            let start = Instant::now();
+            let ssa = ssa.inline_const_brillig_calls_with_cache(
+                InlinerAggressiveness::WhenCheaper,
+                &mut ConstantsBudget::unlimited(),
+                false,
+                &mut cache,
+            );
+            (ssa, start.elapsed())
+        };
+
+        let (first, first_run_time) = run(Ssa::from_str(&src).unwrap());
+        let (second, second_run_time) = run(Ssa::from_str(&src).unwrap());
+        assert_eq!(first.to_string(), second.to_string());
+        assert_eq!(first.main().dfg[first.main().entry_block()].instructions().len(), 0);
+        assert!(
+            second_run_time < first_run_time,
+            "Expected the cached run ({second_run_time:?}) to be faster than the first one ({first_run_time:?})"
+        );
+    }
+
+    #[test]
+    fn produces_the_same_ssa_when_run_twice() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0(v0: Field):
+                v3 = call f1(v0, Field 3) -> Field
+                v5 = call f2(Field 2) -> Field
+                v6 = call f3(v5) -> Field
+                v7 = call f4(v0) -> Field
+                v8 = add v3, v6
+                v9 = add v8, v7
+                return v9
+            }
+            brillig(inline) fn add_values f1 {
+              b0(v0: Field, v1: Field):
+                v2 = add v0, v1
+                return v2
+            }
+            brillig(inline) fn double f2 {
+              b0(v0: Field):
+                v2 = mul v0, Field 2
+                return v2
+            }
+            brillig(inline) fn square f3 {
+              b0(v0: Field):
+                v1 = mul v0, v0
+                return v1
+            }
+            brillig(inline) fn negate f4 {
+              b0(v0: Field):
+                v2 = sub Field 0, v0
+                return v2
+            }
+            ";
+
+        let first = Ssa::from_str(src).unwrap();
+        let first = first.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        let second = Ssa::from_str(src).unwrap();
+        let second = second.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        assert_eq!(first.to_string(), second.to_string());
+        assert_eq!(first.functions.len(), 3);
+    }
+
+    #[test]
+    fn counts_instructions_by_outcome() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0(v0: Field):
+                v2 = call f1(Field 1) -> Field
+                v3 = call f1(v0) -> Field
+                v4 = add v2, v3
+                return v4
+            }
+            brillig(inline) fn add_one f1 {
+              b0(v0: Field):
+                v2 = add v0, Field 1
+                return v2
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let (_, stats) = ssa.inline_const_brillig_calls_with_stats(
+            0.into(),
+            &mut ConstantsBudget::unlimited(),
+            false,
+        );
+
+        // The first iteration replaces one call, so every remaining instruction is looked at twice
+        assert_eq!(stats.optimized, 1);
+        assert_eq!(stats.cannot_optimize, 2);
+        assert_eq!(stats.not_a_brillig_call, 4);
+    }
+
+    #[test]
+    fn does_not_specialize_failed_call_again() {
+        // `f2` can't be turned into a constant for `Field 1`. The call to `f1` is replaced
+        // so the calls are looked at again, but `f2` isn't specialized for `Field 1` again.
+        let src = "
+            acir(inline) fn main f0 {
+              b0(v0: Field):
+                v2 = call f1(Field 1) -> Field
+                v3 = call f2(Field 1) -> Field
+                v4 = add v2, v3
+                return v4
+            }
+            brillig(inline) fn add_one f1 {
+              b0(v0: Field):
+                v2 = add v0, Field 1
+                return v2
+            }
+            brillig(inline) fn assert_two f2 {
+              b0(v0: Field):
+                constrain v0 == Field 2
+                return v0
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let (ssa, stats) = ssa.inline_const_brillig_calls_with_stats(
+            0.into(),
+            &mut ConstantsBudget::unlimited(),
+            false,
+        );
+
+        assert_eq!(stats.optimized, 1);
+        assert_eq!(stats.cannot_optimize, 2);
+        assert_eq!(stats.already_failed, 1);
+        assert_eq!(ssa.functions.len(), 2);
+    }
+
+    #[test]
+    fn inlines_brillig_call_with_loop_bound_computed_from_argument() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v1 = call f1(u32 2) -> Field
+                return v1
+            }
+            brillig(inline) fn count f1 {
+              b0(v0: u32):
+                v1 = allocate -> &mut Field
+                store Field 0 at v1
+                v4 = add v0, u32 1
+                jmp b1(u32 0)
+              b1(v2: u32):
+                v5 = lt v2, v4
+                jmpif v5 then: b3, else: b2
+              b2():
+                v6 = load v1 -> Field
+                return v6
+              b3():
+                v7 = load v1 -> Field
+                v8 = add v7, Field 1
+                store v8 at v1
+                v9 = add v2, u32 1
+                jmp b1(v9)
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        let expected = "
+            acir(inline) fn main f0 {
+              b0():
+                return Field 3
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+
+    #[test]
+    fn removes_brillig_functions_orphaned_by_removing_their_caller() {
+        // `f3` is never called so it's removed, which leaves nothing calling `f2`.
+        // The call to `f2` in `f3` can't be inlined, so `f2` is only removed
+        // by finding it's no longer reachable from `main`.
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v2 = call f1(Field 2) -> Field
+                return v2
+            }
+            brillig(inline) fn double f1 {
+              b0(v0: Field):
+                v1 = mul v0, Field 2
+                return v1
+            }
+            brillig(inline) fn square f2 {
+              b0(v0: Field):
+                v1 = mul v0, v0
+                return v1
+            }
+            brillig(inline) fn unused f3 {
+              b0(v0: Field):
+                v1 = call f2(v0) -> Field
+                return v1
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        let expected = "
+            acir(inline) fn main f0 {
+              b0():
+                return Field 4
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+
+    #[test]
+    fn keeps_runtime_of_brillig_main() {
+        // This is what main looks like when compiling with `--force-brillig`
+        let src = "
+            brillig(inline) fn main f0 {
+              b0():
+                v2 = call f1(Field 2) -> Field
+                return v2
+            }
+            brillig(inline) fn double f1 {
+              b0(v0: Field):
+                v1 = mul v0, Field 2
+                return v1
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+        assert_eq!(ssa.main().runtime(), RuntimeType::Brillig(InlineType::Inline));
+
+        let expected = "
+            brillig(inline) fn main f0 {
+              b0():
+                return Field 4
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+
+    #[test]
+    fn named_inliner_aggressiveness_levels_behave_as_their_thresholds() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v2 = call f1(Field 2) -> Field
+                v3 = call f2(v2) -> Field
+                return v3
+            }
+            brillig(inline) fn double f1 {
+              b0(v0: Field):
+                v1 = mul v0, Field 2
+                return v1
+            }
+            brillig(inline) fn square f2 {
+              b0(v0: Field):
+                v1 = call f1(v0) -> Field
+                v2 = mul v1, v1
+                return v2
+            }
+            ";
+        for (level, threshold) in [
+            (InlinerAggressiveness::Never, i64::MIN),
+            (InlinerAggressiveness::WhenCheaper, 0),
+            (InlinerAggressiveness::Always, i64::MAX),
+        ] {
+            let named = Ssa::from_str(src)
+                .unwrap()
+                .inline_const_brillig_calls(level, &mut ConstantsBudget::unlimited());
+            let numeric = Ssa::from_str(src).unwrap().inline_const_brillig_calls(
+                InlinerAggressiveness::Custom(threshold),
+                &mut ConstantsBudget::unlimited(),
+            );
+            assert!(named.alpha_equivalent(&numeric), "{level:?} differs from {threshold}");
+        }
+    }
+
+    #[test]
+    fn runs_pass_by_name() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                v2 = call f1(Field 2) -> Field
+                return v2
+            }
+            brillig(inline) fn double f1 {
+              b0(v0: Field):
+                v1 = mul v0, Field 2
+                return v1
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = run_pass(ssa, "inline_const_brillig_calls").unwrap();
+
+        let expected = "
+            acir(inline) fn main f0 {
+              b0():
+                return Field 4
+            }
+            ";
+        assert_normalized_ssa_equals(ssa, expected);
+
+        let ssa = Ssa::from_str(src).unwrap();
+        let ssa = run_pass(ssa, "inline_const_brillig_calls_with_options").unwrap();
+        assert_eq!(ssa.functions.len(), 2);
+    }
+
+    #[test]
+    fn errors_on_unknown_pass_name() {
+        let src = "
+            acir(inline) fn main f0 {
+              b0():
+                return
+            }
+            ";
+        let ssa = Ssa::from_str(src).unwrap();
+        assert!(run_pass(ssa, "not_a_pass").is_err());
+    }
+
+    #[test]
+    fn inlined_array_keeps_location_of_call() {
+        // fn main f0 {
+        //   b0():
+        //     v1 = call f1() -> [Field; 2]    (with a location)
+        //     return v1
+        // }
+        // brillig fn pair f1 {
+        //   b0():
+        //     v2 = make_array [Field 1, Field 2] : [Field; 2]
+        //     return v2
+        // }
+        let main_id = Id::test_new(0);
+        let pair_id = Id::test_new(1);
+        let array_type = Type::Array(Arc::new(vec![Type::field()]), 2);
+        let call_stack = vector![Location::dummy()];
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id);
+        let pair = builder.import_function(pair_id);
+        builder.set_call_stack(call_stack.clone());
+        let v1 = builder.insert_call(pair, Vec::new(), vec![array_type.clone()]).to_vec();
+        builder.set_call_stack(CallStack::new());
+        builder.terminate_with_return(v1);
+
+        builder.new_brillig_function("pair".into(), pair_id, InlineType::default());
+        let one = builder.field_constant(1_u128);
+        let two = builder.field_constant(2_u128);
+        let v2 = builder.insert_make_array(vector![one, two], array_type);
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish();
+        let ssa = ssa.inline_const_brillig_calls(
+            InlinerAggressiveness::WhenCheaper,
+            &mut ConstantsBudget::unlimited(),
+        );
+
+        // The call was replaced by a `make_array`, which has the call's location
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 1);
+        assert!(matches!(main.dfg[instructions[0]], Instruction::MakeArray { .. }));
+        assert_eq!(main.dfg.get_call_stack(instructions[0]), call_stack);
+    }
+}
@@ -24,6 +24,57 @@ use fxhash::FxHashMap as HashMap;
 /// frames at any point in time.
 const RECURSION_LIMIT: u32 = 1000;
 
+/// How eagerly brillig functions are inlined into their callers.
+///
+/// A function is inlined when the cost of inlining it, minus the cost of keeping it as a
+/// separate function, is lower than a threshold: a more aggressive inliner generates larger
+/// but more optimized programs, a less aggressive one smaller programs. Functions that must
+/// be inlined, such as `#[inline(always)]` ones, are inlined regardless of this setting.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InlinerAggressiveness {
+    /// Only inline the functions that must be inlined.
+    Never,
+    /// Inline a function when that is cheaper than keeping it.
+    WhenCheaper,
+    /// Inline every function that can be inlined. This is the default when compiling.
+    #[default]
+    Always,
+    /// Inline a function when the cost of inlining it minus the cost of keeping it
+    /// is lower than the given threshold.
+    Custom(i64),
+}
+
+impl InlinerAggressiveness {
+    /// The threshold the difference between the inline and retain costs of a function
+    /// is compared against.
+    pub fn threshold(self) -> i64 {
+        match self {
+            InlinerAggressiveness::Never => i64::MIN,
+            InlinerAggressiveness::WhenCheaper => 0,
+            InlinerAggressiveness::Always => i64::MAX,
+            InlinerAggressiveness::Custom(threshold) => threshold,
+        }
+    }
+}
+
+/// Thresholds matching a named level are turned into that level.
+impl From<i64> for InlinerAggressiveness {
+    fn from(threshold: i64) -> Self {
+        match threshold {
+            i64::MIN => InlinerAggressiveness::Never,
+            0 => InlinerAggressiveness::WhenCheaper,
+            i64::MAX => InlinerAggressiveness::Always,
+            threshold => InlinerAggressiveness::Custom(threshold),
+        }
+    }
+}
+
+impl From<InlinerAggressiveness> for i64 {
+    fn from(aggressiveness: InlinerAggressiveness) -> Self {
+        aggressiveness.threshold()
+    }
+}
+
 impl Ssa {
     /// Inline all functions within the IR.
     ///
@@ -44,22 +95,40 @@ impl Ssa {
     ///
     /// This step should run after runtime separation, since it relies on the runtime of the called functions being final.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub(crate) fn inline_functions(self, aggressiveness: i64) -> Ssa {
-        Self::inline_functions_inner(self, aggressiveness, false)
+    pub(crate) fn inline_functions(self, aggressiveness: InlinerAggressiveness) -> Ssa {
+        Self::inline_functions_inner(self, aggressiveness.threshold(), false, false)
     }
 
     // Run the inlining pass where functions marked with `InlineType::NoPredicates` as not entry points
-    pub(crate) fn inline_functions_with_no_predicates(self, aggressiveness: i64) -> Ssa {
-        Self::inline_functions_inner(self, aggressiveness, true)
+    pub(crate) fn inline_functions_with_no_predicates(
+        self,
+        aggressiveness: InlinerAggressiveness,
+    ) -> Ssa {
+        self.inline_functions_with_no_predicates_with_options(aggressiveness, false)
+    }
+
+    /// Same as `inline_functions_with_no_predicates`, but if `keep_brillig_functions` is true
+    /// every brillig function is kept, even one which is no longer called.
+    pub(crate) fn inline_functions_with_no_predicates_with_options(
+        self,
+        aggressiveness: InlinerAggressiveness,
+        keep_brillig_functions: bool,
+    ) -> Ssa {
+        Self::inline_functions_inner(self, aggressiveness.threshold(), true, keep_brillig_functions)
     }
 
     fn inline_functions_inner(
         mut self,
         aggressiveness: i64,
         inline_no_predicates_functions: bool,
+        keep_brillig_functions: bool,
     ) -> Ssa {
-        let inline_sources =
-            get_functions_to_inline_into(&self, inline_no_predicates_functions, aggressiveness);
+        let inline_sources = get_functions_to_inline_into(
+            &self,
+            inline_no_predicates_functions,
+            keep_brillig_functions,
+            aggressiveness,
+        );
         self.functions = btree_map(&inline_sources, |entry_point| {
             let new_function = InlineContext::new(
                 &self,
@@ -161,10 +230,12 @@ fn called_functions(func: &Function) -> BTreeSet<FunctionId> {
 fn get_functions_to_inline_into(
     ssa: &Ssa,
     inline_no_predicates_functions: bool,
+    keep_brillig_functions: bool,
     aggressiveness: i64,
 ) -> BTreeSet<FunctionId> {
     let mut brillig_entry_points = BTreeSet::default();
     let mut acir_entry_points = BTreeSet::default();
+    let mut kept_brillig_functions = BTreeSet::default();
 
     if matches!(ssa.main().runtime(), RuntimeType::Brillig(_)) {
         brillig_entry_points.insert(ssa.main_id);
@@ -174,6 +245,9 @@ fn get_functions_to_inline_into(
 
     for (func_id, function) in ssa.functions.iter() {
         if matches!(function.runtime(), RuntimeType::Brillig(_)) {
+            if keep_brillig_functions {
+                kept_brillig_functions.insert(*func_id);
+            }
             continue;
         }
 
@@ -205,6 +279,7 @@ fn get_functions_to_inline_into(
         .into_iter()
         .chain(brillig_entry_points)
         .chain(brillig_functions_to_retain)
+        .chain(kept_brillig_functions)
         .collect()
 }
 
@@ -810,6 +885,7 @@ mod test {
     use acvm::{acir::AcirField, FieldElement};
     use noirc_frontend::monomorphization::ast::InlineType;
 
+    use super::InlinerAggressiveness;
     use crate::ssa::{
         function_builder::FunctionBuilder,
         ir::{
@@ -848,7 +924,7 @@ mod test {
         let ssa = builder.finish();
         assert_eq!(ssa.functions.len(), 2);
 
-        let inlined = ssa.inline_functions(i64::MAX);
+        let inlined = ssa.inline_functions(InlinerAggressiveness::Always);
         assert_eq!(inlined.functions.len(), 1);
     }
 
@@ -914,7 +990,7 @@ mod test {
         let ssa = builder.finish();
         assert_eq!(ssa.functions.len(), 4);
 
-        let inlined = ssa.inline_functions(i64::MAX);
+        let inlined = ssa.inline_functions(InlinerAggressiveness::Always);
         assert_eq!(inlined.functions.len(), 1);
     }
 
@@ -988,7 +1064,7 @@ mod test {
         //   b6():
         //     return Field 120
         // }
-        let inlined = ssa.inline_functions(i64::MAX);
+        let inlined = ssa.inline_functions(InlinerAggressiveness::Always);
         assert_eq!(inlined.functions.len(), 1);
 
         let main = inlined.main();
@@ -1071,7 +1147,7 @@ mod test {
         builder.switch_to_block(join_block);
         builder.terminate_with_return(vec![join_param]);
 
-        let ssa = builder.finish().inline_functions(i64::MAX);
+        let ssa = builder.finish().inline_functions(InlinerAggressiveness::Always);
         // Expected result:
         // fn main f3 {
         //   b0(v0: u1):
@@ -1108,7 +1184,7 @@ mod test {
         let ssa = builder.finish();
         assert_eq!(ssa.functions.len(), 1);
 
-        let inlined = ssa.inline_functions(i64::MAX);
+        let inlined = ssa.inline_functions(InlinerAggressiveness::Always);
         assert_eq!(inlined.functions.len(), 0);
     }
 
@@ -1140,7 +1216,7 @@ mod test {
         let ssa = builder.finish();
         assert_eq!(ssa.functions.len(), 2);
 
-        let inlined = ssa.inline_functions(i64::MIN);
+        let inlined = ssa.inline_functions(InlinerAggressiveness::Never);
         // No inlining has happened
         assert_eq!(inlined.functions.len(), 2);
     }
@@ -1196,8 +1272,25 @@ mod test {
         let ssa = builder.finish();
         assert_eq!(ssa.functions.len(), 2);
 
-        let inlined = ssa.inline_functions(0);
+        let inlined = ssa.inline_functions(InlinerAggressiveness::WhenCheaper);
         // No inlining has happened
         assert_eq!(inlined.functions.len(), 2);
     }
+
+    #[test]
+    fn inliner_aggressiveness_levels_map_to_thresholds() {
+        assert_eq!(InlinerAggressiveness::Never.threshold(), i64::MIN);
+        assert_eq!(InlinerAggressiveness::WhenCheaper.threshold(), 0);
+        assert_eq!(InlinerAggressiveness::Always.threshold(), i64::MAX);
+        assert_eq!(InlinerAggressiveness::Custom(42).threshold(), 42);
+        assert_eq!(InlinerAggressiveness::default(), InlinerAggressiveness::Always);
+
+        for threshold in [i64::MIN, -1, 0, 1, i64::MAX] {
+            assert_eq!(InlinerAggressiveness::from(threshold).threshold(), threshold);
+        }
+        assert_eq!(InlinerAggressiveness::from(i64::MIN), InlinerAggressiveness::Never);
+        assert_eq!(InlinerAggressiveness::from(0), InlinerAggressiveness::WhenCheaper);
+        assert_eq!(InlinerAggressiveness::from(i64::MAX), InlinerAggressiveness::Always);
+        assert_eq!(InlinerAggressiveness::from(7), InlinerAggressiveness::Custom(7));
+    }
 }
@@ -8,10 +8,12 @@ mod array_set;
 mod as_slice_length;
 mod assert_constant;
 mod constant_folding;
+mod dedup_constants;
 mod defunctionalize;
 mod die;
 pub(crate) mod flatten_cfg;
-mod inlining;
+pub(crate) mod inline_const_brillig_calls;
+pub(crate) mod inlining;
 mod loop_invariant;
 mod mem2reg;
 mod normalize_value_ids;
@@ -28,6 +28,15 @@ impl Ssa {
             context.normalize_ids(function);
         }
         self.functions = context.functions.into_btree();
+
+        // Function IDs only change if they weren't consecutive, as can be the case in parsed SSA
+        let function_ids = &context.new_ids.function_ids;
+        self.main_id = function_ids[&self.main_id];
+        self.entry_point_to_generated_index =
+            std::mem::take(&mut self.entry_point_to_generated_index)
+                .into_iter()
+                .filter_map(|(id, index)| Some((*function_ids.get(&id)?, index)))
+                .collect();
     }
 }
 
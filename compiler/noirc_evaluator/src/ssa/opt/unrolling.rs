@@ -42,14 +42,34 @@ use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
 impl Ssa {
     /// Loop unrolling can return errors, since ACIR functions need to be fully unrolled.
     /// This meta-pass will keep trying to unroll loops and simplifying the SSA until no more errors are found.
+    pub(crate) fn unroll_loops_iteratively(ssa: Ssa) -> Result<Ssa, RuntimeError> {
+        Self::unroll_loops_iteratively_with_limit(ssa, usize::MAX)
+    }
+
+    /// Same as `unroll_loops_iteratively`, but fails with [`RuntimeError::UnrollLimitExceeded`]
+    /// as soon as unrolling the loops of a function creates more than `max_instructions`
+    /// instructions, instead of going on with loops that have a huge number of iterations.
     #[tracing::instrument(level = "trace", skip(ssa))]
-    pub(crate) fn unroll_loops_iteratively(mut ssa: Ssa) -> Result<Ssa, RuntimeError> {
+    pub(crate) fn unroll_loops_iteratively_with_limit(
+        mut ssa: Ssa,
+        max_instructions: usize,
+    ) -> Result<Ssa, RuntimeError> {
         for (_, function) in ssa.functions.iter_mut() {
+            let mut limit = UnrollLimit::new(max_instructions);
+
             // Try to unroll loops first:
-            let mut unroll_errors = function.try_unroll_loops();
+            let mut unroll_errors = function.try_unroll_loops(&mut limit);
 
             // Keep unrolling until no more errors are found
             while !unroll_errors.is_empty() {
+                // Simplifying won't make the loops any shorter
+                if let Some(index) = unroll_errors
+                    .iter()
+                    .position(|error| matches!(error, RuntimeError::UnrollLimitExceeded { .. }))
+                {
+                    return Err(unroll_errors.swap_remove(index));
+                }
+
                 let prev_unroll_err_count = unroll_errors.len();
 
                 // Simplify the SSA before retrying
@@ -61,7 +81,7 @@ impl Ssa {
                 function.mem2reg();
 
                 // Unroll again
-                unroll_errors = function.try_unroll_loops();
+                unroll_errors = function.try_unroll_loops(&mut limit);
                 // If we didn't manage to unroll any more loops, exit
                 if unroll_errors.len() >= prev_unroll_err_count {
                     return Err(unroll_errors.swap_remove(0));
@@ -77,8 +97,27 @@ impl Function {
     // This can also be true for ACIR, but we have no alternative to unrolling in ACIR.
     // Brillig also generally prefers smaller code rather than faster code,
     // so we only attempt to unroll small loops, which we decide on a case-by-case basis.
-    fn try_unroll_loops(&mut self) -> Vec<RuntimeError> {
-        Loops::find_all(self).unroll_each(self)
+    fn try_unroll_loops(&mut self, limit: &mut UnrollLimit) -> Vec<RuntimeError> {
+        Loops::find_all(self).unroll_each(self, limit)
+    }
+}
+
+/// Limits the number of instructions unrolling the loops of a function may create.
+struct UnrollLimit {
+    max_instructions: usize,
+    unrolled_instructions: usize,
+}
+
+impl UnrollLimit {
+    fn new(max_instructions: usize) -> Self {
+        Self { max_instructions, unrolled_instructions: 0 }
+    }
+
+    /// Records that `amount` more instructions were unrolled.
+    /// Returns false if that goes over the limit.
+    fn try_unroll(&mut self, amount: usize) -> bool {
+        self.unrolled_instructions = self.unrolled_instructions.saturating_add(amount);
+        self.unrolled_instructions <= self.max_instructions
     }
 }
 
@@ -170,7 +209,12 @@ impl Loops {
 
     /// Unroll all loops within a given function.
     /// Any loops which fail to be unrolled (due to using non-constant indices) will be unmodified.
-    fn unroll_each(mut self, function: &mut Function) -> Vec<RuntimeError> {
+    /// If `limit` is exceeded, the remaining loops aren't looked at.
+    fn unroll_each(
+        mut self,
+        function: &mut Function,
+        limit: &mut UnrollLimit,
+    ) -> Vec<RuntimeError> {
         let mut unroll_errors = vec![];
         while let Some(next_loop) = self.yet_to_unroll.pop() {
             if function.runtime().is_brillig() && !next_loop.is_small_loop(function, &self.cfg) {
@@ -181,16 +225,23 @@ impl Loops {
             if next_loop.blocks.iter().any(|block| self.modified_blocks.contains(block)) {
                 let mut new_loops = Self::find_all(function);
                 new_loops.failed_to_unroll = self.failed_to_unroll;
-                return unroll_errors.into_iter().chain(new_loops.unroll_each(function)).collect();
+                return unroll_errors
+                    .into_iter()
+                    .chain(new_loops.unroll_each(function, limit))
+                    .collect();
             }
 
             // Don't try to unroll the loop again if it is known to fail
             if !self.failed_to_unroll.contains(&next_loop.header) {
-                match next_loop.unroll(function, &self.cfg) {
+                match next_loop.unroll(function, &self.cfg, limit) {
                     Ok(_) => self.modified_blocks.extend(next_loop.blocks),
-                    Err(call_stack) => {
+                    Err(error @ RuntimeError::UnrollLimitExceeded { .. }) => {
+                        unroll_errors.push(error);
+                        return unroll_errors;
+                    }
+                    Err(error) => {
                         self.failed_to_unroll.insert(next_loop.header);
-                        unroll_errors.push(RuntimeError::UnknownLoopBound { call_stack });
+                        unroll_errors.push(error);
                     }
                 }
             }
@@ -363,14 +414,36 @@ impl Loop {
     ///   jmp loop_end
     /// ```
     ///
-    /// When e.g. `v8 = lt v5 v1` cannot be evaluated to a constant, the loop signals by returning `Err`
-    /// that a few SSA passes are required to evaluate and simplify these values.
-    fn unroll(&self, function: &mut Function, cfg: &ControlFlowGraph) -> Result<(), CallStack> {
-        let mut unroll_into = self.get_pre_header(function, cfg)?;
-        let mut jump_value = get_induction_variable(function, unroll_into)?;
+    /// When e.g. `v8 = lt v5 v1` cannot be evaluated to a constant, the loop signals by returning
+    /// `RuntimeError::UnknownLoopBound` that a few SSA passes are required to evaluate and simplify
+    /// these values. Each iteration counts towards `limit`: if it is exceeded, the loop is left
+    /// partially unrolled and `RuntimeError::UnrollLimitExceeded` is returned.
+    fn unroll(
+        &self,
+        function: &mut Function,
+        cfg: &ControlFlowGraph,
+        limit: &mut UnrollLimit,
+    ) -> Result<(), RuntimeError> {
+        let unknown_loop_bound = |call_stack| RuntimeError::UnknownLoopBound { call_stack };
+
+        let mut unroll_into = self.get_pre_header(function, cfg).map_err(unknown_loop_bound)?;
+        let mut jump_value =
+            get_induction_variable(function, unroll_into).map_err(unknown_loop_bound)?;
         let mut array_cache = Some(ArrayCache::default());
 
-        while let Some(mut context) = self.unroll_header(function, unroll_into, jump_value)? {
+        let instructions_per_iteration = self.count_all_instructions(function);
+        let call_stack = function.dfg[self.header].unwrap_terminator().call_stack();
+
+        while let Some(mut context) =
+            self.unroll_header(function, unroll_into, jump_value).map_err(unknown_loop_bound)?
+        {
+            if !limit.try_unroll(instructions_per_iteration) {
+                return Err(RuntimeError::UnrollLimitExceeded {
+                    max_instructions: limit.max_instructions,
+                    call_stack,
+                });
+            }
+
             // The inserter's array cache must be explicitly enabled. This is to
             // confirm that we're inserting in insertion order. This is true here since:
             // 1. We have a fresh inserter for each loop
@@ -954,14 +1027,14 @@ mod tests {
     use crate::errors::RuntimeError;
     use crate::ssa::{ir::value::ValueId, opt::assert_normalized_ssa_equals, Ssa};
 
-    use super::{BoilerplateStats, Loops};
+    use super::{BoilerplateStats, Loops, UnrollLimit};
 
     /// Tries to unroll all loops in each SSA function.
     /// If any loop cannot be unrolled, it is left as-is or in a partially unrolled state.
     fn try_unroll_loops(mut ssa: Ssa) -> (Ssa, Vec<RuntimeError>) {
         let mut errors = vec![];
         for function in ssa.functions.values_mut() {
-            errors.extend(function.try_unroll_loops());
+            errors.extend(function.try_unroll_loops(&mut UnrollLimit::new(usize::MAX)));
         }
         (ssa, errors)
     }
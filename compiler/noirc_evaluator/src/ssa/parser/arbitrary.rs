@@ -0,0 +1,220 @@
+//! Generation of arbitrary [`ParsedSsa`] trees with proptest, to fuzz the parser and the SSA passes.
+//!
+//! Trees aren't generated directly. Instead, a set of plain random choices is generated and then
+//! resolved against the values defined so far, which makes every generated tree valid: each value
+//! is defined before it is used, operands have matching types and each block ends with a
+//! terminator whose arguments match the parameters of its destination.
+use acvm::FieldElement;
+use noirc_errors::Span;
+use noirc_frontend::monomorphization::ast::InlineType;
+use proptest::{collection::vec, prelude::*};
+
+use crate::ssa::ir::{function::RuntimeType, instruction::BinaryOp, types::Type};
+
+use super::{
+    Identifier, ParsedBlock, ParsedFunction, ParsedInstruction, ParsedParameter, ParsedSsa,
+    ParsedTerminator, ParsedValue,
+};
+
+/// The random choices a block is built from.
+#[derive(Debug, Clone)]
+struct BlockChoices {
+    /// One choice per parameter, picking its type.
+    parameters: Vec<u8>,
+    /// One choice per instruction: its kind and two choices for its operands.
+    instructions: Vec<(u8, u8, u8)>,
+}
+
+/// Generates programs made of a single `main` function, whose blocks form a chain: each block
+/// jumps to the next one and the last one returns.
+pub(crate) fn arbitrary_parsed_ssa() -> impl Strategy<Value = ParsedSsa> {
+    let block = (vec(any::<u8>(), 0..3), vec(any::<(u8, u8, u8)>(), 0..8))
+        .prop_map(|(parameters, instructions)| BlockChoices { parameters, instructions });
+
+    (any::<bool>(), vec(block, 1..5)).prop_map(|(brillig, blocks)| {
+        let runtime_type = if brillig {
+            RuntimeType::Brillig(InlineType::Inline)
+        } else {
+            RuntimeType::Acir(InlineType::Inline)
+        };
+        let function = FunctionGenerator::default().generate(runtime_type, &blocks);
+        ParsedSsa {
+            globals: Vec::new(),
+            functions: vec![function],
+            directives: Vec::new(),
+            header: None,
+        }
+    })
+}
+
+/// The types values are generated with.
+fn types() -> [Type; 3] {
+    [Type::field(), Type::unsigned(32), Type::bool()]
+}
+
+fn choose_type(choice: u8) -> Type {
+    let types = types();
+    types[choice as usize % types.len()].clone()
+}
+
+fn identifier(name: String) -> Identifier {
+    Identifier::new(name, Span::default())
+}
+
+#[derive(Default)]
+struct FunctionGenerator {
+    /// The values defined so far, with their types.
+    /// Blocks form a chain, so all of them are available in the block being generated.
+    values: Vec<(String, Type)>,
+}
+
+impl FunctionGenerator {
+    fn generate(mut self, runtime_type: RuntimeType, blocks: &[BlockChoices]) -> ParsedFunction {
+        let parameter_types: Vec<Vec<Type>> = blocks
+            .iter()
+            .map(|block| block.parameters.iter().map(|choice| choose_type(*choice)).collect())
+            .collect();
+
+        let mut parsed_blocks = Vec::with_capacity(blocks.len());
+        for (index, block) in blocks.iter().enumerate() {
+            let parameters = parameter_types[index]
+                .iter()
+                .map(|typ| ParsedParameter {
+                    identifier: self.define_value(typ.clone()),
+                    typ: typ.clone(),
+                })
+                .collect();
+
+            let instructions = block
+                .instructions
+                .iter()
+                .map(|(kind, first, second)| self.instruction(*kind, *first, *second))
+                .collect();
+
+            let terminator = match parameter_types.get(index + 1) {
+                Some(next_parameter_types) => ParsedTerminator::Jmp {
+                    destination: identifier(format!("b{}", index + 1)),
+                    arguments: next_parameter_types
+                        .iter()
+                        .enumerate()
+                        .map(|(argument, typ)| self.value_of_type(typ, argument as u8))
+                        .collect(),
+                },
+                None => ParsedTerminator::Return {
+                    values: self
+                        .values
+                        .last()
+                        .map(|(name, _)| ParsedValue::Variable(identifier(name.clone())))
+                        .into_iter()
+                        .collect(),
+                    types: None,
+                },
+            };
+
+            parsed_blocks.push(ParsedBlock {
+                name: format!("b{index}"),
+                parameters,
+                instructions,
+                terminator,
+            });
+        }
+
+        ParsedFunction {
+            attributes: Vec::new(),
+            runtime_type,
+            external_name: "main".to_string(),
+            internal_name: identifier("f0".to_string()),
+            blocks: parsed_blocks,
+        }
+    }
+
+    fn instruction(&mut self, kind: u8, first: u8, second: u8) -> ParsedInstruction {
+        let typ = choose_type(first);
+        match kind % 4 {
+            0 => {
+                let is_integer = typ != Type::field();
+                let op = match second % 5 {
+                    0 => BinaryOp::Add,
+                    1 => BinaryOp::Sub,
+                    2 => BinaryOp::Mul,
+                    3 if is_integer => BinaryOp::Lt,
+                    _ => BinaryOp::Eq,
+                };
+                let lhs = self.value_of_type(&typ, second);
+                let rhs = self.value_of_type(&typ, first);
+                let result_type =
+                    if matches!(op, BinaryOp::Eq | BinaryOp::Lt) { Type::bool() } else { typ };
+                ParsedInstruction::BinaryOp { target: self.define_value(result_type), lhs, op, rhs }
+            }
+            1 if typ != Type::field() => {
+                let value = self.value_of_type(&typ, second);
+                ParsedInstruction::Not { target: self.define_value(typ), value }
+            }
+            2 => {
+                // Casting to a Field never changes the value being cast
+                let lhs = self.value_of_type(&typ, second);
+                ParsedInstruction::Cast {
+                    target: self.define_value(Type::field()),
+                    lhs,
+                    typ: Type::field(),
+                }
+            }
+            _ => {
+                let lhs = self.value_of_type(&typ, second);
+                let rhs = self.value_of_type(&typ, first);
+                ParsedInstruction::Constrain { lhs, rhs }
+            }
+        }
+    }
+
+    /// Defines a new value of the given type, named after the number of values defined so far.
+    fn define_value(&mut self, typ: Type) -> Identifier {
+        let name = format!("v{}", self.values.len());
+        self.values.push((name.clone(), typ));
+        identifier(name)
+    }
+
+    /// Picks a value of the given type among the ones defined so far.
+    /// A constant is picked instead if there are none, and every now and then otherwise.
+    fn value_of_type(&self, typ: &Type, choice: u8) -> ParsedValue {
+        let candidates: Vec<&String> = self
+            .values
+            .iter()
+            .filter(|(_, value_type)| value_type == typ)
+            .map(|(name, _)| name)
+            .collect();
+
+        if candidates.is_empty() || choice % 4 == 0 {
+            let constant = if *typ == Type::bool() { choice % 2 } else { choice };
+            ParsedValue::NumericConstant {
+                constant: FieldElement::from(constant as u128),
+                typ: typ.clone(),
+            }
+        } else {
+            let name = candidates[choice as usize % candidates.len()];
+            ParsedValue::Variable(identifier(name.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::ssa::Ssa;
+
+    use super::arbitrary_parsed_ssa;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100))]
+        #[test]
+        fn arbitrary_parsed_ssa_builds(parsed_ssa in arbitrary_parsed_ssa()) {
+            let ssa = parsed_ssa.into_ssa();
+            prop_assert!(ssa.is_ok(), "Failed to build SSA: {:?}", ssa.err());
+
+            // The printed program can be parsed back too
+            let src = ssa.unwrap().to_string();
+            prop_assert!(Ssa::from_str(&src).is_ok(), "Failed to parse back:\n{src}");
+        }
+    }
+}
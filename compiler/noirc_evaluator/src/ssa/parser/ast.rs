@@ -7,17 +7,40 @@ use crate::ssa::ir::{function::RuntimeType, instruction::BinaryOp, types::Type};
 
 #[derive(Debug)]
 pub(crate) struct ParsedSsa {
+    pub(crate) globals: Vec<ParsedGlobal>,
     pub(crate) functions: Vec<ParsedFunction>,
+    /// The contents of `//@` comments, in source order, for test harnesses to read.
+    /// For example `//@ expect: optimized` gives `expect: optimized`.
+    pub(crate) directives: Vec<String>,
+    /// The contents of the `//` comment on the first line, if the source starts with one.
+    /// Tools emitting SSA can stamp a format version there, as in `// noir-ssa v1`,
+    /// and check it before translating the parsed SSA.
+    pub(crate) header: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ParsedGlobal {
+    pub(crate) name: Identifier,
+    pub(crate) elements: Vec<ParsedValue>,
+    pub(crate) typ: Type,
 }
 
 #[derive(Debug)]
 pub(crate) struct ParsedFunction {
+    pub(crate) attributes: Vec<ParsedAttribute>,
     pub(crate) runtime_type: RuntimeType,
     pub(crate) external_name: String,
-    pub(crate) internal_name: String,
+    pub(crate) internal_name: Identifier,
     pub(crate) blocks: Vec<ParsedBlock>,
 }
 
+/// An attribute written as `#[name]` on the line before a function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ParsedAttribute {
+    /// Marks a function as an entry point even if its runtime doesn't make it one.
+    EntryPoint,
+}
+
 #[derive(Debug)]
 pub(crate) struct ParsedBlock {
     pub(crate) name: String,
@@ -76,10 +99,17 @@ pub(crate) enum ParsedInstruction {
         rhs: ParsedValue,
     },
     Call {
-        targets: Vec<Identifier>,
+        /// Each result of the call together with its type.
+        targets: Vec<(Identifier, Type)>,
         function: Identifier,
         arguments: Vec<ParsedValue>,
-        types: Vec<Type>,
+    },
+    /// A call to an oracle, as in `v1 = foreign "get_sibling_index"(v0) -> Field`
+    ForeignCall {
+        /// Each result of the call together with its type.
+        targets: Vec<(Identifier, Type)>,
+        name: String,
+        arguments: Vec<ParsedValue>,
     },
     Cast {
         target: Identifier,
@@ -96,6 +126,12 @@ pub(crate) enum ParsedInstruction {
     EnableSideEffectsIf {
         condition: ParsedValue,
     },
+    IfElse {
+        target: Identifier,
+        condition: ParsedValue,
+        then_value: ParsedValue,
+        else_value: ParsedValue,
+    },
     IncrementRc {
         value: ParsedValue,
     },
@@ -103,6 +139,9 @@ pub(crate) enum ParsedInstruction {
         target: Identifier,
         value: ParsedValue,
         typ: Type,
+        /// Aliasing metadata from a trailing `// alias: ...` comment. It isn't used when
+        /// turning the instruction into SSA, but is kept for tooling.
+        alias: Option<String>,
     },
     MakeArray {
         target: Identifier,
@@ -116,10 +155,15 @@ pub(crate) enum ParsedInstruction {
     RangeCheck {
         value: ParsedValue,
         max_bit_size: u32,
+        /// The message of the assertion failing if the check fails, as in
+        /// `range_check v0 to 8 bits, "value must fit in a byte"`
+        message: Option<String>,
     },
     Store {
         value: ParsedValue,
         address: ParsedValue,
+        /// Aliasing metadata from a trailing `// alias: ...` comment, as in `Load`.
+        alias: Option<String>,
     },
     Truncate {
         target: Identifier,
@@ -131,13 +175,38 @@ pub(crate) enum ParsedInstruction {
 
 #[derive(Debug)]
 pub(crate) enum ParsedTerminator {
-    Jmp { destination: Identifier, arguments: Vec<ParsedValue> },
-    Jmpif { condition: ParsedValue, then_block: Identifier, else_block: Identifier },
-    Return(Vec<ParsedValue>),
+    Jmp {
+        destination: Identifier,
+        arguments: Vec<ParsedValue>,
+    },
+    Jmpif {
+        condition: ParsedValue,
+        then_block: Identifier,
+        else_block: Identifier,
+    },
+    Return {
+        values: Vec<ParsedValue>,
+        types: Option<ParsedReturnTypes>,
+    },
+    /// Ends a block that can never complete, such as a branch proven dead by the compiler.
+    Unreachable,
+}
+
+/// The types optionally written after the values of a `return`, as in `return v0, v1: (Field, u32)`.
+#[derive(Debug)]
+pub(crate) struct ParsedReturnTypes {
+    pub(crate) types: Vec<Type>,
+    /// The span of the first type, used to point at the types on a mismatch.
+    pub(crate) span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub(crate) enum ParsedValue {
-    NumericConstant { constant: FieldElement, typ: Type },
+    NumericConstant {
+        constant: FieldElement,
+        typ: Type,
+    },
     Variable(Identifier),
+    /// A reference to a function of the program, as in `call f2(f1)`.
+    Function(Identifier),
 }
@@ -1,27 +1,65 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use acvm::{AcirField, FieldElement};
+use iter_extended::vecmap;
+use noirc_errors::Span;
 
 use crate::ssa::{
     function_builder::FunctionBuilder,
-    ir::{basic_block::BasicBlockId, function::FunctionId, value::ValueId},
+    ir::{
+        basic_block::BasicBlockId,
+        dom::DominatorTree,
+        function::{Function, FunctionId},
+        instruction::{Instruction, TerminatorInstruction},
+        types::{NumericType, Type},
+        value::{Value, ValueId},
+    },
 };
 
 use super::{
-    Identifier, ParsedBlock, ParsedFunction, ParsedInstruction, ParsedSsa, ParsedTerminator,
-    ParsedValue, RuntimeType, Ssa, SsaError,
+    Identifier, ParsedAttribute, ParsedBlock, ParsedFunction, ParsedGlobal, ParsedInstruction,
+    ParsedSsa, ParsedTerminator, ParsedValue, RuntimeType, Ssa, SsaError,
 };
 
 impl ParsedSsa {
     pub(crate) fn into_ssa(self) -> Result<Ssa, SsaError> {
         Translator::translate(self)
     }
+
+    /// Same as `into_ssa`, but also checks that every reachable block of the resulting SSA
+    /// is terminated and that every value is defined before it is used.
+    pub(crate) fn into_ssa_verified(self) -> Result<Ssa, SsaError> {
+        let ssa = self.into_ssa()?;
+        for function in ssa.functions.values() {
+            verify_function(function)?;
+        }
+        Ok(ssa)
+    }
+
+    /// Same as `into_ssa`, but instead of stopping at the first error, keeps translating
+    /// the remaining functions and returns the errors of all of them.
+    ///
+    /// Only an error in the main function, or in the globals, stops the translation right away.
+    pub(crate) fn into_ssa_collect_errors(self) -> Result<Ssa, Vec<SsaError>> {
+        Translator::translate_collecting_errors(self)
+    }
 }
 
-struct Translator {
+/// Builds SSA from parsed SSA, one function at a time.
+///
+/// Most SSA is translated in one go with [`ParsedSsa::into_ssa`], but SSA assembled from
+/// several parsed sources can be translated with [`Translator::from_parsed`] followed by
+/// [`Translator::add_parsed`] for each remaining source, and finally [`Translator::finish`].
+pub(crate) struct Translator {
     builder: FunctionBuilder,
 
     /// Maps function names to their IDs
     functions: HashMap<String, FunctionId>,
 
+    /// Maps function IDs to the runtime they were declared with, so that calls can be checked
+    /// before the callee is translated.
+    runtimes: HashMap<FunctionId, RuntimeType>,
+
     /// Maps block names to their IDs
     blocks: HashMap<FunctionId, HashMap<String, BasicBlockId>>,
 
@@ -31,10 +69,36 @@ struct Translator {
     /// passes already which replaced some of the original IDs. The translator
     /// will recreate the SSA step by step, which can result in a new ID layout.
     variables: HashMap<FunctionId, HashMap<String, ValueId>>,
+
+    /// Maps global names to their declarations.
+    globals: HashMap<String, ParsedGlobal>,
+
+    /// Maps global names to the value holding them in each function.
+    ///
+    /// Value IDs are local to a function, so a global can't be a single value for the
+    /// whole program. Instead, the first time a function refers to a global its array is
+    /// built in that function's entry block, and every later reference reuses that value.
+    global_values: HashMap<FunctionId, HashMap<String, ValueId>>,
+
+    /// Functions marked with `#[entry_point]`, in the order they were translated.
+    entry_points: Vec<FunctionId>,
+}
+
+/// A jump from one block to another, kept around so that the arguments it passes can be
+/// checked against the destination's parameters once all blocks of a function are translated.
+struct Jump {
+    predecessor: String,
+    destination: Identifier,
+    arguments: Vec<ValueId>,
 }
 
 impl Translator {
-    fn translate(mut parsed_ssa: ParsedSsa) -> Result<Ssa, SsaError> {
+    fn translate(parsed_ssa: ParsedSsa) -> Result<Ssa, SsaError> {
+        Ok(Self::from_parsed(parsed_ssa)?.finish())
+    }
+
+    /// Translates every function of `parsed_ssa`. The first one is the main function.
+    pub(crate) fn from_parsed(mut parsed_ssa: ParsedSsa) -> Result<Self, SsaError> {
         let mut translator = Self::new(&mut parsed_ssa)?;
 
         // Note that the `new` call above removed the main function,
@@ -43,36 +107,135 @@ impl Translator {
             translator.translate_non_main_function(function)?;
         }
 
-        Ok(translator.finish())
+        Ok(translator)
+    }
+
+    /// Translates the globals and functions of another parsed SSA next to the ones translated
+    /// so far. None of its functions becomes the main function.
+    ///
+    /// Function IDs which are already taken are replaced by unused ones. A call refers to the
+    /// function of that name in `parsed_ssa` if there's one, and to a function translated
+    /// before otherwise.
+    pub(crate) fn add_parsed(&mut self, parsed_ssa: ParsedSsa) -> Result<(), SsaError> {
+        let parsed_ids = parsed_ssa
+            .functions
+            .iter()
+            .map(|function| function_id_from_name(&function.internal_name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // New IDs come after both the IDs taken so far and the ones of `parsed_ssa`,
+        // so that a replaced ID can't be taken by a later function of `parsed_ssa`.
+        let mut next_id = self
+            .runtimes
+            .keys()
+            .chain(&parsed_ids)
+            .map(|id| id.to_usize() + 1)
+            .max()
+            .unwrap_or_default();
+
+        let mut functions = HashMap::new();
+        let mut taken_ids: HashSet<FunctionId> = self.runtimes.keys().copied().collect();
+        for (function, parsed_id) in parsed_ssa.functions.iter().zip(parsed_ids) {
+            let name = function.internal_name.name.clone();
+            if functions.contains_key(&name) {
+                return Err(SsaError::FunctionAlreadyDefined(function.internal_name.clone()));
+            }
+
+            let function_id = if taken_ids.contains(&parsed_id) {
+                next_id += 1;
+                FunctionId::new(next_id - 1)
+            } else {
+                parsed_id
+            };
+            taken_ids.insert(function_id);
+            functions.insert(name, function_id);
+            self.runtimes.insert(function_id, function.runtime_type);
+        }
+        self.functions.extend(functions);
+
+        for global in parsed_ssa.globals {
+            if self.globals.contains_key(&global.name.name) {
+                return Err(SsaError::VariableAlreadyDefined(global.name));
+            }
+            self.globals.insert(global.name.name.clone(), global);
+        }
+
+        for function in parsed_ssa.functions {
+            self.translate_non_main_function(function)?;
+        }
+
+        Ok(())
+    }
+
+    fn translate_collecting_errors(mut parsed_ssa: ParsedSsa) -> Result<Ssa, Vec<SsaError>> {
+        let mut translator = Self::new(&mut parsed_ssa).map_err(|error| vec![error])?;
+
+        // A function that fails to translate is left unfinished in the builder, but
+        // that's fine because no SSA is produced once there's an error.
+        let mut errors = Vec::new();
+        for function in parsed_ssa.functions {
+            if let Err(error) = translator.translate_non_main_function(function) {
+                errors.push(error);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(translator.finish())
+        } else {
+            Err(errors)
+        }
     }
 
     fn new(parsed_ssa: &mut ParsedSsa) -> Result<Self, SsaError> {
+        if parsed_ssa.functions.is_empty() {
+            return Err(SsaError::NoFunctions);
+        }
+
+        // Map function names to their IDs so calls can be resolved. Each function keeps the ID
+        // in its name, so that the SSA prints back with the same function names.
+        let mut functions = HashMap::new();
+        let mut runtimes = HashMap::new();
+        for function in &parsed_ssa.functions {
+            let function_id = function_id_from_name(&function.internal_name)?;
+            let name = function.internal_name.name.clone();
+            if functions.insert(name, function_id).is_some() {
+                return Err(SsaError::FunctionAlreadyDefined(function.internal_name.clone()));
+            }
+            runtimes.insert(function_id, function.runtime_type);
+        }
+
         // A FunctionBuilder must be created with a main Function, so here wer remove it
         // from the parsed SSA to avoid adding it twice later on.
         let main_function = parsed_ssa.functions.remove(0);
-        let main_id = FunctionId::new(0);
+        let main_id = functions[&main_function.internal_name.name];
         let mut builder = FunctionBuilder::new(main_function.external_name.clone(), main_id);
         builder.set_runtime(main_function.runtime_type);
 
-        // Map function names to their IDs so calls can be resolved
-        let mut function_id_counter = 1;
-        let mut functions = HashMap::new();
-        for function in &parsed_ssa.functions {
-            let function_id = FunctionId::new(function_id_counter);
-            function_id_counter += 1;
-
-            functions.insert(function.internal_name.clone(), function_id);
+        let mut globals = HashMap::new();
+        for global in std::mem::take(&mut parsed_ssa.globals) {
+            if globals.contains_key(&global.name.name) {
+                return Err(SsaError::VariableAlreadyDefined(global.name));
+            }
+            globals.insert(global.name.name.clone(), global);
         }
 
-        let mut translator =
-            Self { builder, functions, variables: HashMap::new(), blocks: HashMap::new() };
+        let mut translator = Self {
+            builder,
+            functions,
+            runtimes,
+            variables: HashMap::new(),
+            blocks: HashMap::new(),
+            globals,
+            global_values: HashMap::new(),
+            entry_points: Vec::new(),
+        };
         translator.translate_function_body(main_function)?;
 
         Ok(translator)
     }
 
     fn translate_non_main_function(&mut self, function: ParsedFunction) -> Result<(), SsaError> {
-        let function_id = self.functions[&function.internal_name];
+        let function_id = self.functions[&function.internal_name.name];
         let external_name = function.external_name.clone();
 
         match function.runtime_type {
@@ -88,6 +251,10 @@ impl Translator {
     }
 
     fn translate_function_body(&mut self, function: ParsedFunction) -> Result<(), SsaError> {
+        if function.attributes.contains(&ParsedAttribute::EntryPoint) {
+            self.entry_points.push(self.current_function_id());
+        }
+
         // First define all blocks so that they are known (a block might jump to a block that comes next)
         for (index, block) in function.blocks.iter().enumerate() {
             // The first block is the entry block and it was automatically created by the builder
@@ -100,14 +267,22 @@ impl Translator {
             entry.insert(block.name.clone(), block_id);
         }
 
+        let mut jumps = Vec::new();
+        let mut unreachable_blocks = Vec::new();
         for block in function.blocks {
-            self.translate_block(block)?;
+            self.translate_block(block, &mut jumps, &mut unreachable_blocks)?;
         }
 
-        Ok(())
+        self.terminate_unreachable_blocks(unreachable_blocks)?;
+        self.validate(jumps)
     }
 
-    fn translate_block(&mut self, block: ParsedBlock) -> Result<(), SsaError> {
+    fn translate_block(
+        &mut self,
+        block: ParsedBlock,
+        jumps: &mut Vec<Jump>,
+        unreachable_blocks: &mut Vec<BasicBlockId>,
+    ) -> Result<(), SsaError> {
         let block_id = self.blocks[&self.current_function_id()][&block.name];
         self.builder.switch_to_block(block_id);
 
@@ -122,25 +297,105 @@ impl Translator {
 
         match block.terminator {
             ParsedTerminator::Jmp { destination, arguments } => {
-                let block_id = self.lookup_block(destination)?;
+                let block_id = self.lookup_block(destination.clone())?;
                 let arguments = self.translate_values(arguments)?;
+                jumps.push(Jump {
+                    predecessor: block.name,
+                    destination,
+                    arguments: arguments.clone(),
+                });
                 self.builder.terminate_with_jmp(block_id, arguments);
             }
             ParsedTerminator::Jmpif { condition, then_block, else_block } => {
                 let condition = self.translate_value(condition)?;
-                let then_destination = self.lookup_block(then_block)?;
-                let else_destination = self.lookup_block(else_block)?;
+                let then_destination = self.lookup_block(then_block.clone())?;
+                let else_destination = self.lookup_block(else_block.clone())?;
+                // A `jmpif` doesn't pass any arguments to the blocks it jumps to
+                for destination in [then_block, else_block] {
+                    let predecessor = block.name.clone();
+                    jumps.push(Jump { predecessor, destination, arguments: Vec::new() });
+                }
                 self.builder.terminate_with_jmpif(condition, then_destination, else_destination);
             }
-            ParsedTerminator::Return(values) => {
+            ParsedTerminator::Return { values, types } => {
                 let return_values = self.translate_values(values)?;
+                if let Some(types) = types {
+                    let dfg = &self.builder.current_function.dfg;
+                    let found = vecmap(&return_values, |value| dfg.type_of_value(*value));
+                    if found != types.types {
+                        return Err(SsaError::MismatchedReturnTypes {
+                            expected: types.types,
+                            found,
+                            span: types.span,
+                        });
+                    }
+                }
                 self.builder.terminate_with_return(return_values);
             }
+            ParsedTerminator::Unreachable => {
+                // SSA has no unreachable terminator, so the block gets a constraint that always
+                // fails, and is terminated with a `return` once the function's return types are known
+                let zero = self.builder.numeric_constant(FieldElement::zero(), Type::bool());
+                let one = self.builder.numeric_constant(FieldElement::one(), Type::bool());
+                self.builder.insert_constrain(zero, one, None);
+                unreachable_blocks.push(block_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Terminates the blocks ending in `unreachable` with a `return` of zeroed values,
+    /// typed like the values returned by the other blocks of the current function.
+    fn terminate_unreachable_blocks(&mut self, blocks: Vec<BasicBlockId>) -> Result<(), SsaError> {
+        if blocks.is_empty() {
+            return Ok(());
         }
 
+        let dfg = &self.builder.current_function.dfg;
+        let return_types = dfg
+            .basic_blocks_iter()
+            .find_map(|(_, block)| match block.terminator() {
+                Some(TerminatorInstruction::Return { return_values, .. }) => {
+                    Some(vecmap(return_values, |value| dfg.type_of_value(*value)))
+                }
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        for block_id in blocks {
+            self.builder.switch_to_block(block_id);
+            let return_values =
+                return_types.iter().map(|typ| self.zeroed_value(typ)).collect::<Result<_, _>>()?;
+            self.builder.terminate_with_return(return_values);
+        }
         Ok(())
     }
 
+    fn zeroed_value(&mut self, typ: &Type) -> Result<ValueId, SsaError> {
+        match typ {
+            Type::Numeric(_) => {
+                Ok(self.builder.numeric_constant(FieldElement::zero(), typ.clone()))
+            }
+            Type::Array(element_types, length) => {
+                let mut elements = im::Vector::new();
+                for _ in 0..*length {
+                    for element_type in element_types.iter() {
+                        elements.push_back(self.zeroed_value(element_type)?);
+                    }
+                }
+                Ok(self.builder.insert_make_array(elements, typ.clone()))
+            }
+            Type::Slice(_) => Ok(self.builder.insert_make_array(im::Vector::new(), typ.clone())),
+            Type::Reference(element_type) => {
+                Ok(self.builder.insert_allocate(element_type.as_ref().clone()))
+            }
+            Type::Function => Err(SsaError::Validation(
+                "a function value can't be returned from an unreachable block".to_string(),
+            )),
+        }
+    }
+
     fn translate_instruction(&mut self, instruction: ParsedInstruction) -> Result<(), SsaError> {
         match instruction {
             ParsedInstruction::Allocate { target, typ } => {
@@ -167,14 +422,23 @@ impl Translator {
             ParsedInstruction::BinaryOp { target, lhs, op, rhs } => {
                 let lhs = self.translate_value(lhs)?;
                 let rhs = self.translate_value(rhs)?;
+                // This goes through the same path as SSA generation from the frontend, which
+                // doesn't insert any check of its own for `div` and `mod`: division by zero is
+                // checked when generating ACIR or brillig, and a division by a constant zero is
+                // never folded away, so the parsed SSA fails the same way as the compiled one.
                 let value_id = self.builder.insert_binary(lhs, op, rhs);
                 self.define_variable(target, value_id)?;
             }
-            ParsedInstruction::Call { targets, function, arguments, types } => {
+            ParsedInstruction::Call { targets, function, arguments } => {
+                let (targets, types): (Vec<_>, Vec<_>) = targets.into_iter().unzip();
                 let function_id = if let Some(id) = self.builder.import_intrinsic(&function.name) {
                     id
+                } else if self.is_variable(&function.name) {
+                    // Calling a function received as a value, as in `call v0(v1)`
+                    self.lookup_variable(function)?
                 } else {
-                    let function_id = self.lookup_function(function)?;
+                    let function_id = self.lookup_function(function.clone())?;
+                    self.check_call_runtimes(function, function_id)?;
                     self.builder.import_function(function_id)
                 };
 
@@ -193,6 +457,16 @@ impl Translator {
                     self.define_variable(target, value_id)?;
                 }
             }
+            ParsedInstruction::ForeignCall { targets, name, arguments } => {
+                let (targets, types): (Vec<_>, Vec<_>) = targets.into_iter().unzip();
+                let function_id = self.builder.import_foreign_function(&name);
+                let arguments = self.translate_values(arguments)?;
+
+                let value_ids = self.builder.insert_call(function_id, arguments, types).to_vec();
+                for (target, value_id) in targets.into_iter().zip(value_ids) {
+                    self.define_variable(target, value_id)?;
+                }
+            }
             ParsedInstruction::Cast { target, lhs, typ } => {
                 let lhs = self.translate_value(lhs)?;
                 let value_id = self.builder.insert_cast(lhs, typ);
@@ -205,25 +479,31 @@ impl Translator {
             }
             ParsedInstruction::DecrementRc { value } => {
                 let value = self.translate_value(value)?;
-                self.builder.decrement_array_reference_count(value);
+                self.builder.insert_dec_rc(value);
             }
             ParsedInstruction::EnableSideEffectsIf { condition } => {
                 let condition = self.translate_value(condition)?;
                 self.builder.insert_enable_side_effects_if(condition);
             }
+            ParsedInstruction::IfElse { target, condition, then_value, else_value } => {
+                let condition = self.translate_value(condition)?;
+                let then_value = self.translate_value(then_value)?;
+                let else_value = self.translate_value(else_value)?;
+                let instruction =
+                    Instruction::IfElse { then_condition: condition, then_value, else_value };
+                let value_id = self.builder.insert_instruction(instruction, None).first();
+                self.define_variable(target, value_id)?;
+            }
             ParsedInstruction::IncrementRc { value } => {
                 let value = self.translate_value(value)?;
-                self.builder.increment_array_reference_count(value);
+                self.builder.insert_inc_rc(value);
             }
             ParsedInstruction::MakeArray { target, elements, typ } => {
-                let elements = elements
-                    .into_iter()
-                    .map(|element| self.translate_value(element))
-                    .collect::<Result<_, _>>()?;
+                let elements = self.translate_array_elements(elements, &typ, target.span)?;
                 let value_id = self.builder.insert_make_array(elements, typ);
                 self.define_variable(target, value_id)?;
             }
-            ParsedInstruction::Load { target, value, typ } => {
+            ParsedInstruction::Load { target, value, typ, alias: _ } => {
                 let value = self.translate_value(value)?;
                 let value_id = self.builder.insert_load(value, typ);
                 self.define_variable(target, value_id)?;
@@ -233,11 +513,11 @@ impl Translator {
                 let value_id = self.builder.insert_not(value);
                 self.define_variable(target, value_id)?;
             }
-            ParsedInstruction::RangeCheck { value, max_bit_size } => {
+            ParsedInstruction::RangeCheck { value, max_bit_size, message } => {
                 let value = self.translate_value(value)?;
-                self.builder.insert_range_check(value, max_bit_size, None);
+                self.builder.insert_range_check(value, max_bit_size, message);
             }
-            ParsedInstruction::Store { value, address } => {
+            ParsedInstruction::Store { value, address, alias: _ } => {
                 let value = self.translate_value(value)?;
                 let address = self.translate_value(address)?;
                 self.builder.insert_store(address, value);
@@ -265,8 +545,132 @@ impl Translator {
             ParsedValue::NumericConstant { constant, typ } => {
                 Ok(self.builder.numeric_constant(constant, typ))
             }
-            ParsedValue::Variable(identifier) => self.lookup_variable(identifier),
+            ParsedValue::Variable(identifier) => {
+                if !self.is_variable(&identifier.name)
+                    && self.globals.contains_key(&identifier.name)
+                {
+                    self.translate_global(identifier)
+                } else {
+                    self.lookup_variable(identifier)
+                }
+            }
+            ParsedValue::Function(identifier) => {
+                let function_id = self.lookup_function(identifier)?;
+                Ok(self.builder.import_function(function_id))
+            }
+        }
+    }
+
+    /// Translates the elements of an array of the given type.
+    ///
+    /// The array's element type is authoritative: each numeric constant element takes the
+    /// type of its position in the array, and is rejected if its value does not fit that type.
+    /// Every element must also have the shape of its position's type, so for example a
+    /// `[[Field; 2]; 2]` only accepts `[Field; 2]` arrays as elements.
+    fn translate_array_elements(
+        &mut self,
+        elements: Vec<ParsedValue>,
+        typ: &Type,
+        span: Span,
+    ) -> Result<im::Vector<ValueId>, SsaError> {
+        let element_types = match typ {
+            Type::Array(element_types, _) | Type::Slice(element_types)
+                if !element_types.is_empty() =>
+            {
+                element_types.clone()
+            }
+            _ => return Ok(self.translate_values(elements)?.into_iter().collect()),
+        };
+
+        let mut translated_elements = im::Vector::new();
+        for (index, element) in elements.into_iter().enumerate() {
+            let expected = &element_types[index % element_types.len()];
+            let element = match (element, Some(expected)) {
+                (
+                    ParsedValue::NumericConstant { constant, .. },
+                    Some(Type::Numeric(numeric_type)),
+                ) => {
+                    if !constant_fits_numeric_type(constant, *numeric_type) {
+                        let typ = Type::Numeric(*numeric_type);
+                        return Err(SsaError::ConstantDoesNotFitType { constant, typ, span });
+                    }
+                    let typ = Type::Numeric(*numeric_type);
+                    ParsedValue::NumericConstant { constant, typ }
+                }
+                (element, _) => element,
+            };
+            let element = self.translate_value(element)?;
+
+            let found = self.builder.type_of_value(element);
+            if !has_same_shape(expected, &found) {
+                let expected = expected.clone();
+                return Err(SsaError::MismatchedArrayElementShape { expected, found, span });
+            }
+
+            translated_elements.push_back(element);
+        }
+        Ok(translated_elements)
+    }
+
+    fn is_variable(&self, name: &str) -> bool {
+        self.variables
+            .get(&self.current_function_id())
+            .is_some_and(|variables| variables.contains_key(name))
+    }
+
+    fn translate_global(&mut self, identifier: Identifier) -> Result<ValueId, SsaError> {
+        let function_id = self.current_function_id();
+        if let Some(value_id) =
+            self.global_values.get(&function_id).and_then(|values| values.get(&identifier.name))
+        {
+            return Ok(*value_id);
+        }
+
+        // Build the array in the entry block so that it dominates every use of the global
+        let global = self.globals[&identifier.name].clone();
+        let current_block = self.builder.current_block();
+        let entry_block = self.builder.current_function.entry_block();
+        self.builder.switch_to_block(entry_block);
+        let elements =
+            self.translate_array_elements(global.elements, &global.typ, global.name.span);
+        let value_id =
+            elements.map(|elements| self.builder.insert_make_array(elements, global.typ));
+        self.builder.switch_to_block(current_block);
+        let value_id = value_id?;
+
+        self.global_values.entry(function_id).or_default().insert(identifier.name, value_id);
+        Ok(value_id)
+    }
+
+    /// Checks that every jump passes exactly one argument per parameter of the block it
+    /// jumps to, with the argument having the same type as the parameter.
+    fn validate(&self, jumps: Vec<Jump>) -> Result<(), SsaError> {
+        let dfg = &self.builder.current_function.dfg;
+        for jump in jumps {
+            let block_id = self.blocks[&self.current_function_id()][&jump.destination.name];
+            let parameters = dfg.block_parameters(block_id);
+
+            if parameters.len() != jump.arguments.len() {
+                return Err(SsaError::BlockParameterArityMismatch {
+                    block: jump.destination,
+                    predecessor: jump.predecessor,
+                });
+            }
+
+            for (parameter, argument) in parameters.iter().zip(&jump.arguments) {
+                let expected = dfg.type_of_value(*parameter);
+                let found = dfg.type_of_value(*argument);
+                if expected != found {
+                    return Err(SsaError::BlockArgumentTypeMismatch {
+                        block: jump.destination,
+                        predecessor: jump.predecessor,
+                        expected,
+                        found,
+                    });
+                }
+            }
         }
+        Ok(())
     }
 
     fn define_variable(
@@ -287,7 +691,10 @@ impl Translator {
     }
 
     fn lookup_variable(&mut self, identifier: Identifier) -> Result<ValueId, SsaError> {
-        if let Some(value_id) = self.variables[&self.current_function_id()].get(&identifier.name) {
+        // Parsed names are only mapped to IDs once they are defined, so any numbering
+        // works as long as every variable is defined before it is used.
+        let variables = self.variables.get(&self.current_function_id());
+        if let Some(value_id) = variables.and_then(|variables| variables.get(&identifier.name)) {
             Ok(*value_id)
         } else {
             Err(SsaError::UnknownVariable(identifier))
@@ -310,8 +717,40 @@ impl Translator {
         }
     }
 
-    fn finish(self) -> Ssa {
+    /// Checks that the current function may call `callee`, given their runtimes.
+    ///
+    /// ACIR functions may call both ACIR functions, which are inlined or compiled into their own
+    /// circuit, and Brillig functions, which are called through the Brillig VM. Brillig code,
+    /// however, is never lowered to ACIR calls, so a Brillig function may only call Brillig
+    /// functions.
+    fn check_call_runtimes(
+        &self,
+        callee: Identifier,
+        callee_id: FunctionId,
+    ) -> Result<(), SsaError> {
+        let caller_runtime = self.builder.current_function.runtime();
+        let callee_runtime = self.runtimes[&callee_id];
+
+        match (caller_runtime, callee_runtime) {
+            (RuntimeType::Brillig(_), RuntimeType::Acir(_)) => {
+                Err(SsaError::IncompatibleCallRuntimes { callee, caller_runtime, callee_runtime })
+            }
+            (RuntimeType::Acir(_), _) | (RuntimeType::Brillig(_), RuntimeType::Brillig(_)) => {
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> Ssa {
         let mut ssa = self.builder.finish();
+
+        // Entry points implied by a function's runtime were already found when building the
+        // SSA, the remaining ones are numbered after them.
+        for function_id in self.entry_points {
+            let index = ssa.entry_point_to_generated_index.len() as u32;
+            ssa.entry_point_to_generated_index.entry(function_id).or_insert(index);
+        }
+
         // Normalize the IDs so we have a better chance of matching the SSA we parsed
         // after the step-by-step reconstruction done during translation. This assumes
         // that the SSA we parsed was printed by the `SsaBuilder`, which normalizes
@@ -324,3 +763,101 @@ impl Translator {
         self.builder.current_function.id()
     }
 }
+
+fn verify_function(function: &Function) -> Result<(), SsaError> {
+    let dfg = &function.dfg;
+    let blocks = function.reachable_blocks();
+    let mut dom = DominatorTree::with_function(function);
+
+    // The block and position of each instruction, so uses can be checked against definitions
+    let mut definitions = HashMap::new();
+    for block in &blocks {
+        for (index, instruction) in dfg[*block].instructions().iter().enumerate() {
+            definitions.insert(*instruction, (*block, index));
+        }
+    }
+
+    for block in blocks.iter().copied() {
+        let Some(terminator) = dfg[block].terminator() else {
+            return Err(SsaError::Validation(format!(
+                "Block {block} of function {} is not terminated",
+                function.name()
+            )));
+        };
+
+        // Each used value along with the position of the instruction using it.
+        // The terminator comes after every instruction.
+        let instructions = dfg[block].instructions();
+        let mut used_values = Vec::new();
+        for (index, instruction) in instructions.iter().enumerate() {
+            dfg[*instruction].for_each_value(|value| used_values.push((value, index)));
+        }
+        terminator.for_each_value(|value| used_values.push((value, instructions.len())));
+
+        for (value, index) in used_values {
+            let is_defined = match &dfg[dfg.resolve(value)] {
+                Value::Instruction { instruction, .. } => match definitions.get(instruction) {
+                    Some((defining_block, defining_index)) if *defining_block == block => {
+                        *defining_index < index
+                    }
+                    Some((defining_block, _)) => dom.dominates(*defining_block, block),
+                    None => false,
+                },
+                Value::Param { block: defining_block, .. } => {
+                    blocks.contains(defining_block) && dom.dominates(*defining_block, block)
+                }
+                Value::NumericConstant { .. }
+                | Value::Function(_)
+                | Value::Intrinsic(_)
+                | Value::ForeignFunction(_) => true,
+            };
+            if !is_defined {
+                return Err(SsaError::Validation(format!(
+                    "Value {value} is used in block {block} of function {} before it is defined",
+                    function.name()
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns true if `constant` is within the limits of `numeric_type`.
+/// Negative constants are only accepted for signed types.
+fn constant_fits_numeric_type(constant: FieldElement, numeric_type: NumericType) -> bool {
+    numeric_type.value_is_outside_limits(constant, false).is_none()
+        || (matches!(numeric_type, NumericType::Signed { .. })
+            && numeric_type.value_is_outside_limits(-constant, true).is_none())
+}
+
+/// Returns true if both types are numeric, or are arrays or slices whose elements have the
+/// same shape, down to the arrays' lengths. Numeric types themselves aren't compared.
+fn has_same_shape(expected: &Type, found: &Type) -> bool {
+    let same_element_shapes = |expected: &[Type], found: &[Type]| {
+        expected.len() == found.len()
+            && expected.iter().zip(found).all(|(expected, found)| has_same_shape(expected, found))
+    };
+
+    match (expected, found) {
+        (Type::Numeric(_), Type::Numeric(_))
+        | (Type::Reference(_), Type::Reference(_))
+        | (Type::Function, Type::Function) => true,
+        (Type::Array(expected, expected_length), Type::Array(found, found_length)) => {
+            expected_length == found_length && same_element_shapes(expected, found)
+        }
+        (Type::Slice(expected), Type::Slice(found)) => same_element_shapes(expected, found),
+        _ => false,
+    }
+}
+
+/// Returns the ID a function named `f<number>` is given, which is that number.
+/// The number must be written without leading zeros so that each ID has a single name.
+fn function_id_from_name(name: &Identifier) -> Result<FunctionId, SsaError> {
+    let id = name.name.strip_prefix('f').and_then(|digits| {
+        let id: u32 = digits.parse().ok()?;
+        (id.to_string() == digits).then_some(id)
+    });
+    id.map(|id| FunctionId::new(id as usize))
+        .ok_or_else(|| SsaError::InvalidFunctionName(name.clone()))
+}
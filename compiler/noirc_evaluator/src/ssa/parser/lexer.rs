@@ -14,6 +14,14 @@ pub(crate) struct Lexer<'a> {
     position: Position,
     done: bool,
     max_integer: BigInt,
+    /// The contents of the `//@` directive comments seen so far, in source order.
+    directives: Vec<String>,
+    /// The metadata of the last `// alias: ...` comment that trailed a token on the same line.
+    trailing_alias: Option<String>,
+    /// Whether a newline was seen since the last token was returned.
+    newline_since_token: bool,
+    /// The contents of the `//` comment on the first non-blank line, if any.
+    header: Option<String>,
 }
 
 impl<'a> Lexer<'a> {
@@ -24,28 +32,44 @@ impl<'a> Lexer<'a> {
             done: false,
             max_integer: BigInt::from_biguint(num_bigint::Sign::Plus, FieldElement::modulus())
                 - BigInt::one(),
+            directives: Vec::new(),
+            trailing_alias: None,
+            newline_since_token: true,
+            header: Self::header_of(source),
         }
     }
 
     pub(crate) fn next_token(&mut self) -> SpannedTokenResult {
+        let token = self.lex_token();
+        self.newline_since_token = false;
+        token
+    }
+
+    fn lex_token(&mut self) -> SpannedTokenResult {
         match self.next_char() {
             Some(char) if char.is_ascii_whitespace() => {
+                self.newline_since_token |= char == '\n';
                 while let Some(char) = self.peek_char() {
                     if char.is_ascii_whitespace() {
+                        self.newline_since_token |= char == '\n';
                         self.next_char();
                     } else {
                         break;
                     }
                 }
-                self.next_token()
+                self.lex_token()
             }
             Some('/') if self.peek_char() == Some('/') => {
-                while let Some(char) = self.next_char() {
-                    if char == '\n' {
-                        break;
+                self.next_char();
+                let comment = self.eat_while(None, |char| char != '\n');
+                if let Some(directive) = comment.strip_prefix('@') {
+                    self.directives.push(directive.trim().to_string());
+                } else if let Some(alias) = comment.trim_start().strip_prefix("alias:") {
+                    if !self.newline_since_token {
+                        self.trailing_alias = Some(alias.trim().to_string());
                     }
                 }
-                self.next_token()
+                self.lex_token()
             }
             Some('=') if self.peek_char() == Some('=') => self.double_char_token(Token::Equal),
             Some('=') => self.single_char_token(Token::Assign),
@@ -59,8 +83,10 @@ impl<'a> Lexer<'a> {
             Some('[') => self.single_char_token(Token::LeftBracket),
             Some(']') => self.single_char_token(Token::RightBracket),
             Some('&') => self.single_char_token(Token::Ampersand),
+            Some('#') => self.single_char_token(Token::Hash),
             Some('-') if self.peek_char() == Some('>') => self.double_char_token(Token::Arrow),
             Some('-') => self.single_char_token(Token::Dash),
+            Some('"') => self.eat_string_literal(),
             Some(ch) if ch.is_ascii_alphanumeric() || ch == '_' => self.eat_alpha_numeric(ch),
             Some(char) => Err(LexerError::UnexpectedCharacter {
                 char,
@@ -173,10 +199,56 @@ impl<'a> Lexer<'a> {
             }
         };
 
+        // An integer type may directly follow the literal, as in `5u32`
+        if matches!(self.peek_char(), Some('u' | 'i')) {
+            let initial_char = self.next_char().unwrap();
+            let (_, suffix, end) = self.lex_word(initial_char);
+            return match IntType::lookup_int_type(&suffix) {
+                Some(int_type) => Ok(Token::TypedInt(integer, int_type).into_span(start, end)),
+                None => Err(LexerError::InvalidIntegerLiteral {
+                    span: Span::inclusive(start, end),
+                    found: format!("{integer_str}{suffix}"),
+                }),
+            };
+        }
+
         let integer_token = Token::Int(integer);
         Ok(integer_token.into_span(start, end))
     }
 
+    /// Lexes a string literal whose opening quote was just consumed. A backslash escapes
+    /// the next character, as printed by `{:?}`.
+    fn eat_string_literal(&mut self) -> SpannedTokenResult {
+        let start = self.position;
+        let mut string = String::new();
+
+        loop {
+            let Some(char) = self.next_char() else {
+                return Err(LexerError::UnterminatedStringLiteral {
+                    span: Span::inclusive(start, self.position),
+                });
+            };
+            match char {
+                '"' => break,
+                '\\' => match self.next_char() {
+                    Some('n') => string.push('\n'),
+                    Some('t') => string.push('\t'),
+                    Some('r') => string.push('\r'),
+                    Some('0') => string.push('\0'),
+                    Some(char) => string.push(char),
+                    None => {
+                        return Err(LexerError::UnterminatedStringLiteral {
+                            span: Span::inclusive(start, self.position),
+                        })
+                    }
+                },
+                char => string.push(char),
+            }
+        }
+
+        Ok(Token::Str(string).into_span(start, self.position))
+    }
+
     fn eat_while<F: Fn(char) -> bool>(
         &mut self,
         initial_char: Option<char>,
@@ -231,14 +303,55 @@ impl<'a> Lexer<'a> {
         c.is_ascii_whitespace()
     }
 
+    /// Returns the contents of the first non-blank line of `source` if it is a regular comment,
+    /// such as `noir-ssa v1` for `// noir-ssa v1`. Directives are not headers.
+    fn header_of(source: &str) -> Option<String> {
+        let first_line = source.lines().find(|line| !line.trim().is_empty())?;
+        let comment = first_line.trim_start().strip_prefix("//")?;
+        (!comment.starts_with('@')).then(|| comment.trim().to_string())
+    }
+
+    /// Returns the header of the source, see [`ParsedSsa::header`][super::ast::ParsedSsa::header].
+    pub(crate) fn header(&self) -> Option<&str> {
+        self.header.as_deref()
+    }
+
+    /// Returns the directives collected from `//@` comments so far, such as `expect: optimized`
+    /// for `//@ expect: optimized`.
+    pub(crate) fn take_directives(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.directives)
+    }
+
+    /// Returns the metadata of the last `// alias: ...` comment that trailed a token on
+    /// the same line, if any, and forgets it.
+    pub(crate) fn take_trailing_alias(&mut self) -> Option<String> {
+        self.trailing_alias.take()
+    }
+
+    /// Returns true if the next token is on a later line. A comment always runs until the end
+    /// of its line, so one coming next means the next token is on a later line too.
     pub(crate) fn newline_follows(&self) -> bool {
-        let chars = self.chars.clone();
-        chars.take_while(|(_, char)| char.is_ascii_whitespace()).any(|(_, char)| char == '\n')
+        let rest = self.chars.as_str();
+        let rest = rest.trim_start_matches(|char: char| char.is_ascii_whitespace() && char != '\n');
+        rest.starts_with('\n') || rest.starts_with("//")
     }
 }
 
 type SpannedTokenResult = Result<SpannedToken, LexerError>;
 
+impl<'a> Iterator for Lexer<'a> {
+    type Item = SpannedTokenResult;
+
+    /// Yields every token of the source, ending with a single `Eof` token.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            None
+        } else {
+            Some(self.next_token())
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum LexerError {
     #[error("Unexpected character: {char:?}")]
@@ -247,6 +360,8 @@ pub(crate) enum LexerError {
     InvalidIntegerLiteral { span: Span, found: String },
     #[error("Integer literal too large")]
     IntegerLiteralTooLarge { span: Span, limit: String },
+    #[error("Unterminated string literal")]
+    UnterminatedStringLiteral { span: Span },
 }
 
 impl LexerError {
@@ -254,7 +369,8 @@ impl LexerError {
         match self {
             LexerError::UnexpectedCharacter { span, .. }
             | LexerError::InvalidIntegerLiteral { span, .. }
-            | LexerError::IntegerLiteralTooLarge { span, .. } => *span,
+            | LexerError::IntegerLiteralTooLarge { span, .. }
+            | LexerError::UnterminatedStringLiteral { span } => *span,
         }
     }
 }
@@ -1,31 +1,42 @@
 use std::{
-    fmt::{self, Debug, Formatter},
+    collections::HashSet,
+    fmt::{self, Debug, Display, Formatter},
     sync::Arc,
 };
 
 use super::{
-    ir::{instruction::BinaryOp, types::Type},
-    Ssa,
+    ir::{
+        instruction::BinaryOp,
+        types::{NumericType, Type},
+    },
+    optimize_ssa_before_brillig, Ssa, SsaBuilder,
 };
 
 use acvm::{AcirField, FieldElement};
 use ast::{
-    Identifier, ParsedBlock, ParsedFunction, ParsedInstruction, ParsedParameter, ParsedSsa,
-    ParsedValue,
+    Identifier, ParsedAttribute, ParsedBlock, ParsedFunction, ParsedGlobal, ParsedInstruction,
+    ParsedParameter, ParsedReturnTypes, ParsedSsa, ParsedValue,
 };
 use lexer::{Lexer, LexerError};
 use noirc_errors::Span;
 use noirc_frontend::{monomorphization::ast::InlineType, token::IntType};
 use thiserror::Error;
 use token::{Keyword, SpannedToken, Token};
+use walk::ParsedNodeMut;
 
-use crate::ssa::{ir::function::RuntimeType, parser::ast::ParsedTerminator};
+use crate::{
+    errors::RuntimeError,
+    ssa::{ir::function::RuntimeType, parser::ast::ParsedTerminator},
+};
 
+#[cfg(all(test, feature = "fuzz"))]
+mod arbitrary;
 mod ast;
 mod into_ssa;
 mod lexer;
 mod tests;
 mod token;
+mod walk;
 
 impl Ssa {
     /// Creates an Ssa object from the given string.
@@ -42,7 +53,50 @@ impl Ssa {
     }
 }
 
-pub(crate) struct SsaErrorWithSource {
+impl SsaBuilder {
+    /// Creates an `SsaBuilder` from the given string, to run several passes over it with
+    /// the same printing settings as when compiling a program.
+    ///
+    /// Unlike `SsaBuilder::new`, the initial SSA isn't printed.
+    pub(crate) fn from_str(
+        src: &str,
+        print_ssa_passes: bool,
+        print_codegen_timings: bool,
+    ) -> Result<SsaBuilder, SsaErrorWithSource> {
+        let ssa = Ssa::from_str(src)?;
+        Ok(SsaBuilder::from_ssa(ssa, print_ssa_passes, print_codegen_timings))
+    }
+}
+
+/// Parses `src`, runs the SSA passes that come before generating brillig when compiling a program
+/// over it, and returns the printed result.
+///
+/// `aggressiveness` is the inliner aggressiveness threshold, as given with `--inliner-aggressiveness`.
+///
+/// ```
+/// use noirc_evaluator::ssa::optimize_ssa_text;
+///
+/// let src = "
+///     acir(inline) fn main f0 {
+///       b0(v0: Field):
+///         v2 = add Field 1, Field 2
+///         v3 = add v0, v2
+///         return v3
+///     }
+///     ";
+/// let optimized = optimize_ssa_text(src, i64::MAX).unwrap();
+/// assert!(optimized.contains("add v0, Field 3"));
+/// ```
+pub fn optimize_ssa_text(src: &str, aggressiveness: i64) -> Result<String, SsaErrorWithSource> {
+    let builder = SsaBuilder::from_str(src, false, false)?;
+    let builder = optimize_ssa_before_brillig(builder, aggressiveness.into()).map_err(|error| {
+        SsaErrorWithSource { src: src.to_string(), error: SsaError::RuntimeError(error) }
+    })?;
+    Ok(builder.finish().to_string())
+}
+
+/// An error parsing or optimizing SSA, displayed along with the source it happened in.
+pub struct SsaErrorWithSource {
     src: String,
     error: SsaError,
 }
@@ -54,6 +108,14 @@ impl SsaErrorWithSource {
 }
 
 impl Debug for SsaErrorWithSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// Shows the error below the source line it happened in, with carets pointing at its span.
+/// This also applies to errors coming from the lexer, such as an unexpected character.
+impl Display for SsaErrorWithSource {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let span = self.error.span();
 
@@ -68,16 +130,24 @@ impl Debug for SsaErrorWithSource {
             writeln!(f, "{}", line)?;
 
             if has_error {
+                // Spans are byte offsets, but tabs and multi-byte characters don't take one
+                // column per byte, so the padding mirrors the tabs of the line instead
                 let offset = span.start() as usize - byte;
-                write!(f, "{}", " ".repeat(offset))?;
-                writeln!(f, "{}", "^".repeat((span.end() - span.start()) as usize))?;
-                write!(f, "{}", " ".repeat(offset))?;
-                writeln!(f, "{}", self.error)?;
+                let padding: String =
+                    line[..offset].chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+                let span_chars = line[offset..][..(span.end() - span.start()) as usize].chars();
+                writeln!(f, "{padding}{}", "^".repeat(span_chars.count()))?;
+                writeln!(f, "{padding}{}", self.error)?;
                 writeln!(f)?;
             }
 
             byte += line.len() + 1; // "+ 1" for the newline
         }
+
+        // There are no lines to point at for an empty source
+        if self.src.is_empty() {
+            writeln!(f, "{}", self.error)?;
+        }
         Ok(())
     }
 }
@@ -96,6 +166,41 @@ pub(crate) enum SsaError {
     MismatchedReturnValues { returns: Vec<Identifier>, expected: usize },
     #[error("Variable '{0}' already defined")]
     VariableAlreadyDefined(Identifier),
+    #[error("Function '{0}' already defined")]
+    FunctionAlreadyDefined(Identifier),
+    #[error("Function name '{0}' is not of the form `f<number>`")]
+    InvalidFunctionName(Identifier),
+    #[error(
+        "Block '{block}' does not receive one argument per parameter from block '{predecessor}'"
+    )]
+    BlockParameterArityMismatch { block: Identifier, predecessor: String },
+    #[error(
+        "Block '{block}' expects an argument of type {expected} from block '{predecessor}', found one of type {found}"
+    )]
+    BlockArgumentTypeMismatch {
+        block: Identifier,
+        predecessor: String,
+        expected: Type,
+        found: Type,
+    },
+    #[error("A {caller_runtime} function cannot call {callee_runtime} function '{callee}'")]
+    IncompatibleCallRuntimes {
+        callee: Identifier,
+        caller_runtime: RuntimeType,
+        callee_runtime: RuntimeType,
+    },
+    #[error("Invalid SSA: {0}")]
+    Validation(String),
+    #[error("Mismatched return types")]
+    MismatchedReturnTypes { expected: Vec<Type>, found: Vec<Type>, span: Span },
+    #[error("Expected at least one function")]
+    NoFunctions,
+    #[error("Constant {constant} does not fit in type {typ}")]
+    ConstantDoesNotFitType { constant: FieldElement, typ: Type, span: Span },
+    #[error("Expected an array element of type {expected}, found one of type {found}")]
+    MismatchedArrayElementShape { expected: Type, found: Type, span: Span },
+    #[error("{0}")]
+    RuntimeError(RuntimeError),
 }
 
 impl SsaError {
@@ -105,8 +210,20 @@ impl SsaError {
             SsaError::UnknownVariable(identifier)
             | SsaError::UnknownBlock(identifier)
             | SsaError::VariableAlreadyDefined(identifier)
-            | SsaError::UnknownFunction(identifier) => identifier.span,
+            | SsaError::FunctionAlreadyDefined(identifier)
+            | SsaError::InvalidFunctionName(identifier)
+            | SsaError::UnknownFunction(identifier)
+            | SsaError::IncompatibleCallRuntimes { callee: identifier, .. }
+            | SsaError::BlockParameterArityMismatch { block: identifier, .. }
+            | SsaError::BlockArgumentTypeMismatch { block: identifier, .. } => identifier.span,
             SsaError::MismatchedReturnValues { returns, expected: _ } => returns[0].span,
+            // Validation and passes run on the built SSA, which no longer knows about source positions
+            SsaError::Validation(_) | SsaError::RuntimeError(_) | SsaError::NoFunctions => {
+                Span::default()
+            }
+            SsaError::MismatchedReturnTypes { span, .. }
+            | SsaError::ConstantDoesNotFitType { span, .. }
+            | SsaError::MismatchedArrayElementShape { span, .. } => *span,
         }
     }
 }
@@ -127,20 +244,65 @@ impl<'a> Parser<'a> {
     }
 
     pub(crate) fn parse_ssa(&mut self) -> ParseResult<ParsedSsa> {
+        let mut globals = Vec::new();
+        while self.eat_keyword(Keyword::Global)? {
+            let global = self.parse_global()?;
+            globals.push(global);
+        }
+
         let mut functions = Vec::new();
         while !self.at(Token::Eof) {
             let function = self.parse_function()?;
             functions.push(function);
         }
-        Ok(ParsedSsa { functions })
+
+        let directives = self.lexer.take_directives();
+        let header = self.lexer.header().map(ToString::to_string);
+        let mut parsed_ssa = ParsedSsa { globals, functions, directives, header };
+        Self::resolve_function_values(&mut parsed_ssa);
+        Ok(parsed_ssa)
+    }
+
+    /// A function can be referred to before it's parsed, so values naming a function
+    /// are only turned into function references once all functions are known.
+    fn resolve_function_values(parsed_ssa: &mut ParsedSsa) {
+        let function_names: HashSet<String> = parsed_ssa
+            .functions
+            .iter()
+            .map(|function| function.internal_name.name.clone())
+            .collect();
+
+        parsed_ssa.walk_mut(|node| {
+            let ParsedNodeMut::Value(value) = node else {
+                return;
+            };
+            if let ParsedValue::Variable(identifier) = value {
+                if function_names.contains(&identifier.name) {
+                    *value = ParsedValue::Function(identifier.clone());
+                }
+            }
+        });
+    }
+
+    /// Parses `gN = [elements] : type`, the `global` keyword having already been eaten.
+    fn parse_global(&mut self) -> ParseResult<ParsedGlobal> {
+        let name = self.eat_identifier_or_error()?;
+        self.eat_or_error(Token::Assign)?;
+        self.eat_or_error(Token::LeftBracket)?;
+        let elements = self.parse_comma_separated_values()?;
+        self.eat_or_error(Token::RightBracket)?;
+        self.eat_or_error(Token::Colon)?;
+        let typ = self.parse_type()?;
+        Ok(ParsedGlobal { name, elements, typ })
     }
 
     fn parse_function(&mut self) -> ParseResult<ParsedFunction> {
+        let attributes = self.parse_attributes()?;
         let runtime_type = self.parse_runtime_type()?;
         self.eat_or_error(Token::Keyword(Keyword::Fn))?;
 
         let external_name = self.eat_ident_or_error()?;
-        let internal_name = self.eat_ident_or_error()?;
+        let internal_name = self.eat_identifier_or_error()?;
 
         self.eat_or_error(Token::LeftBrace)?;
 
@@ -148,7 +310,22 @@ impl<'a> Parser<'a> {
 
         self.eat_or_error(Token::RightBrace)?;
 
-        Ok(ParsedFunction { runtime_type, external_name, internal_name, blocks })
+        Ok(ParsedFunction { attributes, runtime_type, external_name, internal_name, blocks })
+    }
+
+    fn parse_attributes(&mut self) -> ParseResult<Vec<ParsedAttribute>> {
+        let mut attributes = Vec::new();
+        while self.eat(Token::Hash)? {
+            self.eat_or_error(Token::LeftBracket)?;
+            let name = self.eat_identifier_or_error()?;
+            let attribute = match name.name.as_str() {
+                "entry_point" => ParsedAttribute::EntryPoint,
+                _ => return Err(ParserError::UnknownAttribute { name }),
+            };
+            self.eat_or_error(Token::RightBracket)?;
+            attributes.push(attribute);
+        }
+        Ok(attributes)
     }
 
     fn parse_runtime_type(&mut self) -> ParseResult<RuntimeType> {
@@ -163,9 +340,14 @@ impl<'a> Parser<'a> {
             ]);
         };
 
-        self.eat_or_error(Token::LeftParen)?;
-        let inline_type = self.parse_inline_type()?;
-        self.eat_or_error(Token::RightParen)?;
+        // The inline type can be omitted, in which case it's the default one
+        let inline_type = if self.eat(Token::LeftParen)? {
+            let inline_type = self.parse_inline_type()?;
+            self.eat_or_error(Token::RightParen)?;
+            inline_type
+        } else {
+            InlineType::default()
+        };
 
         if acir {
             Ok(RuntimeType::Acir(inline_type))
@@ -194,9 +376,14 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_blocks(&mut self) -> ParseResult<Vec<ParsedBlock>> {
-        let mut blocks = Vec::new();
+        let mut blocks: Vec<ParsedBlock> = Vec::new();
         while !self.at(Token::RightBrace) {
+            let span = self.token.to_span();
             let block = self.parse_block()?;
+            if blocks.iter().any(|other| other.name == block.name) {
+                let name = Identifier::new(block.name, span);
+                return Err(ParserError::DuplicateBlock { name });
+            }
             blocks.push(block);
         }
         Ok(blocks)
@@ -217,8 +404,8 @@ impl<'a> Parser<'a> {
         self.eat_or_error(Token::RightParen)?;
         self.eat_or_error(Token::Colon)?;
 
-        let instructions = self.parse_instructions()?;
-        let terminator = self.parse_terminator()?;
+        let instructions = self.parse_instructions(&name)?;
+        let terminator = self.parse_terminator(&name)?;
         Ok(ParsedBlock { name, parameters, instructions, terminator })
     }
 
@@ -229,19 +416,24 @@ impl<'a> Parser<'a> {
         Ok(ParsedParameter { identifier, typ })
     }
 
-    fn parse_instructions(&mut self) -> ParseResult<Vec<ParsedInstruction>> {
+    /// Parses the instructions of the block named `block`.
+    fn parse_instructions(&mut self, block: &str) -> ParseResult<Vec<ParsedInstruction>> {
         let mut instructions = Vec::new();
-        while let Some(instruction) = self.parse_instruction()? {
+        while let Some(instruction) = self.parse_instruction(block)? {
             instructions.push(instruction);
         }
         Ok(instructions)
     }
 
-    fn parse_instruction(&mut self) -> ParseResult<Option<ParsedInstruction>> {
+    fn parse_instruction(&mut self, block: &str) -> ParseResult<Option<ParsedInstruction>> {
         if let Some(instruction) = self.parse_call()? {
             return Ok(Some(instruction));
         }
 
+        if let Some(instruction) = self.parse_foreign_call()? {
+            return Ok(Some(instruction));
+        }
+
         if let Some(instruction) = self.parse_constrain()? {
             return Ok(Some(instruction));
         }
@@ -267,12 +459,58 @@ impl<'a> Parser<'a> {
         }
 
         if let Some(target) = self.eat_identifier()? {
+            // An identifier followed by parentheses starts the next block,
+            // so the current block is missing its terminator
+            if self.at(Token::LeftParen) {
+                let block = block.to_string();
+                return Err(ParserError::MissingTerminator { block, span: target.span });
+            }
+
             return Ok(Some(self.parse_assignment(target)?));
         }
 
+        // An instruction with a result that isn't assigned to anything
+        if self.at_single_result_instruction() {
+            return Err(ParserError::WrongResultCount {
+                expected: 1,
+                found: 0,
+                span: self.token.to_span(),
+            });
+        }
+
         Ok(None)
     }
 
+    /// Returns true if the current token starts an instruction which has exactly one result.
+    fn at_single_result_instruction(&self) -> bool {
+        matches!(
+            self.token.token(),
+            Token::Keyword(
+                Keyword::Allocate
+                    | Keyword::ArrayGet
+                    | Keyword::ArraySet
+                    | Keyword::Cast
+                    | Keyword::If
+                    | Keyword::Load
+                    | Keyword::MakeArray
+                    | Keyword::Not
+                    | Keyword::Truncate
+                    | Keyword::Add
+                    | Keyword::Sub
+                    | Keyword::Mul
+                    | Keyword::Div
+                    | Keyword::Eq
+                    | Keyword::Mod
+                    | Keyword::Lt
+                    | Keyword::And
+                    | Keyword::Or
+                    | Keyword::Xor
+                    | Keyword::Shl
+                    | Keyword::Shr
+            )
+        )
+    }
+
     fn eat_binary_op(&mut self) -> ParseResult<Option<BinaryOp>> {
         let op = match self.token.token() {
             Token::Keyword(Keyword::Add) => BinaryOp::Add,
@@ -302,7 +540,17 @@ impl<'a> Parser<'a> {
 
         let function = self.eat_identifier_or_error()?;
         let arguments = self.parse_arguments()?;
-        Ok(Some(ParsedInstruction::Call { targets: vec![], function, arguments, types: vec![] }))
+        Ok(Some(ParsedInstruction::Call { targets: vec![], function, arguments }))
+    }
+
+    fn parse_foreign_call(&mut self) -> ParseResult<Option<ParsedInstruction>> {
+        if !self.eat_keyword(Keyword::Foreign)? {
+            return Ok(None);
+        }
+
+        let name = self.eat_string_or_error()?;
+        let arguments = self.parse_arguments()?;
+        Ok(Some(ParsedInstruction::ForeignCall { targets: vec![], name, arguments }))
     }
 
     fn parse_constrain(&mut self) -> ParseResult<Option<ParsedInstruction>> {
@@ -350,28 +598,47 @@ impl<'a> Parser<'a> {
 
         let value = self.parse_value_or_error()?;
         self.eat_or_error(Token::Keyword(Keyword::To))?;
-        let max_bit_size = self.eat_int_or_error()?.to_u128() as u32;
+
+        // A value can't have more bits than a field element
+        let span = self.token.to_span();
+        let bit_size = self.eat_int_or_error()?;
+        let max_bit_size = bit_size
+            .try_to_u32()
+            .filter(|bits| *bits <= FieldElement::max_num_bits())
+            .ok_or_else(|| ParserError::RangeCheckBitSizeTooLarge {
+                found: bit_size.to_string(),
+                max: FieldElement::max_num_bits(),
+                span,
+            })?;
         self.eat_or_error(Token::Keyword(Keyword::Bits))?;
-        Ok(Some(ParsedInstruction::RangeCheck { value, max_bit_size }))
+
+        let message =
+            if self.eat(Token::Comma)? { Some(self.eat_string_or_error()?) } else { None };
+        Ok(Some(ParsedInstruction::RangeCheck { value, max_bit_size, message }))
     }
 
     fn parse_store(&mut self) -> ParseResult<Option<ParsedInstruction>> {
-        if !self.eat_keyword(Keyword::Store)? {
+        if !self.at_keyword(Keyword::Store) {
             return Ok(None);
         }
 
+        self.lexer.take_trailing_alias();
+        self.bump()?;
+
         let value = self.parse_value_or_error()?;
         self.eat_or_error(Token::Keyword(Keyword::At))?;
         let address = self.parse_value_or_error()?;
-        Ok(Some(ParsedInstruction::Store { address, value }))
+        let alias = self.lexer.take_trailing_alias();
+        Ok(Some(ParsedInstruction::Store { address, value, alias }))
     }
 
     fn parse_assignment(&mut self, target: Identifier) -> ParseResult<ParsedInstruction> {
-        let mut targets = vec![target];
+        let mut targets = vec![(target, self.parse_target_type()?)];
 
         while self.eat(Token::Comma)? {
             let target = self.eat_identifier_or_error()?;
-            targets.push(target);
+            let typ = self.parse_target_type()?;
+            targets.push((target, typ));
         }
 
         self.eat_or_error(Token::Assign)?;
@@ -379,18 +646,31 @@ impl<'a> Parser<'a> {
         if self.eat_keyword(Keyword::Call)? {
             let function = self.eat_identifier_or_error()?;
             let arguments = self.parse_arguments()?;
-            self.eat_or_error(Token::Arrow)?;
-            let types = self.parse_types()?;
-            return Ok(ParsedInstruction::Call { targets, function, arguments, types });
+            let targets = self.parse_call_result_types(targets)?;
+            return Ok(ParsedInstruction::Call { targets, function, arguments });
+        }
+
+        if self.eat_keyword(Keyword::Foreign)? {
+            let name = self.eat_string_or_error()?;
+            let arguments = self.parse_arguments()?;
+            let targets = self.parse_call_result_types(targets)?;
+            return Ok(ParsedInstruction::ForeignCall { targets, name, arguments });
+        }
+
+        if let Some((target, _)) = targets.iter().find(|(_, typ)| typ.is_some()) {
+            return Err(ParserError::ResultTypeOnlyAllowedForCall { target: target.clone() });
         }
 
+        // Only calls may have several results, every other instruction has exactly one
         if targets.len() > 1 {
-            return Err(ParserError::MultipleReturnValuesOnlyAllowedForCall {
-                second_target: targets[1].clone(),
+            return Err(ParserError::WrongResultCount {
+                expected: 1,
+                found: targets.len(),
+                span: targets[1].0.span,
             });
         }
 
-        let target = targets.remove(0);
+        let target = targets.remove(0).0;
 
         if self.eat_keyword(Keyword::Allocate)? {
             self.eat_or_error(Token::Arrow)?;
@@ -402,7 +682,7 @@ impl<'a> Parser<'a> {
             let array = self.parse_value_or_error()?;
             self.eat_or_error(Token::Comma)?;
             self.eat_or_error(Token::Keyword(Keyword::Index))?;
-            let index = self.parse_value_or_error()?;
+            let index = self.parse_array_index()?;
             self.eat_or_error(Token::Arrow)?;
             let element_type = self.parse_type()?;
             return Ok(ParsedInstruction::ArrayGet { target, element_type, array, index });
@@ -413,7 +693,7 @@ impl<'a> Parser<'a> {
             let array = self.parse_value_or_error()?;
             self.eat_or_error(Token::Comma)?;
             self.eat_or_error(Token::Keyword(Keyword::Index))?;
-            let index = self.parse_value_or_error()?;
+            let index = self.parse_array_index()?;
             self.eat_or_error(Token::Comma)?;
             self.eat_or_error(Token::Keyword(Keyword::Value))?;
             let value = self.parse_value_or_error()?;
@@ -427,11 +707,25 @@ impl<'a> Parser<'a> {
             return Ok(ParsedInstruction::Cast { target, lhs, typ });
         }
 
-        if self.eat_keyword(Keyword::Load)? {
+        if self.eat_keyword(Keyword::If)? {
+            let condition = self.parse_value_or_error()?;
+            self.eat_or_error(Token::Keyword(Keyword::Then))?;
+            let then_value = self.parse_value_or_error()?;
+            self.eat_or_error(Token::Keyword(Keyword::Else))?;
+            let else_value = self.parse_value_or_error()?;
+            return Ok(ParsedInstruction::IfElse { target, condition, then_value, else_value });
+        }
+
+        if self.at_keyword(Keyword::Load) {
+            // Forget any alias comment trailing a previous instruction
+            self.lexer.take_trailing_alias();
+            self.bump()?;
+
             let value = self.parse_value_or_error()?;
             self.eat_or_error(Token::Arrow)?;
             let typ = self.parse_type()?;
-            return Ok(ParsedInstruction::Load { target, value, typ });
+            let alias = self.lexer.take_trailing_alias();
+            return Ok(ParsedInstruction::Load { target, value, typ, alias });
         }
 
         if self.eat_keyword(Keyword::MakeArray)? {
@@ -470,7 +764,7 @@ impl<'a> Parser<'a> {
         self.expected_instruction_or_terminator()
     }
 
-    fn parse_terminator(&mut self) -> ParseResult<ParsedTerminator> {
+    fn parse_terminator(&mut self, block: &str) -> ParseResult<ParsedTerminator> {
         if let Some(terminator) = self.parse_return()? {
             return Ok(terminator);
         }
@@ -483,6 +777,15 @@ impl<'a> Parser<'a> {
             return Ok(terminator);
         }
 
+        if self.eat_keyword(Keyword::Unreachable)? {
+            return Ok(ParsedTerminator::Unreachable);
+        }
+
+        if self.at(Token::RightBrace) {
+            let block = block.to_string();
+            return Err(ParserError::MissingTerminator { block, span: self.token.to_span() });
+        }
+
         self.expected_instruction_or_terminator()
     }
 
@@ -504,9 +807,22 @@ impl<'a> Parser<'a> {
             return Ok(None);
         }
 
-        let values =
-            if newline_follows { Vec::new() } else { self.parse_comma_separated_values()? };
-        Ok(Some(ParsedTerminator::Return(values)))
+        if newline_follows {
+            return Ok(Some(ParsedTerminator::Return { values: Vec::new(), types: None }));
+        }
+
+        let values = self.parse_comma_separated_values()?;
+
+        // The types of the returned values can optionally be given after a colon
+        let types = if self.eat(Token::Colon)? {
+            let span = self.token.to_span();
+            let types = self.parse_types()?;
+            Some(ParsedReturnTypes { types, span })
+        } else {
+            None
+        };
+
+        Ok(Some(ParsedTerminator::Return { values, types }))
     }
 
     fn parse_jmp(&mut self) -> ParseResult<Option<ParsedTerminator>> {
@@ -571,6 +887,14 @@ impl<'a> Parser<'a> {
             return Ok(Some(value));
         }
 
+        if let Some(value) = self.parse_bool_value()? {
+            return Ok(Some(value));
+        }
+
+        if let Some(value) = self.parse_typed_int_value()? {
+            return Ok(Some(value));
+        }
+
         if let Some(identifier) = self.eat_identifier()? {
             return Ok(Some(ParsedValue::Variable(identifier)));
         }
@@ -600,6 +924,104 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Parses the index of an `array_get` or `array_set`, which can't be a negative constant.
+    ///
+    /// Without this check a negative index would be wrapped into a huge field element.
+    fn parse_array_index(&mut self) -> ParseResult<ParsedValue> {
+        let span = self.token.to_span();
+        if self.at(Token::Dash) {
+            return Err(ParserError::NegativeArrayIndex { span });
+        }
+
+        let index = self.parse_value_or_error()?;
+        if let ParsedValue::NumericConstant { constant, .. } = &index {
+            // A negative constant is stored as its field element, whose negation is smaller
+            if (-*constant).num_bits() < constant.num_bits() {
+                return Err(ParserError::NegativeArrayIndex { span });
+            }
+        }
+        Ok(index)
+    }
+
+    /// Parses `true` or `false` as a `u1` constant.
+    fn parse_bool_value(&mut self) -> ParseResult<Option<ParsedValue>> {
+        let constant = if self.eat_keyword(Keyword::True)? {
+            FieldElement::one()
+        } else if self.eat_keyword(Keyword::False)? {
+            FieldElement::zero()
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(ParsedValue::NumericConstant { constant, typ: Type::bool() }))
+    }
+
+    /// Parses an integer literal carrying its own type, as in `5u32` or `-1i8`,
+    /// checking that its value fits the type.
+    fn parse_typed_int_value(&mut self) -> ParseResult<Option<ParsedValue>> {
+        let start_span = self.token.to_span();
+        let negative = self.eat(Token::Dash)?;
+        if !matches!(self.token.token(), Token::TypedInt(..)) {
+            return if negative { self.expected_int() } else { Ok(None) };
+        }
+
+        let token = self.bump()?;
+        let span = start_span.merge(token.to_span());
+        let Token::TypedInt(mut constant, int_type) = token.into_token() else { unreachable!() };
+
+        let numeric_type = match int_type {
+            IntType::Unsigned(bit_size) => NumericType::Unsigned { bit_size },
+            IntType::Signed(bit_size) => NumericType::Signed { bit_size },
+        };
+        if let Some(range) = numeric_type.value_is_outside_limits(constant, negative) {
+            let typ = Type::Numeric(numeric_type);
+            return Err(ParserError::IntegerLiteralOutOfRange { typ, range, span });
+        }
+
+        if negative {
+            constant = -constant;
+        }
+        Ok(Some(ParsedValue::NumericConstant { constant, typ: Type::Numeric(numeric_type) }))
+    }
+
+    /// Parses the optional type given to the target of an assignment, as in `v2: Field`.
+    fn parse_target_type(&mut self) -> ParseResult<Option<Type>> {
+        if self.eat(Token::Colon)? {
+            Ok(Some(self.parse_type()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The result types of a call are either given next to each target, as in
+    /// `v2: Field, v3: [u8; 4] = call f1()`, or all at once after the call, as in
+    /// `v2, v3 = call f1() -> (Field, [u8; 4])`.
+    fn parse_call_result_types(
+        &mut self,
+        targets: Vec<(Identifier, Option<Type>)>,
+    ) -> ParseResult<Vec<(Identifier, Type)>> {
+        if targets.iter().all(|(_, typ)| typ.is_some()) {
+            return Ok(targets.into_iter().map(|(target, typ)| (target, typ.unwrap())).collect());
+        }
+
+        if targets.iter().any(|(_, typ)| typ.is_some()) {
+            let (target, _) = targets.iter().find(|(_, typ)| typ.is_none()).unwrap();
+            return Err(ParserError::MissingResultType { target: target.clone() });
+        }
+
+        self.eat_or_error(Token::Arrow)?;
+        let span = self.token.to_span();
+        let types = self.parse_types()?;
+        if types.len() != targets.len() {
+            return Err(ParserError::MismatchedResultTypes {
+                expected: targets.len(),
+                found: types.len(),
+                span,
+            });
+        }
+
+        Ok(targets.into_iter().map(|(target, _)| target).zip(types).collect())
+    }
+
     fn parse_types(&mut self) -> ParseResult<Vec<Type>> {
         if self.eat(Token::LeftParen)? {
             let types = self.parse_comma_separated_types()?;
@@ -631,6 +1053,10 @@ impl<'a> Parser<'a> {
             return Ok(Type::field());
         }
 
+        if self.eat_keyword(Keyword::Function)? {
+            return Ok(Type::Function);
+        }
+
         if let Some(int_type) = self.eat_int_type()? {
             return Ok(match int_type {
                 IntType::Unsigned(bit_size) => Type::unsigned(bit_size),
@@ -727,6 +1153,26 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn eat_string(&mut self) -> ParseResult<Option<String>> {
+        if !matches!(self.token.token(), Token::Str(..)) {
+            return Ok(None);
+        }
+
+        let token = self.bump()?;
+        match token.into_token() {
+            Token::Str(string) => Ok(Some(string)),
+            _ => unreachable!(),
+        }
+    }
+
+    fn eat_string_or_error(&mut self) -> ParseResult<String> {
+        if let Some(string) = self.eat_string()? {
+            Ok(string)
+        } else {
+            self.expected_string()
+        }
+    }
+
     fn eat_int(&mut self) -> ParseResult<Option<FieldElement>> {
         let negative = self.eat(Token::Dash)?;
 
@@ -819,6 +1265,13 @@ impl<'a> Parser<'a> {
         })
     }
 
+    fn expected_string<T>(&mut self) -> ParseResult<T> {
+        Err(ParserError::ExpectedString {
+            found: self.token.token().clone(),
+            span: self.token.to_span(),
+        })
+    }
+
     fn expected_int<T>(&mut self) -> ParseResult<T> {
         Err(ParserError::ExpectedInt {
             found: self.token.token().clone(),
@@ -867,6 +1320,8 @@ pub(crate) enum ParserError {
     ExpectedOneOfTokens { tokens: Vec<Token>, found: Token, span: Span },
     #[error("Expected an identifier, found '{found}'")]
     ExpectedIdentifier { found: Token, span: Span },
+    #[error("Expected a string, found '{found}'")]
+    ExpectedString { found: Token, span: Span },
     #[error("Expected an int, found '{found}'")]
     ExpectedInt { found: Token, span: Span },
     #[error("Expected a type, found '{found}'")]
@@ -875,8 +1330,26 @@ pub(crate) enum ParserError {
     ExpectedInstructionOrTerminator { found: Token, span: Span },
     #[error("Expected a value, found '{found}'")]
     ExpectedValue { found: Token, span: Span },
-    #[error("Multiple return values only allowed for call")]
-    MultipleReturnValuesOnlyAllowedForCall { second_target: Identifier },
+    #[error("Expected {expected} result(s) for this instruction, found {found}")]
+    WrongResultCount { expected: usize, found: usize, span: Span },
+    #[error("Result types next to the results are only allowed for call")]
+    ResultTypeOnlyAllowedForCall { target: Identifier },
+    #[error("Result '{target}' has no type while other results of the call do")]
+    MissingResultType { target: Identifier },
+    #[error("Expected {expected} result types, found {found}")]
+    MismatchedResultTypes { expected: usize, found: usize, span: Span },
+    #[error("Unknown attribute '{name}'")]
+    UnknownAttribute { name: Identifier },
+    #[error("Block '{block}' has no terminator")]
+    MissingTerminator { block: String, span: Span },
+    #[error("Block '{name}' is defined more than once")]
+    DuplicateBlock { name: Identifier },
+    #[error("Integer literal does not fit in {typ}, expected a value in {range}")]
+    IntegerLiteralOutOfRange { typ: Type, range: String, span: Span },
+    #[error("Array index cannot be negative")]
+    NegativeArrayIndex { span: Span },
+    #[error("Range check bit size must be at most {max}, found {found}")]
+    RangeCheckBitSizeTooLarge { found: String, max: u32, span: Span },
 }
 
 impl ParserError {
@@ -886,13 +1359,22 @@ impl ParserError {
             ParserError::ExpectedToken { span, .. }
             | ParserError::ExpectedOneOfTokens { span, .. }
             | ParserError::ExpectedIdentifier { span, .. }
+            | ParserError::ExpectedString { span, .. }
             | ParserError::ExpectedInt { span, .. }
             | ParserError::ExpectedType { span, .. }
             | ParserError::ExpectedInstructionOrTerminator { span, .. }
-            | ParserError::ExpectedValue { span, .. } => *span,
-            ParserError::MultipleReturnValuesOnlyAllowedForCall { second_target, .. } => {
-                second_target.span
+            | ParserError::ExpectedValue { span, .. }
+            | ParserError::MissingTerminator { span, .. }
+            | ParserError::IntegerLiteralOutOfRange { span, .. }
+            | ParserError::NegativeArrayIndex { span }
+            | ParserError::RangeCheckBitSizeTooLarge { span, .. }
+            | ParserError::MismatchedResultTypes { span, .. }
+            | ParserError::WrongResultCount { span, .. } => *span,
+            ParserError::UnknownAttribute { name } | ParserError::DuplicateBlock { name } => {
+                name.span
             }
+            ParserError::ResultTypeOnlyAllowedForCall { target }
+            | ParserError::MissingResultType { target } => target.span,
         }
     }
 }
@@ -1,10 +1,33 @@
 #![cfg(test)]
 
+use std::{cell::RefCell, io::Write, rc::Rc, sync::Arc};
+
+use acvm::{AcirField, FieldElement};
+use noirc_frontend::token::IntType;
+
 use crate::{
-    ssa::{opt::assert_normalized_ssa_equals, Ssa},
+    ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            instruction::{BinaryOp, Instruction},
+            map::Id,
+            types::{NumericType, Type},
+        },
+        opt::assert_normalized_ssa_equals,
+        optimize_ssa_before_brillig, Ssa, SsaBuilder,
+    },
     trim_leading_whitespace_from_lines,
 };
 
+use super::{
+    into_ssa::Translator,
+    lexer::{Lexer, LexerError},
+    optimize_ssa_text,
+    token::Token,
+    walk::{ParsedNode, ParsedNodeMut},
+    ParsedInstruction, ParsedTerminator, ParsedValue, Parser, ParserError, SsaError,
+};
+
 fn assert_ssa_roundtrip(src: &str) {
     let ssa = Ssa::from_str(src).unwrap();
     let ssa = ssa.to_string();
@@ -54,297 +77,423 @@ fn test_return_integer() {
 }
 
 #[test]
-fn test_make_array() {
+fn test_runtime_without_inline_type() {
     let src = "
-        acir(inline) fn main f0 {
+        acir fn main f0 {
           b0():
-            v1 = make_array [Field 1] : [Field; 1]
-            return v1
+            return
+        }
+        brillig fn foo f1 {
+          b0():
+            return
         }
         ";
-    assert_ssa_roundtrip(src);
-}
-
-#[test]
-fn test_make_empty_array() {
-    let src = "
+    let expected = "
         acir(inline) fn main f0 {
           b0():
-            v0 = make_array [] : [Field; 0]
-            return v0
+            return
+        }
+        brillig(inline) fn foo f1 {
+          b0():
+            return
         }
         ";
-    assert_ssa_roundtrip(src);
+    let ssa = Ssa::from_str(src).unwrap();
+    assert_normalized_ssa_equals(ssa, expected);
 }
 
 #[test]
-fn test_make_composite_array() {
+fn test_explicit_inline_types() {
     let src = "
         acir(inline) fn main f0 {
           b0():
-            v2 = make_array [Field 1, Field 2] : [(Field, Field); 1]
-            return v2
+            return
+        }
+        brillig(fold) fn foo f1 {
+          b0():
+            return
         }
         ";
     assert_ssa_roundtrip(src);
 }
 
 #[test]
-fn test_block_parameters() {
+fn test_entry_point_attribute() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field, v1: Field):
-            return v0, v1
+          b0():
+            call f1()
+            return
+        }
+        #[entry_point]
+        brillig(inline) fn foo f1 {
+          b0():
+            return
         }
         ";
     assert_ssa_roundtrip(src);
+
+    let ssa = Ssa::from_str(src).unwrap();
+    let foo_id = *ssa.functions.keys().nth(1).unwrap();
+    assert_eq!(ssa.entry_point_to_generated_index.get(&foo_id), Some(&1));
 }
 
 #[test]
-fn test_multiple_blocks_and_jmp() {
+fn test_unknown_attribute() {
     let src = "
+        #[foo]
         acir(inline) fn main f0 {
           b0():
-            jmp b1(Field 1)
-          b1(v0: Field):
-            return v0
+            return
         }
         ";
-    assert_ssa_roundtrip(src);
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected an unknown attribute to be rejected");
+    };
+    assert!(matches!(error.error, SsaError::ParserError(ParserError::UnknownAttribute { .. })));
 }
 
 #[test]
-fn test_jmpif() {
+fn test_typed_return() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field):
-            jmpif v0 then: b2, else: b1
-          b2():
-            return
-          b1():
-            return
+          b0(v0: Field, v1: u32):
+            return v0, v1: (Field, u32)
         }
         ";
-    assert_ssa_roundtrip(src);
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: Field, v1: u32):
+            return v0, v1
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap();
+    assert_normalized_ssa_equals(ssa, expected);
 }
 
 #[test]
-fn test_call() {
+fn test_typed_return_with_mismatched_types() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field):
-            v2 = call f1(v0) -> Field
-            return v2
-        }
-        acir(inline) fn foo f1 {
-          b0(v0: Field):
-            return v0
+          b0(v0: Field, v1: u32):
+            return v0, v1: (Field, u64)
         }
         ";
-    assert_ssa_roundtrip(src);
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected mismatched return types to be rejected");
+    };
+    assert!(matches!(error.error, SsaError::MismatchedReturnTypes { .. }));
 }
 
 #[test]
-fn test_call_multiple_return_values() {
+fn test_several_instructions_before_terminator() {
     let src = "
         acir(inline) fn main f0 {
-          b0():
-            v1, v2 = call f1() -> ([Field; 3], [Field; 1])
-            return v1
-        }
-        acir(inline) fn foo f1 {
-          b0():
-            v3 = make_array [Field 1, Field 2, Field 3] : [Field; 3]
-            v5 = make_array [Field 4] : [Field; 1]
-            return v3, v5
+          b0(v0: Field, v1: Field):
+            v2 = add v0, v1
+            v3 = mul v2, v0
+            constrain v3 == v1
+            v4 = sub v3, v2
+            return v4
         }
         ";
     assert_ssa_roundtrip(src);
 }
 
 #[test]
-fn test_call_no_return_value() {
-    let src = "
+fn test_block_without_terminator() {
+    let before_closing_brace = "
         acir(inline) fn main f0 {
           b0(v0: Field):
-            call f1(v0)
-            return
+            v1 = add v0, Field 1
         }
-        acir(inline) fn foo f1 {
+        ";
+    let before_next_block = "
+        acir(inline) fn main f0 {
           b0(v0: Field):
+            v1 = add v0, Field 1
+          b1():
             return
         }
         ";
-    assert_ssa_roundtrip(src);
+    for src in [before_closing_brace, before_next_block] {
+        let Err(error) = Ssa::from_str(src) else {
+            panic!("Expected a block without terminator to be rejected");
+        };
+        assert!(matches!(
+            error.error,
+            SsaError::ParserError(ParserError::MissingTerminator { .. })
+        ));
+    }
 }
 
 #[test]
-fn test_call_intrinsic() {
+fn test_adjacent_blocks_without_terminator() {
+    // `b1` directly follows the instructions of `b0`, as if `b0` fell through to it
     let src = "
         acir(inline) fn main f0 {
           b0(v0: Field):
-            call assert_constant(v0)
+            v1 = add v0, Field 1
+          b1(v2: Field):
+            v3 = add v2, Field 1
+          b2():
             return
         }
         ";
-    assert_ssa_roundtrip(src);
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a block without terminator to be rejected");
+    };
+    let SsaError::ParserError(ParserError::MissingTerminator { block, span }) = error.error else {
+        panic!("Expected a missing terminator error, got: {}", error.error);
+    };
+    assert_eq!(block, "b0");
+    assert_eq!(&src[span.start() as usize..span.end() as usize], "b1");
 }
 
 #[test]
-fn test_cast() {
+fn test_bare_return_is_an_empty_return() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field):
-            v1 = cast v0 as i32
-            return v1
+          b0(v0: u1):
+            jmpif v0 then: b1, else: b2
+          b1():
+            return // returns nothing
+          b2():
+            return
         }
         ";
-    assert_ssa_roundtrip(src);
+    let parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+    let blocks = &parsed_ssa.functions[0].blocks;
+    assert_eq!(blocks.len(), 3);
+    for block in &blocks[1..] {
+        assert!(matches!(
+            &block.terminator,
+            ParsedTerminator::Return { values, types: None } if values.is_empty()
+        ));
+    }
+
+    // Without the `return`, the block has no terminator at all
+    let src = src.replace("return // returns nothing", "// returns nothing");
+    let Err(error) = Ssa::from_str(&src) else {
+        panic!("Expected a block without terminator to be rejected");
+    };
+    let SsaError::ParserError(ParserError::MissingTerminator { block, .. }) = error.error else {
+        panic!("Expected a missing terminator error, got: {}", error.error);
+    };
+    assert_eq!(block, "b1");
 }
 
 #[test]
-fn test_constrain() {
+fn test_make_array() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field):
-            constrain v0 == Field 1
-            return
+          b0():
+            v1 = make_array [Field 1] : [Field; 1]
+            return v1
         }
         ";
     assert_ssa_roundtrip(src);
 }
 
 #[test]
-fn test_enable_side_effects() {
+fn test_make_empty_array() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field):
-            enable_side_effects v0
-            return
+          b0():
+            v0 = make_array [] : [Field; 0]
+            return v0
         }
         ";
     assert_ssa_roundtrip(src);
 }
 
 #[test]
-fn test_array_get() {
+fn test_make_composite_array() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: [Field; 3]):
-            v2 = array_get v0, index Field 0 -> Field
-            return
+          b0():
+            v2 = make_array [Field 1, Field 2] : [(Field, Field); 1]
+            return v2
         }
         ";
     assert_ssa_roundtrip(src);
 }
 
 #[test]
-fn test_array_set() {
+fn test_global_shared_by_multiple_functions() {
     let src = "
+        global g0 = [Field 1, Field 2] : [Field; 2]
         acir(inline) fn main f0 {
-          b0(v0: [Field; 3]):
-            v3 = array_set v0, index Field 0, value Field 1
-            return
+          b0(v0: u32):
+            v1 = array_get g0, index v0 -> Field
+            v2, v3 = call f1(v0) -> (Field, [Field; 2])
+            v4 = add v1, v2
+            return v4, g0
+        }
+        acir(inline) fn foo f1 {
+          b0(v0: u32):
+            v1 = array_get g0, index v0 -> Field
+            return v1, g0
         }
         ";
-    assert_ssa_roundtrip(src);
+
+    // Each function builds the global once and shares it between all of its uses
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: u32):
+            v1 = make_array [Field 1, Field 2] : [Field; 2]
+            v2 = array_get v1, index v0 -> Field
+            v3, v4 = call f1(v0) -> (Field, [Field; 2])
+            v5 = add v2, v3
+            return v5, v1
+        }
+        acir(inline) fn foo f1 {
+          b0(v0: u32):
+            v1 = make_array [Field 1, Field 2] : [Field; 2]
+            v2 = array_get v1, index v0 -> Field
+            return v2, v1
+        }
+        ";
+
+    let ssa = Ssa::from_str(src).unwrap();
+    let expected = Ssa::from_str(expected).unwrap();
+    assert_eq!(ssa.to_string(), expected.to_string());
 }
 
 #[test]
-fn test_mutable_array_set() {
+fn test_duplicate_global() {
     let src = "
+        global g0 = [Field 1] : [Field; 1]
+        global g0 = [Field 2] : [Field; 1]
         acir(inline) fn main f0 {
-          b0(v0: [Field; 3]):
-            v3 = array_set mut v0, index Field 0, value Field 1
-            return
+          b0():
+            return g0
         }
         ";
-    assert_ssa_roundtrip(src);
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a duplicate global to be rejected");
+    };
+    assert!(matches!(error.error, SsaError::VariableAlreadyDefined(..)));
 }
 
 #[test]
-fn test_array_get_set_bug() {
+fn test_sparse_and_out_of_order_value_ids() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: [u32; 3]):
-            v3 = array_set v0, index u32 1, value u32 2
-            v5 = array_get v3, index u32 0 -> u32
-            return
+          b0(v7: Field):
+            v12 = add v7, Field 1
+            v3 = mul v12, v12
+            v40 = sub v3, v7
+            return v40
         }
         ";
-    assert_ssa_roundtrip(src);
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v2 = add v0, Field 1
+            v3 = mul v2, v2
+            v4 = sub v3, v0
+            return v4
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap();
+    assert_normalized_ssa_equals(ssa, expected);
 }
 
 #[test]
-fn test_binary() {
-    for op in ["add", "sub", "mul", "div", "eq", "mod", "lt", "and", "or", "xor", "shl", "shr"] {
-        let src = format!(
-            "
-            acir(inline) fn main f0 {{
-              b0(v0: Field, v1: Field):
-                v2 = {op} v0, v1
-                return
-            }}
-            "
-        );
-        assert_ssa_roundtrip(&src);
-    }
+fn test_use_before_definition() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v1 = add v3, Field 1
+            v3 = add Field 1, Field 2
+            return v1
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a use before definition to be rejected");
+    };
+    let SsaError::UnknownVariable(identifier) = error.error else {
+        panic!("Expected an unknown variable error, got: {}", error.error);
+    };
+    assert_eq!(identifier.name, "v3");
 }
 
 #[test]
-fn test_truncate() {
+fn test_verified_ssa() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field):
-            v1 = truncate v0 to 8 bits, max_bit_size: 16
-            return
+          b0(v0: u1, v1: Field):
+            jmpif v0 then: b1, else: b2
+          b1():
+            v2 = add v1, Field 1
+            jmp b3(v2)
+          b2():
+            jmp b3(v1)
+          b3(v3: Field):
+            return v3
         }
         ";
-    assert_ssa_roundtrip(src);
+    let parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+    assert!(parsed_ssa.into_ssa_verified().is_ok());
 }
 
 #[test]
-fn test_not() {
+fn test_verified_ssa_with_value_not_dominated_by_its_definition() {
+    // `v2` is defined in `b1` but `b3` can also be reached through `b2`
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field):
-            v1 = not v0
-            return
+          b0(v0: u1, v1: Field):
+            jmpif v0 then: b1, else: b2
+          b1():
+            v2 = add v1, Field 1
+            jmp b3()
+          b2():
+            jmp b3()
+          b3():
+            return v2
         }
         ";
-    assert_ssa_roundtrip(src);
+    let parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+    let Err(error) = parsed_ssa.into_ssa_verified() else {
+        panic!("Expected the SSA to fail validation");
+    };
+    assert!(matches!(error, SsaError::Validation(..)));
 }
 
 #[test]
-fn test_range_check() {
+fn test_block_parameters() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field):
-            range_check v0 to 8 bits
-            return
+          b0(v0: Field, v1: Field):
+            return v0, v1
         }
         ";
     assert_ssa_roundtrip(src);
 }
 
 #[test]
-fn test_allocate() {
+fn test_multiple_blocks_and_jmp() {
     let src = "
         acir(inline) fn main f0 {
           b0():
-            v0 = allocate -> &mut [Field; 3]
-            return
+            jmp b1(Field 1)
+          b1(v0: Field):
+            return v0
         }
         ";
     assert_ssa_roundtrip(src);
 }
 
 #[test]
-fn test_load() {
+fn test_jmpif() {
     let src = "
         acir(inline) fn main f0 {
           b0(v0: Field):
-            v1 = load v0 -> Field
+            jmpif v0 then: b2, else: b1
+          b2():
+            return
+          b1():
             return
         }
         ";
@@ -352,79 +501,435 @@ fn test_load() {
 }
 
 #[test]
-fn test_store() {
+fn test_merge_block_receives_arguments_from_every_predecessor() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: Field):
-            store Field 1 at v0
-            return
+          b0(v0: u1):
+            jmpif v0 then: b1, else: b2
+          b1():
+            jmp b3(Field 1)
+          b2():
+            jmp b3(Field 2)
+          b3(v1: Field):
+            return v1
         }
         ";
-    assert_ssa_roundtrip(src);
+    assert!(Ssa::from_str(src).is_ok());
 }
 
 #[test]
-fn test_inc_rc() {
+fn test_predecessor_omits_block_argument() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: [Field; 3]):
-            inc_rc v0
-            return
+          b0(v0: u1):
+            jmpif v0 then: b1, else: b2
+          b1():
+            jmp b3(Field 1)
+          b2():
+            jmp b3()
+          b3(v1: Field):
+            return v1
         }
         ";
-    assert_ssa_roundtrip(src);
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected an error when a predecessor omits a block argument");
+    };
+    let SsaError::BlockParameterArityMismatch { block, predecessor } = error.error else {
+        panic!("Expected a block parameter arity mismatch, got: {}", error.error);
+    };
+    assert_eq!(block.name, "b3");
+    assert_eq!(predecessor, "b2");
 }
 
 #[test]
-fn test_dec_rc() {
+fn test_block_argument_of_wrong_type() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: [Field; 3]):
-            dec_rc v0
-            return
+          b0():
+            jmp b1(u32 1)
+          b1(v0: Field):
+            return v0
         }
         ";
-    assert_ssa_roundtrip(src);
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected an error when a block argument has the wrong type");
+    };
+    let SsaError::BlockArgumentTypeMismatch { block, predecessor, expected, found } = error.error
+    else {
+        panic!("Expected a block argument type mismatch, got: {}", error.error);
+    };
+    assert_eq!(block.name, "b1");
+    assert_eq!(predecessor, "b0");
+    assert_eq!(expected, Type::field());
+    assert_eq!(found, Type::unsigned(32));
 }
 
 #[test]
-fn test_mutable_reference_type() {
+fn test_loop_header_with_induction_variable() {
     let src = "
         acir(inline) fn main f0 {
-          b0(v0: &mut Field):
-            return
+          b0(v0: u32):
+            jmp b1(u32 0)
+          b1(v1: u32):
+            v4 = lt v1, u32 4
+            jmpif v4 then: b3, else: b2
+          b3():
+            v6 = add v1, u32 1
+            jmp b1(v6)
+          b2():
+            return v1
         }
         ";
     assert_ssa_roundtrip(src);
 }
 
 #[test]
-fn test_parses_with_comments() {
+fn test_call() {
     let src = "
-        // This is a comment
         acir(inline) fn main f0 {
-          b0(v0: &mut Field): // This is a block
-            return // Returns nothing
+          b0(v0: Field):
+            v2 = call f1(v0) -> Field
+            return v2
+        }
+        acir(inline) fn foo f1 {
+          b0(v0: Field):
+            return v0
         }
         ";
+    assert_ssa_roundtrip(src);
+}
 
-    let expected = "
+#[test]
+fn test_function_ids_come_from_names() {
+    let src = "
         acir(inline) fn main f0 {
-          b0(v0: &mut Field):
+          b0(v0: Field):
+            v2 = call f2(v0) -> Field
+            return v2
+        }
+        acir(inline) fn bar f2 {
+          b0(v0: Field):
+            v2 = call f1(v0) -> Field
+            return v2
+        }
+        acir(inline) fn foo f1 {
+          b0(v0: Field):
+            return v0
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap();
+    assert_eq!(ssa.main_id, Id::test_new(0));
+
+    let names: Vec<_> =
+        ssa.functions.iter().map(|(id, function)| (id.to_string(), function.name())).collect();
+    assert_eq!(
+        names,
+        vec![("f0".to_string(), "main"), ("f1".to_string(), "foo"), ("f2".to_string(), "bar")]
+    );
+
+    // The calls still go to the same functions once printed
+    let printed = ssa.to_string();
+    assert!(printed.contains("acir(inline) fn bar f2 {"));
+    assert!(printed.contains("call f1(v0) -> Field"));
+    assert!(Ssa::from_str(&printed).unwrap().alpha_equivalent(&ssa));
+}
+
+#[test]
+fn test_invalid_function_name() {
+    for name in ["main", "f", "f01", "g1"] {
+        let src = format!(
+            "
+            acir(inline) fn main {name} {{
+              b0():
+                return
+            }}
+            "
+        );
+        let Err(error) = Ssa::from_str(&src) else {
+            panic!("Expected function name '{name}' to be rejected");
+        };
+        let SsaError::InvalidFunctionName(identifier) = error.error else {
+            panic!("Expected an invalid function name error, got: {}", error.error);
+        };
+        assert_eq!(identifier.name, name);
+    }
+}
+
+#[test]
+fn test_duplicate_function_name() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            return
+        }
+        acir(inline) fn foo f0 {
+          b0():
+            return
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected functions with the same name to be rejected");
+    };
+    assert!(matches!(error.error, SsaError::FunctionAlreadyDefined(..)));
+}
+
+#[test]
+fn test_call_multiple_return_values() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v1, v2 = call f1() -> ([Field; 3], [Field; 1])
+            return v1
+        }
+        acir(inline) fn foo f1 {
+          b0():
+            v3 = make_array [Field 1, Field 2, Field 3] : [Field; 3]
+            v5 = make_array [Field 4] : [Field; 1]
+            return v3, v5
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_function_passed_as_value() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v3 = call f2(f1, v0) -> Field
+            return v3
+        }
+        brillig(inline) fn double f1 {
+          b0(v0: Field):
+            v2 = mul v0, Field 2
+            return v2
+        }
+        brillig(inline) fn apply f2 {
+          b0(v0: function, v1: Field):
+            v2 = call v0(v1) -> Field
+            return v2
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_call_no_return_value() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            call f1(v0)
+            return
+        }
+        acir(inline) fn foo f1 {
+          b0(v0: Field):
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_foreign_call() {
+    let src = "
+        brillig(inline) fn main f0 {
+          b0(v0: Field):
+            v2 = foreign \"get_sibling_index\"(v0) -> Field
+            return v2
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_acir_function_calling_brillig_function() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v2 = call f1(v0) -> Field
+            return v2
+        }
+        brillig(inline) fn foo f1 {
+          b0(v0: Field):
+            return v0
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_brillig_function_calling_acir_function() {
+    let src = "
+        brillig(inline) fn main f0 {
+          b0(v0: Field):
+            v2 = call f1(v0) -> Field
+            return v2
+        }
+        acir(fold) fn foo f1 {
+          b0(v0: Field):
+            return v0
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a brillig function calling an ACIR function to be rejected");
+    };
+    let SsaError::IncompatibleCallRuntimes { callee, .. } = &error.error else {
+        panic!("Expected incompatible call runtimes, got: {}", error.error);
+    };
+    assert_eq!(callee.name, "f1");
+    assert_eq!(
+        error.error.to_string(),
+        "A brillig(inline) function cannot call acir(fold) function 'f1'"
+    );
+}
+
+#[test]
+fn test_call_intrinsic() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            call assert_constant(v0)
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_slice_push_back() {
+    // Slice intrinsics take the slice length before the slice and return the new length
+    // before the new slice
+    let src = "
+        brillig(inline) fn main f0 {
+          b0(v0: u32, v1: [Field], v2: Field):
+            v4, v5 = call slice_push_back(v0, v1, v2) -> (u32, [Field])
+            return v4, v5
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_slice_pop_front() {
+    let src = "
+        brillig(inline) fn main f0 {
+          b0(v0: u32, v1: [(Field, u8)]):
+            v3, v4, v5, v6 = call slice_pop_front(v0, v1) -> (Field, u8, u32, [(Field, u8)])
+            return v3, v4, v5, v6
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_call_black_box() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: [u64; 25]):
+            v2 = call keccakf1600(v0) -> [u64; 25]
+            return v2
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_call_unknown_intrinsic() {
+    // `keccak256` isn't a black box function, so it is looked up as a function name
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: [u8; 32]):
+            v2 = call keccak256(v0) -> [u8; 32]
+            return v2
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a call to an unknown intrinsic to be rejected");
+    };
+    let SsaError::UnknownFunction(identifier) = error.error else {
+        panic!("Expected an unknown function error, got: {}", error.error);
+    };
+    assert_eq!(identifier.name, "keccak256");
+}
+
+#[test]
+fn test_cast() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v1 = cast v0 as i32
+            return v1
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_constrain() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            constrain v0 == Field 1
             return
         }
         ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_if_else() {
+    // Selecting between numeric values is simplified into arithmetic, so arrays are used here
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: u1, v1: [Field; 2], v2: [Field; 2]):
+            v3 = if v0 then v1 else v2
+            return v3
+        }
+        ";
+    assert_ssa_roundtrip(src);
 
     let ssa = Ssa::from_str(src).unwrap();
-    assert_normalized_ssa_equals(ssa, expected);
+    let main = ssa.main();
+    let instructions = main.dfg[main.entry_block()].instructions();
+    assert_eq!(instructions.len(), 1);
+    assert!(matches!(main.dfg[instructions[0]], Instruction::IfElse { .. }));
 }
 
 #[test]
-fn test_slice() {
+fn test_enable_side_effects() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            enable_side_effects v0
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_toggling_side_effects() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: u1, v1: &mut Field):
+            enable_side_effects v0
+            store Field 1 at v1
+            v3 = not v0
+            enable_side_effects v3
+            store Field 2 at v1
+            enable_side_effects u1 1
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_array_get() {
     let src = "
         acir(inline) fn main f0 {
           b0(v0: [Field; 3]):
-            v2, v3 = call as_slice(v0) -> (u32, [Field])
+            v2 = array_get v0, index Field 0 -> Field
             return
         }
         ";
@@ -432,12 +937,1343 @@ fn test_slice() {
 }
 
 #[test]
-fn test_negative() {
+fn test_array_set() {
     let src = "
         acir(inline) fn main f0 {
-          b0():
-            return Field -1
+          b0(v0: [Field; 3]):
+            v3 = array_set v0, index Field 0, value Field 1
+            return
         }
         ";
     assert_ssa_roundtrip(src);
 }
+
+#[test]
+fn test_negative_array_index() {
+    for (instruction, index) in [
+        ("array_get v0, index u32 -1 -> u32", "u32"),
+        ("array_get v0, index -1u32 -> u32", "-"),
+        ("array_set v0, index Field -2, value u32 1", "Field"),
+    ] {
+        let src = format!(
+            "
+            acir(inline) fn main f0 {{
+              b0(v0: [u32; 3]):
+                v2 = {instruction}
+                return
+            }}
+            "
+        );
+        let Err(error) = Ssa::from_str(&src) else {
+            panic!("Expected a negative array index to be rejected in `{instruction}`");
+        };
+        let SsaError::ParserError(ParserError::NegativeArrayIndex { span }) = error.error else {
+            panic!("Expected a negative array index error, got: {}", error.error);
+        };
+        assert_eq!(&src[span.start() as usize..span.end() as usize], index);
+    }
+}
+
+#[test]
+fn test_mutable_array_set() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: [Field; 3]):
+            v3 = array_set mut v0, index Field 0, value Field 1
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_array_get_set_bug() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: [u32; 3]):
+            v3 = array_set v0, index u32 1, value u32 2
+            v5 = array_get v3, index u32 0 -> u32
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_binary() {
+    for op in ["add", "sub", "mul", "div", "eq", "mod", "lt", "and", "or", "xor", "shl", "shr"] {
+        let src = format!(
+            "
+            acir(inline) fn main f0 {{
+              b0(v0: Field, v1: Field):
+                v2 = {op} v0, v1
+                return
+            }}
+            "
+        );
+        assert_ssa_roundtrip(&src);
+    }
+}
+
+#[test]
+fn test_truncate() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v1 = truncate v0 to 8 bits, max_bit_size: 16
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_not() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v1 = not v0
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_range_check() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            range_check v0 to 8 bits
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_range_check_with_message() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            range_check v0 to 8 bits, \"value does not fit in a byte\"
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_range_check_bit_size_larger_than_field() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            range_check v0 to 255 bits
+            return
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a bit size larger than a field element to be rejected");
+    };
+    let SsaError::ParserError(ParserError::RangeCheckBitSizeTooLarge { span, .. }) = error.error
+    else {
+        panic!("Expected a range check bit size error, got: {}", error.error);
+    };
+    assert_eq!(&src[span.start() as usize..span.end() as usize], "255");
+}
+
+#[test]
+fn test_allocate() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v0 = allocate -> &mut [Field; 3]
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_function_returning_reference_parameter() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v0 = allocate -> &mut Field
+            v2 = call f1(v0) -> &mut Field
+            return v2
+        }
+        brillig(inline) fn identity f1 {
+          b0(v0: &mut Field):
+            jmp b1(v0)
+          b1(v1: &mut Field):
+            return v1
+        }
+        ";
+    assert_ssa_roundtrip(src);
+
+    let ssa = Ssa::from_str(src).unwrap();
+    let reference = Type::Reference(Arc::new(Type::field()));
+
+    let main = ssa.main();
+    assert_eq!(main.dfg.type_of_value(main.returns()[0]), reference);
+
+    let identity = ssa.functions.values().find(|function| function.name() == "identity").unwrap();
+    assert_eq!(identity.dfg.type_of_value(identity.parameters()[0]), reference);
+    assert_eq!(identity.dfg.type_of_value(identity.returns()[0]), reference);
+}
+
+#[test]
+fn test_load() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v1 = load v0 -> Field
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_store() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            store Field 1 at v0
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_load_and_store_with_alias_metadata() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: &mut Field, v1: &mut Field):
+            v2 = load v0 -> Field // alias: v0, v1
+            // alias: not attached to the store below
+            store v2 at v1
+            store Field 1 at v0 // alias: v0
+            return v2
+        }
+        ";
+    let parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+    let aliases: Vec<_> = parsed_ssa.functions[0].blocks[0]
+        .instructions
+        .iter()
+        .map(|instruction| match instruction {
+            ParsedInstruction::Load { alias, .. } | ParsedInstruction::Store { alias, .. } => {
+                alias.clone()
+            }
+            _ => panic!("Expected only loads and stores"),
+        })
+        .collect();
+    assert_eq!(aliases, vec![Some("v0, v1".to_string()), None, Some("v0".to_string())]);
+
+    // The metadata doesn't change the resulting SSA
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: &mut Field, v1: &mut Field):
+            v2 = load v0 -> Field
+            store v2 at v1
+            store Field 1 at v0
+            return v2
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap();
+    assert_eq!(
+        trim_leading_whitespace_from_lines(&ssa.to_string()),
+        trim_leading_whitespace_from_lines(expected)
+    );
+}
+
+#[test]
+fn test_inc_rc() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: [Field; 3]):
+            inc_rc v0
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_dec_rc() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: [Field; 3]):
+            dec_rc v0
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_rc_instructions_in_brillig() {
+    let src = "
+        brillig(inline) fn main f0 {
+          b0(v0: [Field; 3], v1: &mut [Field; 3], v2: Field):
+            inc_rc v0
+            v4 = array_set v0, index u32 0, value v2
+            dec_rc v0
+            inc_rc v1
+            dec_rc v2
+            return v4
+        }
+        ";
+    // The rc instructions are kept as written, without loading through references or
+    // dropping those on non-array values, since they affect brillig memory behavior
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_mutable_reference_type() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: &mut Field):
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_aligned_printing() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field, v1: Field, v2: Field, v3: Field, v4: Field, v5: Field, v6: Field, v7: Field):
+            v8 = add v0, v1
+            v9 = add v8, v2
+            v10 = add v9, v3
+            v11 = add v10, v4
+            constrain v11 == v5
+            return v11
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap();
+
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: Field, v1: Field, v2: Field, v3: Field, v4: Field, v5: Field, v6: Field, v7: Field):
+            v8  = add v0, v1
+            v9  = add v8, v2
+            v10 = add v9, v3
+            v11 = add v10, v4
+            constrain v11 == v5
+            return v11
+        }
+        ";
+    let aligned = format!("{ssa:#}");
+    similar_asserts::assert_eq!(
+        trim_leading_whitespace_from_lines(&aligned),
+        trim_leading_whitespace_from_lines(expected)
+    );
+
+    // The aligned output parses back to the same SSA
+    let reparsed = Ssa::from_str(&aligned).unwrap();
+    assert_eq!(reparsed.to_string(), ssa.to_string());
+}
+
+#[test]
+fn test_parses_with_comments() {
+    let src = "
+        // This is a comment
+        acir(inline) fn main f0 {
+          b0(v0: &mut Field): // This is a block
+            return // Returns nothing
+        }
+        ";
+
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: &mut Field):
+            return
+        }
+        ";
+
+    let ssa = Ssa::from_str(src).unwrap();
+    assert_normalized_ssa_equals(ssa, expected);
+}
+
+#[test]
+fn test_slice() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: [Field; 3]):
+            v2, v3 = call as_slice(v0) -> (u32, [Field])
+            return
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_make_slice_and_array() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v3 = make_array [Field 1, Field 2, Field 3] : [Field; 3]
+            v4 = make_array [Field 1, Field 2, Field 3] : [Field]
+            return v3, u32 3, v4
+        }
+        ";
+    assert_ssa_roundtrip(src);
+
+    let ssa = Ssa::from_str(src).unwrap();
+    let main = ssa.main();
+    let element_types = Arc::new(vec![Type::field()]);
+    let returns = main.returns();
+    assert_eq!(main.dfg.type_of_value(returns[0]), Type::Array(element_types.clone(), 3));
+    assert_eq!(main.dfg.type_of_value(returns[2]), Type::Slice(element_types));
+}
+
+#[test]
+fn test_negative() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            return Field -1
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_u128_max() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            return u128 340282366920938463463374607431768211455
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_largest_field_constant() {
+    // The modulus minus one is printed back as `-1`
+    let expected = "
+        acir(inline) fn main f0 {
+          b0():
+            return Field -1
+        }
+        ";
+    let decimal = "21888242871839275222246405745257275088548364400416034343698204186575808495616";
+    let hexadecimal = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000";
+
+    for constant in [decimal, hexadecimal] {
+        let src = format!(
+            "
+            acir(inline) fn main f0 {{
+              b0():
+                return Field {constant}
+            }}
+            "
+        );
+        let ssa = Ssa::from_str(&src).unwrap();
+        assert_normalized_ssa_equals(ssa, expected);
+    }
+}
+
+#[test]
+fn test_field_constant_at_modulus_is_rejected() {
+    let decimal = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+    let hexadecimal = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+
+    for constant in [decimal, hexadecimal] {
+        let src = format!(
+            "
+            acir(inline) fn main f0 {{
+              b0():
+                return Field {constant}
+            }}
+            "
+        );
+        let Err(error) = Ssa::from_str(&src) else {
+            panic!("Expected {constant} to be rejected");
+        };
+        assert!(matches!(
+            error.error,
+            SsaError::ParserError(ParserError::LexerError(
+                LexerError::IntegerLiteralTooLarge { .. }
+            ))
+        ));
+    }
+}
+
+#[test]
+fn test_lexer_spans_cover_multi_char_operators() {
+    let mut lexer = Lexer::new("v0 == v1 -> Field");
+    let mut spans = Vec::new();
+    loop {
+        let token = lexer.next_token().unwrap();
+        if token.token() == &Token::Eof {
+            break;
+        }
+        let span = token.to_span();
+        spans.push((token.into_token().to_string(), span.start(), span.end()));
+    }
+
+    let expected = vec![
+        ("v0".to_string(), 0, 2),
+        ("==".to_string(), 3, 5),
+        ("v1".to_string(), 6, 8),
+        ("->".to_string(), 9, 11),
+        ("Field".to_string(), 12, 17),
+    ];
+    assert_eq!(spans, expected);
+}
+
+#[test]
+fn test_lexer_spans_are_byte_offsets_with_tabs() {
+    let src = "b0():\n\t\tv1 = not v0\n";
+    let mut lexer = Lexer::new(src);
+    let mut spans = Vec::new();
+    loop {
+        let token = lexer.next_token().unwrap();
+        if token.token() == &Token::Eof {
+            break;
+        }
+        let span = token.to_span();
+        let text = &src[span.start() as usize..span.end() as usize];
+        spans.push((token.into_token().to_string(), text.to_string(), span.start()));
+    }
+
+    let expected = vec![
+        ("b0".to_string(), "b0".to_string(), 0),
+        ("(".to_string(), "(".to_string(), 2),
+        (")".to_string(), ")".to_string(), 3),
+        (":".to_string(), ":".to_string(), 4),
+        ("v1".to_string(), "v1".to_string(), 8),
+        ("=".to_string(), "=".to_string(), 11),
+        ("not".to_string(), "not".to_string(), 13),
+        ("v0".to_string(), "v0".to_string(), 17),
+    ];
+    assert_eq!(spans, expected);
+}
+
+#[test]
+fn test_lexer_iterator() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            return v0
+        }
+        ";
+    let tokens = Lexer::new(src)
+        .map(|token| token.unwrap().into_token().to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    assert_eq!(
+        tokens,
+        "acir ( inline ) fn main f0 { b0 ( v0 : Field ) : return v0 } (end of stream)"
+    );
+}
+
+#[test]
+fn test_walk_mut_rewrites_constants() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v3 = make_array [Field 1, v0, Field 2] : [Field; 3]
+            return v3, Field 3
+        }
+        ";
+    let mut parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+
+    let mut constants = 0;
+    parsed_ssa.walk(|node| {
+        if let ParsedNode::Value(ParsedValue::NumericConstant { .. }) = node {
+            constants += 1;
+        }
+    });
+    assert_eq!(constants, 3);
+
+    parsed_ssa.walk_mut(|node| {
+        if let ParsedNodeMut::Value(ParsedValue::NumericConstant { constant, .. }) = node {
+            *constant = FieldElement::zero();
+        }
+    });
+
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v2 = make_array [Field 0, v0, Field 0] : [Field; 3]
+            return v2, Field 0
+        }
+        ";
+    let ssa = parsed_ssa.into_ssa().unwrap();
+    assert_normalized_ssa_equals(ssa, expected);
+}
+
+#[test]
+fn test_collect_errors_of_all_functions() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v2 = call f1(v0) -> Field
+            v3 = call f2(v0) -> Field
+            return v3
+        }
+        acir(inline) fn foo f1 {
+          b0(v0: Field):
+            v1 = add v0, v9
+            return v1
+        }
+        acir(inline) fn bar f2 {
+          b0(v0: Field):
+            jmp b7()
+        }
+        ";
+    let parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+    let Err(errors) = parsed_ssa.into_ssa_collect_errors() else {
+        panic!("Expected both functions to be reported");
+    };
+
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(&errors[0], SsaError::UnknownVariable(identifier) if identifier.name == "v9"));
+    assert!(matches!(&errors[1], SsaError::UnknownBlock(identifier) if identifier.name == "b7"));
+}
+
+#[test]
+fn test_collect_errors_stops_at_main() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            return v1
+        }
+        acir(inline) fn foo f1 {
+          b0():
+            return v2
+        }
+        ";
+    let parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+    let Err(errors) = parsed_ssa.into_ssa_collect_errors() else {
+        panic!("Expected main to be reported");
+    };
+
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(&errors[0], SsaError::UnknownVariable(identifier) if identifier.name == "v1"));
+}
+
+#[test]
+fn test_empty_input() {
+    for src in ["", "  \n\t  \n"] {
+        let Err(error) = Ssa::from_str(src) else {
+            panic!("Expected input without functions to be rejected");
+        };
+        assert!(matches!(error.error, SsaError::NoFunctions));
+    }
+}
+
+#[test]
+fn test_integer_division_matches_ssa_generation() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: u32, v1: u32):
+            v2 = div v0, v1
+            v3 = mod v0, v1
+            return v2, v3
+        }
+        ";
+    let parsed = Ssa::from_str(src).unwrap();
+
+    // Build `(v0 / v1, v0 % v1)` the way SSA generation does
+    let mut builder = FunctionBuilder::new("main".into(), Id::test_new(0));
+    let v0 = builder.add_parameter(Type::unsigned(32));
+    let v1 = builder.add_parameter(Type::unsigned(32));
+    let v2 = builder.insert_binary(v0, BinaryOp::Div, v1);
+    let v3 = builder.insert_binary(v0, BinaryOp::Mod, v1);
+    builder.terminate_with_return(vec![v2, v3]);
+    let generated = builder.finish();
+
+    assert_eq!(parsed.to_string(), generated.to_string());
+}
+
+#[test]
+fn test_division_by_constant_zero_is_kept() {
+    // The division must reach ACIR generation, where it fails, rather than being folded
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: u32):
+            v2 = div v0, u32 0
+            v3 = mod v0, u32 0
+            v5 = div u32 7, u32 0
+            return v2, v3, v5
+        }
+        ";
+    assert_ssa_roundtrip(src);
+}
+
+#[test]
+fn test_duplicate_block() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            jmp b1()
+          b1():
+            jmp b1()
+          b1():
+            return
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a block defined twice to be rejected");
+    };
+    assert!(matches!(
+        error.error,
+        SsaError::ParserError(ParserError::DuplicateBlock { name }) if name.name == "b1"
+    ));
+}
+
+#[test]
+fn test_unreachable_terminator() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: u1):
+            jmpif v0 then: b2, else: b1
+          b2():
+            unreachable
+          b1():
+            v2 = make_array [Field 1] : [Field; 1]
+            return v2, u32 1
+        }
+        ";
+
+    // The block fails unconditionally and returns zeroed values of the function's return types
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: u1):
+            jmpif v0 then: b2, else: b1
+          b2():
+            constrain u1 0 == u1 1
+            v7 = make_array [Field 0] : [Field; 1]
+            return v7, u32 0
+          b1():
+            v2 = make_array [Field 1] : [Field; 1]
+            return v2, u32 1
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap().to_string();
+    similar_asserts::assert_eq!(
+        trim_leading_whitespace_from_lines(&ssa),
+        trim_leading_whitespace_from_lines(expected)
+    );
+}
+
+#[test]
+fn test_array_element_constants_take_the_array_element_type() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v0 = make_array [Field 1, Field 2] : [u8; 2]
+            return v0
+        }
+        ";
+    let expected = "
+        acir(inline) fn main f0 {
+          b0():
+            v2 = make_array [u8 1, u8 2] : [u8; 2]
+            return v2
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap().to_string();
+    similar_asserts::assert_eq!(
+        trim_leading_whitespace_from_lines(&ssa),
+        trim_leading_whitespace_from_lines(expected)
+    );
+}
+
+#[test]
+fn test_array_element_constant_overflow() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v0 = make_array [u8 1, u8 300] : [u8; 2]
+            return v0
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected an element that does not fit the array's element type to be rejected");
+    };
+    assert!(matches!(
+        error.error,
+        SsaError::ConstantDoesNotFitType { constant, typ: Type::Numeric(NumericType::Unsigned { bit_size: 8 }), .. }
+            if constant == FieldElement::from(300_u128)
+    ));
+}
+
+#[test]
+fn test_nested_array_constant() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v2 = make_array [Field 1, Field 2] : [Field; 2]
+            v5 = make_array [Field 3, Field 4] : [Field; 2]
+            v6 = make_array [v2, v5] : [[Field; 2]; 2]
+            return v6
+        }
+        ";
+    assert_ssa_roundtrip(src);
+
+    let ssa = Ssa::from_str(src).unwrap();
+    let main = ssa.main();
+    let inner = Type::Array(Arc::new(vec![Type::field()]), 2);
+    assert_eq!(main.dfg.type_of_value(main.returns()[0]), Type::Array(Arc::new(vec![inner]), 2));
+}
+
+#[test]
+fn test_nested_array_element_with_wrong_shape() {
+    // A numeric constant where an array is expected
+    let constant_element = "
+        acir(inline) fn main f0 {
+          b0():
+            v2 = make_array [Field 1, Field 2] : [[Field; 2]; 1]
+            return v2
+        }
+        ";
+    // An inner array of the wrong length
+    let short_element = "
+        acir(inline) fn main f0 {
+          b0():
+            v1 = make_array [Field 1] : [Field; 1]
+            v2 = make_array [v1, v1] : [[Field; 2]; 2]
+            return v2
+        }
+        ";
+    for src in [constant_element, short_element] {
+        let Err(error) = Ssa::from_str(src) else {
+            panic!("Expected an element with the wrong shape to be rejected");
+        };
+        assert!(matches!(error.error, SsaError::MismatchedArrayElementShape { .. }));
+    }
+}
+
+#[test]
+fn test_lexer_typed_int() {
+    let tokens = Lexer::new("255u8 10i8 0xffu16 5")
+        .map(|token| token.unwrap().into_token())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::TypedInt(FieldElement::from(255_u128), IntType::Unsigned(8)),
+            Token::TypedInt(FieldElement::from(10_u128), IntType::Signed(8)),
+            Token::TypedInt(FieldElement::from(255_u128), IntType::Unsigned(16)),
+            Token::Int(FieldElement::from(5_u128)),
+            Token::Eof,
+        ]
+    );
+
+    // The lexer does not know about the sign of a literal, so range checks are left to the parser
+    let tokens = Lexer::new("256u8").map(|token| token.unwrap().into_token()).collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        vec![Token::TypedInt(FieldElement::from(256_u128), IntType::Unsigned(8)), Token::Eof]
+    );
+
+    let error = Lexer::new("5u").next_token().unwrap_err();
+    assert!(matches!(error, LexerError::InvalidIntegerLiteral { .. }));
+}
+
+#[test]
+fn test_typed_int_values() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: u8, v1: i8):
+            v2 = add v0, 255u8
+            v3 = lt v1, -1i8
+            v4 = lt v1, 127i8
+            return v2, v3, v4
+        }
+        ";
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: u8, v1: i8):
+            v3 = add v0, u8 255
+            v5 = lt v1, i8 -1
+            v7 = lt v1, i8 127
+            return v3, v5, v7
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap().to_string();
+    similar_asserts::assert_eq!(
+        trim_leading_whitespace_from_lines(&ssa),
+        trim_leading_whitespace_from_lines(expected)
+    );
+}
+
+#[test]
+fn test_typed_int_overflow() {
+    for src in ["256u8", "128i8", "-129i8", "-1u8"] {
+        let src = format!(
+            "
+            acir(inline) fn main f0 {{
+              b0():
+                return {src}
+            }}
+            "
+        );
+        let Err(error) = Ssa::from_str(&src) else {
+            panic!("Expected {src} to be rejected");
+        };
+        assert!(matches!(
+            error.error,
+            SsaError::ParserError(ParserError::IntegerLiteralOutOfRange { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_jmpif_true() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            jmpif true then: b2, else: b1
+          b2():
+            return Field 1
+          b1():
+            return Field 2
+        }
+        ";
+    let expected = "
+        acir(inline) fn main f0 {
+          b0():
+            jmpif u1 1 then: b2, else: b1
+          b2():
+            return Field 1
+          b1():
+            return Field 2
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap().to_string();
+    similar_asserts::assert_eq!(
+        trim_leading_whitespace_from_lines(&ssa),
+        trim_leading_whitespace_from_lines(expected)
+    );
+}
+
+#[test]
+fn test_constrain_false() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: u1):
+            constrain v0 == false
+            enable_side_effects true
+            return
+        }
+        ";
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: u1):
+            constrain v0 == u1 0
+            enable_side_effects u1 1
+            return
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap().to_string();
+    similar_asserts::assert_eq!(
+        trim_leading_whitespace_from_lines(&ssa),
+        trim_leading_whitespace_from_lines(expected)
+    );
+}
+
+#[test]
+fn test_lexer_error_position_is_reported() {
+    let src = "acir(inline) fn main f0 {\n  b0():\n    return @\n}";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected an illegal character to be rejected");
+    };
+    assert!(matches!(
+        error.error,
+        SsaError::ParserError(ParserError::LexerError(LexerError::UnexpectedCharacter {
+            char: '@',
+            ..
+        }))
+    ));
+
+    let expected = "acir(inline) fn main f0 {
+  b0():
+
+    return @
+           ^
+           Unexpected character: '@'
+
+}
+";
+    assert_eq!(error.to_string(), expected);
+}
+
+#[test]
+fn test_error_position_is_reported_below_tab_indented_line() {
+    let src = "acir(inline) fn main f0 {\n\tb0():\n\t\treturn @\n}";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected an illegal character to be rejected");
+    };
+
+    let expected = "acir(inline) fn main f0 {
+\tb0():
+
+\t\treturn @
+\t\t       ^
+\t\t       Unexpected character: '@'
+
+}
+";
+    assert_eq!(error.to_string(), expected);
+}
+
+#[test]
+fn test_directives_are_collected_in_order() {
+    let src = "
+        //@ expect: optimized
+        acir(inline) fn main f0 {
+          b0():
+            // A regular comment is not a directive
+            return
+            //@ passes: mem2reg
+        }
+        ";
+    let parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+    assert_eq!(parsed_ssa.directives, vec!["expect: optimized", "passes: mem2reg"]);
+}
+
+#[test]
+fn test_translating_several_parsed_ssa() {
+    let first = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            return v0
+        }
+        ";
+    // `foo` has the same ID as `main`, so it is given a new one
+    let second = "
+        acir(inline) fn bar f1 {
+          b0(v0: Field):
+            v2 = call f0(v0) -> Field
+            return v2
+        }
+        acir(inline) fn foo f0 {
+          b0(v0: Field):
+            return v0
+        }
+        ";
+    let first = Parser::new(first).unwrap().parse_ssa().unwrap();
+    let second = Parser::new(second).unwrap().parse_ssa().unwrap();
+
+    let mut translator = Translator::from_parsed(first).unwrap();
+    translator.add_parsed(second).unwrap();
+    let ssa = translator.finish();
+
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            return v0
+        }
+        acir(inline) fn bar f1 {
+          b0(v0: Field):
+            v2 = call f2(v0) -> Field
+            return v2
+        }
+        acir(inline) fn foo f2 {
+          b0(v0: Field):
+            return v0
+        }
+        ";
+    assert_normalized_ssa_equals(ssa, expected);
+}
+
+#[test]
+fn test_header_is_the_leading_comment() {
+    let src = "
+        // noir-ssa v1
+        acir(inline) fn main f0 {
+          b0():
+            // Only the first line can be a header
+            return
+        }
+        ";
+    let parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+    assert_eq!(parsed_ssa.header.as_deref(), Some("noir-ssa v1"));
+
+    let src = "
+        //@ expect: optimized
+        acir(inline) fn main f0 {
+          b0():
+            return
+        }
+        ";
+    let parsed_ssa = Parser::new(src).unwrap().parse_ssa().unwrap();
+    assert_eq!(parsed_ssa.header, None);
+}
+
+#[test]
+fn test_call_with_typed_results() {
+    let src = "
+        brillig(inline) fn main f0 {
+          b0(v0: Field):
+            v2: Field, v3: [u8; 4] = call f1(v0)
+            return v2, v3
+        }
+        brillig(inline) fn foo f1 {
+          b0(v0: Field):
+            v1 = make_array [u8 1, u8 2, u8 3, u8 4] : [u8; 4]
+            return v0, v1
+        }
+        ";
+    // Result types are printed after the call
+    let expected = "
+        brillig(inline) fn main f0 {
+          b0(v0: Field):
+            v2, v3 = call f1(v0) -> (Field, [u8; 4])
+            return v2, v3
+        }
+        brillig(inline) fn foo f1 {
+          b0(v0: Field):
+            v5 = make_array [u8 1, u8 2, u8 3, u8 4] : [u8; 4]
+            return v0, v5
+        }
+        ";
+    let ssa = Ssa::from_str(src).unwrap();
+    let main = ssa.main();
+    let results = main.dfg.instruction_results(main.dfg[main.entry_block()].instructions()[0]);
+    assert_eq!(main.dfg.type_of_value(results[0]), Type::field());
+    assert_eq!(
+        main.dfg.type_of_value(results[1]),
+        Type::Array(Arc::new(vec![Type::unsigned(8)]), 4)
+    );
+
+    similar_asserts::assert_eq!(
+        trim_leading_whitespace_from_lines(&ssa.to_string()),
+        trim_leading_whitespace_from_lines(expected)
+    );
+    assert_ssa_roundtrip(expected);
+}
+
+#[test]
+fn test_call_with_some_untyped_results() {
+    let src = "
+        brillig(inline) fn main f0 {
+          b0(v0: Field):
+            v2: Field, v3 = call f1(v0)
+            return v2, v3
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a call giving only some of its result types to be rejected");
+    };
+    assert!(matches!(
+        error.error,
+        SsaError::ParserError(ParserError::MissingResultType { target }) if target.name == "v3"
+    ));
+}
+
+#[test]
+fn test_binary_op_with_too_many_results() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v1, v2 = add v0, v0
+            return v1
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a binary operation with two results to be rejected");
+    };
+    let SsaError::ParserError(ParserError::WrongResultCount { expected, found, span }) =
+        error.error
+    else {
+        panic!("Expected a wrong result count error, got: {}", error.error);
+    };
+    assert_eq!((expected, found), (1, 2));
+    assert_eq!(&src[span.start() as usize..span.end() as usize], "v2");
+}
+
+#[test]
+fn test_binary_op_without_result() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            add v0, v0
+            return v0
+        }
+        ";
+    let Err(error) = Ssa::from_str(src) else {
+        panic!("Expected a binary operation without result to be rejected");
+    };
+    let SsaError::ParserError(ParserError::WrongResultCount { expected, found, span }) =
+        error.error
+    else {
+        panic!("Expected a wrong result count error, got: {}", error.error);
+    };
+    assert_eq!((expected, found), (1, 0));
+    assert_eq!(&src[span.start() as usize..span.end() as usize], "add");
+}
+
+/// Collects what an `SsaBuilder` prints so that tests can inspect it.
+#[derive(Clone, Default)]
+struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl SharedOutput {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_builder_from_str_prints_passes() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v1 = add v0, Field 1
+            v2 = add v0, Field 1
+            return v1, v2
+        }
+        ";
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v2 = add v0, Field 1
+            return v2, v2
+        }
+        ";
+
+    for print_ssa_passes in [false, true] {
+        let output = SharedOutput::default();
+        let ssa = SsaBuilder::from_str(src, print_ssa_passes, false)
+            .unwrap()
+            .with_output(output.clone())
+            .run_pass(Ssa::fold_constants, "After Constant Folding:")
+            .finish();
+        assert_normalized_ssa_equals(ssa, expected);
+
+        let output = output.contents();
+        if print_ssa_passes {
+            let (message, printed_ssa) = output.split_once('\n').unwrap();
+            assert_eq!(message, "After Constant Folding:");
+            assert_eq!(
+                trim_leading_whitespace_from_lines(printed_ssa),
+                trim_leading_whitespace_from_lines(expected)
+            );
+        } else {
+            assert!(output.is_empty());
+        }
+    }
+}
+
+#[test]
+fn test_optimize_ssa_text() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v2 = call f1(v0) -> Field
+            return v2
+        }
+        acir(inline) fn add_one f1 {
+          b0(v0: Field):
+            v2 = add v0, Field 1
+            return v2
+        }
+        ";
+    let expected = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            v1 = add v0, Field 1
+            return v1
+        }
+        ";
+
+    let optimized = optimize_ssa_text(src, i64::MAX).unwrap();
+    let optimized = Ssa::from_str(&optimized).unwrap();
+    assert!(optimized.alpha_equivalent(&Ssa::from_str(expected).unwrap()));
+}
+
+#[test]
+fn test_optimize_keeping_brillig_functions() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v2 = call f1(Field 2) -> Field
+            return v2
+        }
+        brillig(inline) fn add_one f1 {
+          b0(v0: Field):
+            v2 = add v0, Field 1
+            return v2
+        }
+        ";
+
+    for keep_brillig_functions in [false, true] {
+        let mut builder =
+            SsaBuilder::from_str(src, false, false).unwrap().with_inline_const_brillig_calls(true);
+        if keep_brillig_functions {
+            builder = builder.keep_brillig_functions();
+        }
+        let ssa = optimize_ssa_before_brillig(builder, i64::MAX.into()).unwrap().finish();
+
+        // The call is replaced by its result either way...
+        let main = ssa.main();
+        assert!(main.dfg[main.entry_block()].instructions().is_empty());
+
+        // ...but the function it called is only kept if asked to
+        let expected_functions = if keep_brillig_functions { 2 } else { 1 };
+        assert_eq!(ssa.functions.len(), expected_functions);
+    }
+}
+
+#[test]
+fn test_optimize_with_inline_const_brillig_calls_constants_limit() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0():
+            v2 = call f1(Field 2) -> [Field; 2]
+            return v2
+        }
+        brillig(inline) fn pair f1 {
+          b0(v0: Field):
+            v1 = make_array [v0, v0] : [Field; 2]
+            return v1
+        }
+        ";
+
+    // The call returns 2 constants, so it's only replaced if the limit allows for both
+    for (limit, replaced) in [(Some(1), false), (Some(2), true), (None, true)] {
+        let builder = SsaBuilder::from_str(src, false, false)
+            .unwrap()
+            .with_inline_const_brillig_calls(true)
+            .with_inline_const_brillig_calls_constants_limit(limit);
+        let ssa = optimize_ssa_before_brillig(builder, i64::MAX.into()).unwrap().finish();
+
+        let expected_functions = if replaced { 1 } else { 2 };
+        assert_eq!(ssa.functions.len(), expected_functions);
+    }
+}
+
+#[test]
+fn test_optimize_ssa_text_with_parse_error() {
+    let src = "
+        acir(inline) fn main f0 {
+          b0(v0: Field):
+            return v1
+        }
+        ";
+    let Err(error) = optimize_ssa_text(src, i64::MAX) else {
+        panic!("Expected invalid SSA to be rejected");
+    };
+    assert!(matches!(error.error, SsaError::UnknownVariable(..)));
+}
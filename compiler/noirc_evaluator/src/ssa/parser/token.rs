@@ -29,6 +29,10 @@ impl SpannedToken {
 pub(crate) enum Token {
     Ident(String),
     Int(FieldElement),
+    /// An integer literal with a type suffix, as in `5u32`
+    TypedInt(FieldElement, IntType),
+    /// A string literal, as in `"get_sibling_index"`
+    Str(String),
     Keyword(Keyword),
     IntType(IntType),
     /// =
@@ -57,6 +61,8 @@ pub(crate) enum Token {
     Equal,
     /// &
     Ampersand,
+    /// #
+    Hash,
     /// -
     Dash,
     Eof,
@@ -77,6 +83,8 @@ impl Display for Token {
         match self {
             Token::Ident(ident) => write!(f, "{}", ident),
             Token::Int(int) => write!(f, "{}", int),
+            Token::TypedInt(int, int_type) => write!(f, "{}{}", int, int_type),
+            Token::Str(string) => write!(f, "{:?}", string),
             Token::Keyword(keyword) => write!(f, "{}", keyword),
             Token::IntType(int_type) => write!(f, "{}", int_type),
             Token::Assign => write!(f, "="),
@@ -92,6 +100,7 @@ impl Display for Token {
             Token::Arrow => write!(f, "->"),
             Token::Equal => write!(f, "=="),
             Token::Ampersand => write!(f, "&"),
+            Token::Hash => write!(f, "#"),
             Token::Dash => write!(f, "-"),
             Token::Eof => write!(f, "(end of stream)"),
         }
@@ -127,9 +136,14 @@ pub(crate) enum Keyword {
     Else,
     EnableSideEffects,
     Eq,
+    False,
     Field,
     Fold,
     Fn,
+    Foreign,
+    Function,
+    Global,
+    If,
     IncRc,
     Index,
     Jmp,
@@ -153,7 +167,9 @@ pub(crate) enum Keyword {
     Sub,
     Then,
     To,
+    True,
     Truncate,
+    Unreachable,
     Value,
     Xor,
 }
@@ -180,11 +196,16 @@ impl Keyword {
             "else" => Keyword::Else,
             "enable_side_effects" => Keyword::EnableSideEffects,
             "eq" => Keyword::Eq,
+            "false" => Keyword::False,
             "inline" => Keyword::Inline,
             "inline_always" => Keyword::InlineAlways,
             "Field" => Keyword::Field,
             "fold" => Keyword::Fold,
             "fn" => Keyword::Fn,
+            "foreign" => Keyword::Foreign,
+            "function" => Keyword::Function,
+            "global" => Keyword::Global,
+            "if" => Keyword::If,
             "inc_rc" => Keyword::IncRc,
             "index" => Keyword::Index,
             "jmp" => Keyword::Jmp,
@@ -208,7 +229,9 @@ impl Keyword {
             "sub" => Keyword::Sub,
             "then" => Keyword::Then,
             "to" => Keyword::To,
+            "true" => Keyword::True,
             "truncate" => Keyword::Truncate,
+            "unreachable" => Keyword::Unreachable,
             "value" => Keyword::Value,
             "xor" => Keyword::Xor,
             _ => return None,
@@ -239,9 +262,14 @@ impl Display for Keyword {
             Keyword::Else => write!(f, "else"),
             Keyword::EnableSideEffects => write!(f, "enable_side_effects"),
             Keyword::Eq => write!(f, "eq"),
+            Keyword::False => write!(f, "false"),
             Keyword::Field => write!(f, "Field"),
             Keyword::Fold => write!(f, "fold"),
             Keyword::Fn => write!(f, "fn"),
+            Keyword::Foreign => write!(f, "foreign"),
+            Keyword::Function => write!(f, "function"),
+            Keyword::Global => write!(f, "global"),
+            Keyword::If => write!(f, "if"),
             Keyword::IncRc => write!(f, "inc_rc"),
             Keyword::Index => write!(f, "index"),
             Keyword::Inline => write!(f, "inline"),
@@ -267,7 +295,9 @@ impl Display for Keyword {
             Keyword::Sub => write!(f, "sub"),
             Keyword::Then => write!(f, "then"),
             Keyword::To => write!(f, "to"),
+            Keyword::True => write!(f, "true"),
             Keyword::Truncate => write!(f, "truncate"),
+            Keyword::Unreachable => write!(f, "unreachable"),
             Keyword::Value => write!(f, "value"),
             Keyword::Xor => write!(f, "xor"),
         }
@@ -0,0 +1,190 @@
+//! Traversal of a [`ParsedSsa`] before it is turned into SSA.
+//!
+//! This allows transforming textual SSA at the AST level, for example to rename values
+//! or inject instructions, before `into_ssa` runs.
+use super::{
+    ParsedBlock, ParsedFunction, ParsedInstruction, ParsedSsa, ParsedTerminator, ParsedValue,
+};
+
+/// A node of a [`ParsedSsa`] passed to the closure given to [`ParsedSsa::walk`].
+pub(crate) enum ParsedNode<'a> {
+    Function(&'a ParsedFunction),
+    Block(&'a ParsedBlock),
+    Instruction(&'a ParsedInstruction),
+    Terminator(&'a ParsedTerminator),
+    Value(&'a ParsedValue),
+}
+
+/// A node of a [`ParsedSsa`] passed to the closure given to [`ParsedSsa::walk_mut`].
+pub(crate) enum ParsedNodeMut<'a> {
+    Function(&'a mut ParsedFunction),
+    Block(&'a mut ParsedBlock),
+    Instruction(&'a mut ParsedInstruction),
+    Terminator(&'a mut ParsedTerminator),
+    Value(&'a mut ParsedValue),
+}
+
+impl ParsedSsa {
+    /// Visits every global value, then every function, block, instruction, terminator and
+    /// value in the order they appear in the source.
+    ///
+    /// A node is always visited before the nodes it contains, so for example the values
+    /// of an instruction are visited right after the instruction itself.
+    pub(crate) fn walk(&self, mut f: impl FnMut(ParsedNode)) {
+        for global in &self.globals {
+            for element in &global.elements {
+                f(ParsedNode::Value(element));
+            }
+        }
+
+        for function in &self.functions {
+            f(ParsedNode::Function(function));
+
+            for block in &function.blocks {
+                f(ParsedNode::Block(block));
+
+                for instruction in &block.instructions {
+                    f(ParsedNode::Instruction(instruction));
+                    instruction.for_each_value(|value| f(ParsedNode::Value(value)));
+                }
+
+                f(ParsedNode::Terminator(&block.terminator));
+                block.terminator.for_each_value(|value| f(ParsedNode::Value(value)));
+            }
+        }
+    }
+
+    /// Same as `walk`, but allows the closure to modify each node.
+    ///
+    /// Nodes are visited in the same order as in `walk`, so changes made to a node are
+    /// seen when the nodes it contains are visited.
+    pub(crate) fn walk_mut(&mut self, mut f: impl FnMut(ParsedNodeMut)) {
+        for global in &mut self.globals {
+            for element in &mut global.elements {
+                f(ParsedNodeMut::Value(element));
+            }
+        }
+
+        for function in &mut self.functions {
+            f(ParsedNodeMut::Function(function));
+
+            for block in &mut function.blocks {
+                f(ParsedNodeMut::Block(block));
+
+                for instruction in &mut block.instructions {
+                    f(ParsedNodeMut::Instruction(instruction));
+                    instruction.for_each_value_mut(|value| f(ParsedNodeMut::Value(value)));
+                }
+
+                f(ParsedNodeMut::Terminator(&mut block.terminator));
+                block.terminator.for_each_value_mut(|value| f(ParsedNodeMut::Value(value)));
+            }
+        }
+    }
+}
+
+impl ParsedInstruction {
+    fn for_each_value(&self, mut f: impl FnMut(&ParsedValue)) {
+        match self {
+            ParsedInstruction::Allocate { .. } => (),
+            ParsedInstruction::ArrayGet { array, index, .. } => {
+                f(array);
+                f(index);
+            }
+            ParsedInstruction::ArraySet { array, index, value, .. } => {
+                f(array);
+                f(index);
+                f(value);
+            }
+            ParsedInstruction::BinaryOp { lhs, rhs, .. }
+            | ParsedInstruction::Constrain { lhs, rhs } => {
+                f(lhs);
+                f(rhs);
+            }
+            ParsedInstruction::Call { arguments, .. }
+            | ParsedInstruction::ForeignCall { arguments, .. }
+            | ParsedInstruction::MakeArray { elements: arguments, .. } => {
+                arguments.iter().for_each(f);
+            }
+            ParsedInstruction::IfElse { condition, then_value, else_value, .. } => {
+                f(condition);
+                f(then_value);
+                f(else_value);
+            }
+            ParsedInstruction::Cast { lhs: value, .. }
+            | ParsedInstruction::DecrementRc { value }
+            | ParsedInstruction::EnableSideEffectsIf { condition: value }
+            | ParsedInstruction::IncrementRc { value }
+            | ParsedInstruction::Load { value, .. }
+            | ParsedInstruction::Not { value, .. }
+            | ParsedInstruction::RangeCheck { value, .. }
+            | ParsedInstruction::Truncate { value, .. } => f(value),
+            ParsedInstruction::Store { value, address, .. } => {
+                f(value);
+                f(address);
+            }
+        }
+    }
+
+    fn for_each_value_mut(&mut self, mut f: impl FnMut(&mut ParsedValue)) {
+        match self {
+            ParsedInstruction::Allocate { .. } => (),
+            ParsedInstruction::ArrayGet { array, index, .. } => {
+                f(array);
+                f(index);
+            }
+            ParsedInstruction::ArraySet { array, index, value, .. } => {
+                f(array);
+                f(index);
+                f(value);
+            }
+            ParsedInstruction::BinaryOp { lhs, rhs, .. }
+            | ParsedInstruction::Constrain { lhs, rhs } => {
+                f(lhs);
+                f(rhs);
+            }
+            ParsedInstruction::Call { arguments, .. }
+            | ParsedInstruction::ForeignCall { arguments, .. }
+            | ParsedInstruction::MakeArray { elements: arguments, .. } => {
+                arguments.iter_mut().for_each(f);
+            }
+            ParsedInstruction::IfElse { condition, then_value, else_value, .. } => {
+                f(condition);
+                f(then_value);
+                f(else_value);
+            }
+            ParsedInstruction::Cast { lhs: value, .. }
+            | ParsedInstruction::DecrementRc { value }
+            | ParsedInstruction::EnableSideEffectsIf { condition: value }
+            | ParsedInstruction::IncrementRc { value }
+            | ParsedInstruction::Load { value, .. }
+            | ParsedInstruction::Not { value, .. }
+            | ParsedInstruction::RangeCheck { value, .. }
+            | ParsedInstruction::Truncate { value, .. } => f(value),
+            ParsedInstruction::Store { value, address, .. } => {
+                f(value);
+                f(address);
+            }
+        }
+    }
+}
+
+impl ParsedTerminator {
+    fn for_each_value(&self, f: impl FnMut(&ParsedValue)) {
+        match self {
+            ParsedTerminator::Jmp { arguments: values, .. }
+            | ParsedTerminator::Return { values, .. } => values.iter().for_each(f),
+            ParsedTerminator::Jmpif { condition, .. } => std::iter::once(condition).for_each(f),
+            ParsedTerminator::Unreachable => (),
+        }
+    }
+
+    fn for_each_value_mut(&mut self, f: impl FnMut(&mut ParsedValue)) {
+        match self {
+            ParsedTerminator::Jmp { arguments: values, .. }
+            | ParsedTerminator::Return { values, .. } => values.iter_mut().for_each(f),
+            ParsedTerminator::Jmpif { condition, .. } => std::iter::once(condition).for_each(f),
+            ParsedTerminator::Unreachable => (),
+        }
+    }
+}
@@ -91,6 +91,17 @@ impl Ssa {
         new_id
     }
 
+    /// Returns true if the given function is an entry point even though its runtime
+    /// doesn't make it one, so it has to be marked as such when printed.
+    fn is_explicit_entry_point(&self, function: &Function) -> bool {
+        let id = function.id();
+        let implied = match function.runtime() {
+            RuntimeType::Acir(_) => function.runtime().is_entry_point() || id == self.main_id,
+            RuntimeType::Brillig(_) => false,
+        };
+        self.entry_point_to_generated_index.contains_key(&id) && !implied
+    }
+
     /// Clones an already existing function with a fresh id
     pub(crate) fn clone_fn(&mut self, existing_function_id: FunctionId) -> FunctionId {
         let new_id = self.next_id.next();
@@ -103,7 +114,14 @@ impl Ssa {
 impl Display for Ssa {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for function in self.functions.values() {
-            writeln!(f, "{function}")?;
+            if self.is_explicit_entry_point(function) {
+                writeln!(f, "#[entry_point]")?;
+            }
+            if f.alternate() {
+                writeln!(f, "{function:#}")?;
+            } else {
+                writeln!(f, "{function}")?;
+            }
         }
         Ok(())
     }
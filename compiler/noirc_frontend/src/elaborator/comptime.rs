@@ -101,6 +101,7 @@ impl<'context> Elaborator<'context> {
 
         elaborator.local_module = self.local_module;
         elaborator.file = self.file;
+        elaborator.allow_comptime_env_vars = self.allow_comptime_env_vars;
 
         setup(&mut elaborator);
 
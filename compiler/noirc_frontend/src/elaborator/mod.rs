@@ -158,6 +158,9 @@ pub struct Elaborator<'context> {
     /// The scope of --debug-comptime, or None if unset
     debug_comptime_in_file: Option<FileId>,
 
+    /// Whether comptime code may read environment variables, set by --allow-comptime-env-vars
+    pub(crate) allow_comptime_env_vars: bool,
+
     /// These are the globals that have yet to be elaborated.
     /// This map is used to lazily evaluate these globals if they're encountered before
     /// they are elaborated (e.g. in a function's type or another global's RHS).
@@ -209,6 +212,7 @@ impl<'context> Elaborator<'context> {
             function_context: vec![FunctionContext::default()],
             current_trait_impl: None,
             debug_comptime_in_file,
+            allow_comptime_env_vars: false,
             unresolved_globals: BTreeMap::new(),
             current_trait: None,
             interpreter_call_stack,
@@ -221,14 +225,17 @@ impl<'context> Elaborator<'context> {
         crate_id: CrateId,
         debug_comptime_in_file: Option<FileId>,
     ) -> Self {
-        Self::new(
+        let allow_comptime_env_vars = context.allow_comptime_env_vars;
+        let mut elaborator = Self::new(
             &mut context.def_interner,
             &mut context.def_maps,
             &mut context.usage_tracker,
             crate_id,
             debug_comptime_in_file,
             im::Vector::new(),
-        )
+        );
+        elaborator.allow_comptime_env_vars = allow_comptime_env_vars;
+        elaborator
     }
 
     pub fn elaborate(
@@ -1374,6 +1381,9 @@ impl<'context> Elaborator<'context> {
                 if !is_trait_impl {
                     self.declare_methods(self_type, &function_ids);
                 }
+                for method_id in &function_ids {
+                    self.interner.try_add_array_sort_via(self_type, *method_id);
+                }
             } else {
                 self.push_err(DefCollectorErrorKind::NonStructTypeInImpl { span });
             }
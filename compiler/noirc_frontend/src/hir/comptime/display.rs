@@ -314,6 +314,17 @@ impl<'interner> TokenPrettyPrinter<'interner> {
     }
 }
 
+/// Returns the bytes of a byte string as a string if every byte is printable ASCII.
+fn printable_byte_string(bytes: &[u8]) -> Option<String> {
+    if bytes.is_empty() {
+        return None;
+    }
+    bytes
+        .iter()
+        .map(|byte| (byte.is_ascii_graphic() || *byte == b' ').then_some(*byte as char))
+        .collect()
+}
+
 impl Value {
     pub fn display<'value, 'interner>(
         &'value self,
@@ -321,6 +332,18 @@ impl Value {
     ) -> ValuePrinter<'value, 'interner> {
         ValuePrinter { value: self, interner }
     }
+
+    /// Displays this value the way it's interpolated into a format string.
+    ///
+    /// A quoted value is displayed without the surrounding `quote {` ... `}`,
+    /// as if it were unquoted inside the string.
+    pub(crate) fn display_interpolated(&self, interner: &NodeInterner) -> String {
+        if let Value::Quoted(tokens) = self {
+            vecmap(tokens.iter(), |token| token.display(interner).to_string()).join(" ")
+        } else {
+            self.display(interner).to_string()
+        }
+    }
 }
 
 pub struct ValuePrinter<'value, 'interner> {
@@ -366,6 +389,12 @@ impl<'value, 'interner> Display for ValuePrinter<'value, 'interner> {
                 write!(f, "{typename} {{ {} }}", fields.join(", "))
             }
             Value::Pointer(value, _) => write!(f, "&mut {}", value.borrow().display(self.interner)),
+            Value::ByteString(bytes) => match printable_byte_string(bytes) {
+                Some(string) => write!(f, "{string:?}"),
+                None => {
+                    write!(f, "{}", self.value.clone().expand_byte_string().display(self.interner))
+                }
+            },
             Value::Array(values, _) => {
                 let values = vecmap(values, |value| value.display(self.interner).to_string());
                 write!(f, "[{}]", values.join(", "))
@@ -56,6 +56,13 @@ pub enum InterpreterError {
     },
     FailingConstraint {
         message: Option<String>,
+        /// The displayed values of both sides of the condition, when it's a comparison.
+        operands: Option<(String, String)>,
+        location: Location,
+        call_stack: im::Vector<Location>,
+    },
+    StaticAssertFailed {
+        message: String,
         location: Location,
         call_stack: im::Vector<Location>,
     },
@@ -178,6 +185,11 @@ pub enum InterpreterError {
         location: Location,
     },
     BlackBoxError(BlackBoxResolutionError, Location),
+    InvalidCurvePoint {
+        x: FieldElement,
+        y: FieldElement,
+        location: Location,
+    },
     FailedToResolveTraitBound {
         trait_bound: TraitBound,
         location: Location,
@@ -235,6 +247,36 @@ pub enum InterpreterError {
         err: Box<TypeCheckError>,
         location: Location,
     },
+    RecursionLimitReached {
+        limit: usize,
+        location: Location,
+    },
+    InvalidFormatString {
+        message: String,
+        location: Location,
+    },
+    /// A non-fatal diagnostic requested by comptime code through `std::meta::emit_warning`.
+    /// This is never returned as an `Err`, it is only collected as a warning.
+    ComptimeWarning {
+        message: String,
+        location: Location,
+    },
+    EnvVarsNotAllowed {
+        name: String,
+        location: Location,
+    },
+    CannotCoerceToField {
+        typ: Type,
+        location: Location,
+    },
+    NextPowerOfTwoOutOfRange {
+        value: FieldElement,
+        location: Location,
+    },
+    FreshIdentPrefixShouldBeAnIdent {
+        prefix: Rc<String>,
+        location: Location,
+    },
 
     // These cases are not errors, they are just used to prevent us from running more code
     // until the loop can be resumed properly. These cases will never be displayed to users.
@@ -257,8 +299,17 @@ impl InterpreterError {
         (CompilationError::InterpreterError(self), location.file)
     }
 
+    /// Returns the location of the code which caused this error.
+    ///
+    /// Panics for `Break` and `Continue`, which are not errors shown to users.
     pub fn get_location(&self) -> Location {
-        match self {
+        self.location().expect("Tried to get the location of Break/Continue error!")
+    }
+
+    /// Returns the location of the code which caused this error, for tools such as the LSP
+    /// server to point at, or `None` for `Break` and `Continue` which have no location.
+    pub fn location(&self) -> Option<Location> {
+        let location = match self {
             InterpreterError::ArgumentCountMismatch { location, .. }
             | InterpreterError::TypeMismatch { location, .. }
             | InterpreterError::NonComptimeVarReferenced { location, .. }
@@ -269,6 +320,7 @@ impl InterpreterError {
             | InterpreterError::NonBoolUsedInIf { location, .. }
             | InterpreterError::NonBoolUsedInConstrain { location, .. }
             | InterpreterError::FailingConstraint { location, .. }
+            | InterpreterError::StaticAssertFailed { location, .. }
             | InterpreterError::NoMethodFound { location, .. }
             | InterpreterError::NonIntegerUsedInLoop { location, .. }
             | InterpreterError::NonPointerDereferenced { location, .. }
@@ -295,6 +347,7 @@ impl InterpreterError {
             | InterpreterError::ImplMethodTypeMismatch { location, .. }
             | InterpreterError::DebugEvaluateComptime { location, .. }
             | InterpreterError::BlackBoxError(_, location)
+            | InterpreterError::InvalidCurvePoint { location, .. }
             | InterpreterError::BreakNotInLoop { location, .. }
             | InterpreterError::ContinueNotInLoop { location, .. }
             | InterpreterError::TraitDefinitionMustBeAPath { location }
@@ -309,7 +362,14 @@ impl InterpreterError {
             | InterpreterError::TypeAnnotationsNeededForMethodCall { location }
             | InterpreterError::CannotResolveExpression { location, .. }
             | InterpreterError::CannotSetFunctionBody { location, .. }
-            | InterpreterError::UnknownArrayLength { location, .. } => *location,
+            | InterpreterError::UnknownArrayLength { location, .. }
+            | InterpreterError::RecursionLimitReached { location, .. }
+            | InterpreterError::InvalidFormatString { location, .. }
+            | InterpreterError::ComptimeWarning { location, .. }
+            | InterpreterError::EnvVarsNotAllowed { location, .. }
+            | InterpreterError::CannotCoerceToField { location, .. }
+            | InterpreterError::NextPowerOfTwoOutOfRange { location, .. }
+            | InterpreterError::FreshIdentPrefixShouldBeAnIdent { location, .. } => *location,
 
             InterpreterError::FailedToParseMacro { error, file, .. } => {
                 Location::new(error.span(), *file)
@@ -317,9 +377,126 @@ impl InterpreterError {
             InterpreterError::NoMatchingImplFound { error, file } => {
                 Location::new(error.span, *file)
             }
-            InterpreterError::Break | InterpreterError::Continue => {
-                panic!("Tried to get the location of Break/Continue error!")
-            }
+            InterpreterError::Break | InterpreterError::Continue => return None,
+        };
+        Some(location)
+    }
+
+    /// A stable identifier for the kind of this error, such as `"comptime::recursion_limit_reached"`,
+    /// for tools that need to tell errors apart without matching on their messages.
+    ///
+    /// Each code is the name of the variant in snake case. Codes don't change when a variant's
+    /// message does, and a code is never reused for a different kind of error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            InterpreterError::ArgumentCountMismatch { .. } => "comptime::argument_count_mismatch",
+            InterpreterError::TypeMismatch { .. } => "comptime::type_mismatch",
+            InterpreterError::NonComptimeVarReferenced { .. } => {
+                "comptime::non_comptime_var_referenced"
+            }
+            InterpreterError::VariableNotInScope { .. } => "comptime::variable_not_in_scope",
+            InterpreterError::IntegerOutOfRangeForType { .. } => {
+                "comptime::integer_out_of_range_for_type"
+            }
+            InterpreterError::ErrorNodeEncountered { .. } => "comptime::error_node_encountered",
+            InterpreterError::NonFunctionCalled { .. } => "comptime::non_function_called",
+            InterpreterError::NonBoolUsedInIf { .. } => "comptime::non_bool_used_in_if",
+            InterpreterError::NonBoolUsedInConstrain { .. } => {
+                "comptime::non_bool_used_in_constrain"
+            }
+            InterpreterError::FailingConstraint { .. } => "comptime::failing_constraint",
+            InterpreterError::StaticAssertFailed { .. } => "comptime::static_assert_failed",
+            InterpreterError::NoMethodFound { .. } => "comptime::no_method_found",
+            InterpreterError::NonIntegerUsedInLoop { .. } => "comptime::non_integer_used_in_loop",
+            InterpreterError::NonPointerDereferenced { .. } => "comptime::non_pointer_dereferenced",
+            InterpreterError::NonTupleOrStructInMemberAccess { .. } => {
+                "comptime::non_tuple_or_struct_in_member_access"
+            }
+            InterpreterError::NonArrayIndexed { .. } => "comptime::non_array_indexed",
+            InterpreterError::NonIntegerUsedAsIndex { .. } => "comptime::non_integer_used_as_index",
+            InterpreterError::NonIntegerIntegerLiteral { .. } => {
+                "comptime::non_integer_integer_literal"
+            }
+            InterpreterError::NonIntegerArrayLength { .. } => "comptime::non_integer_array_length",
+            InterpreterError::NonNumericCasted { .. } => "comptime::non_numeric_casted",
+            InterpreterError::IndexOutOfBounds { .. } => "comptime::index_out_of_bounds",
+            InterpreterError::ExpectedStructToHaveField { .. } => {
+                "comptime::expected_struct_to_have_field"
+            }
+            InterpreterError::TypeUnsupported { .. } => "comptime::type_unsupported",
+            InterpreterError::InvalidValueForUnary { .. } => "comptime::invalid_value_for_unary",
+            InterpreterError::InvalidValuesForBinary { .. } => {
+                "comptime::invalid_values_for_binary"
+            }
+            InterpreterError::CastToNonNumericType { .. } => "comptime::cast_to_non_numeric_type",
+            InterpreterError::QuoteInRuntimeCode { .. } => "comptime::quote_in_runtime_code",
+            InterpreterError::NonStructInConstructor { .. } => {
+                "comptime::non_struct_in_constructor"
+            }
+            InterpreterError::CannotInlineMacro { .. } => "comptime::cannot_inline_macro",
+            InterpreterError::UnquoteFoundDuringEvaluation { .. } => {
+                "comptime::unquote_found_during_evaluation"
+            }
+            InterpreterError::DebugEvaluateComptime { .. } => "comptime::debug_evaluate_comptime",
+            InterpreterError::FailedToParseMacro { .. } => "comptime::failed_to_parse_macro",
+            InterpreterError::UnsupportedTopLevelItemUnquote { .. } => {
+                "comptime::unsupported_top_level_item_unquote"
+            }
+            InterpreterError::ComptimeDependencyCycle { .. } => {
+                "comptime::comptime_dependency_cycle"
+            }
+            InterpreterError::NoImpl { .. } => "comptime::no_impl",
+            InterpreterError::NoMatchingImplFound { .. } => "comptime::no_matching_impl_found",
+            InterpreterError::ImplMethodTypeMismatch { .. } => {
+                "comptime::impl_method_type_mismatch"
+            }
+            InterpreterError::BreakNotInLoop { .. } => "comptime::break_not_in_loop",
+            InterpreterError::ContinueNotInLoop { .. } => "comptime::continue_not_in_loop",
+            InterpreterError::BlackBoxError(..) => "comptime::black_box_error",
+            InterpreterError::InvalidCurvePoint { .. } => "comptime::invalid_curve_point",
+            InterpreterError::FailedToResolveTraitBound { .. } => {
+                "comptime::failed_to_resolve_trait_bound"
+            }
+            InterpreterError::TraitDefinitionMustBeAPath { .. } => {
+                "comptime::trait_definition_must_be_a_path"
+            }
+            InterpreterError::FailedToResolveTraitDefinition { .. } => {
+                "comptime::failed_to_resolve_trait_definition"
+            }
+            InterpreterError::FunctionAlreadyResolved { .. } => {
+                "comptime::function_already_resolved"
+            }
+            InterpreterError::MultipleMatchingImpls { .. } => "comptime::multiple_matching_impls",
+            InterpreterError::Unimplemented { .. } => "comptime::unimplemented",
+            InterpreterError::TypeAnnotationsNeededForMethodCall { .. } => {
+                "comptime::type_annotations_needed_for_method_call"
+            }
+            InterpreterError::ExpectedIdentForStructField { .. } => {
+                "comptime::expected_ident_for_struct_field"
+            }
+            InterpreterError::InvalidAttribute { .. } => "comptime::invalid_attribute",
+            InterpreterError::GenericNameShouldBeAnIdent { .. } => {
+                "comptime::generic_name_should_be_an_ident"
+            }
+            InterpreterError::DuplicateGeneric { .. } => "comptime::duplicate_generic",
+            InterpreterError::CannotResolveExpression { .. } => {
+                "comptime::cannot_resolve_expression"
+            }
+            InterpreterError::CannotSetFunctionBody { .. } => "comptime::cannot_set_function_body",
+            InterpreterError::UnknownArrayLength { .. } => "comptime::unknown_array_length",
+            InterpreterError::RecursionLimitReached { .. } => "comptime::recursion_limit_reached",
+            InterpreterError::InvalidFormatString { .. } => "comptime::invalid_format_string",
+            InterpreterError::ComptimeWarning { .. } => "comptime::comptime_warning",
+            InterpreterError::EnvVarsNotAllowed { .. } => "comptime::env_vars_not_allowed",
+            InterpreterError::CannotCoerceToField { .. } => "comptime::cannot_coerce_to_field",
+            InterpreterError::NextPowerOfTwoOutOfRange { .. } => {
+                "comptime::next_power_of_two_out_of_range"
+            }
+            InterpreterError::FreshIdentPrefixShouldBeAnIdent { .. } => {
+                "comptime::fresh_ident_prefix_should_be_an_ident"
+            }
+            InterpreterError::Break => "comptime::break",
+            InterpreterError::Continue => "comptime::continue",
         }
     }
 
@@ -394,15 +571,31 @@ impl<'a> From<&'a InterpreterError> for CustomDiagnostic {
                 let msg = format!("Expected a `bool` but found `{typ}`");
                 CustomDiagnostic::simple_error(msg, String::new(), location.span)
             }
-            InterpreterError::FailingConstraint { message, location, call_stack } => {
+            InterpreterError::FailingConstraint { message, operands, location, call_stack } => {
+                let operands = operands
+                    .as_ref()
+                    .map(|(lhs, rhs)| format!("Left side is `{lhs}`, right side is `{rhs}`"));
                 let (primary, secondary) = match message {
-                    Some(msg) => (msg.clone(), "Assertion failed".into()),
-                    None => ("Assertion failed".into(), String::new()),
+                    Some(msg) => {
+                        (msg.clone(), operands.unwrap_or_else(|| "Assertion failed".into()))
+                    }
+                    None => ("Assertion failed".into(), operands.unwrap_or_default()),
                 };
                 let diagnostic = CustomDiagnostic::simple_error(primary, secondary, location.span);
 
                 diagnostic.with_call_stack(call_stack.into_iter().copied().collect())
             }
+            InterpreterError::StaticAssertFailed { message, location, call_stack } => {
+                let primary = if message.is_empty() {
+                    "Static assertion failed".to_string()
+                } else {
+                    message.clone()
+                };
+                let secondary = "Static assertion failed".into();
+                let diagnostic = CustomDiagnostic::simple_error(primary, secondary, location.span);
+
+                diagnostic.with_call_stack(call_stack.into_iter().copied().collect())
+            }
             InterpreterError::NoMethodFound { name, typ, location } => {
                 let msg = format!("No method named `{name}` found for type `{typ}`");
                 CustomDiagnostic::simple_error(msg, String::new(), location.span)
@@ -561,6 +754,11 @@ impl<'a> From<&'a InterpreterError> for CustomDiagnostic {
             InterpreterError::BlackBoxError(error, location) => {
                 CustomDiagnostic::simple_error(error.to_string(), String::new(), location.span)
             }
+            InterpreterError::InvalidCurvePoint { x, y, location } => {
+                let msg =
+                    format!("Point ({}, {}) is not on the embedded curve", x.to_hex(), y.to_hex());
+                CustomDiagnostic::simple_error(msg, String::new(), location.span)
+            }
             InterpreterError::FailedToResolveTraitBound { trait_bound, location } => {
                 let msg = format!("Failed to resolve trait bound `{trait_bound}`");
                 CustomDiagnostic::simple_error(msg, String::new(), location.span)
@@ -654,6 +852,41 @@ impl<'a> From<&'a InterpreterError> for CustomDiagnostic {
                 let secondary = format!("Evaluating the length failed with: `{err}`");
                 CustomDiagnostic::simple_error(msg, secondary, location.span)
             }
+            InterpreterError::RecursionLimitReached { limit, location } => {
+                let msg = format!("Comptime recursion limit of {limit} nested calls reached");
+                let secondary = "This call may be part of an infinite recursion".into();
+                CustomDiagnostic::simple_error(msg, secondary, location.span)
+            }
+            InterpreterError::InvalidFormatString { message, location } => {
+                let msg = format!("Invalid format string: {message}");
+                CustomDiagnostic::simple_error(msg, String::new(), location.span)
+            }
+            InterpreterError::ComptimeWarning { message, location } => {
+                let secondary = "Emitted by comptime code".into();
+                CustomDiagnostic::simple_warning(message.clone(), secondary, location.span)
+            }
+            InterpreterError::EnvVarsNotAllowed { name, location } => {
+                let msg = format!("Cannot read environment variable `{name}` at compile time");
+                let secondary =
+                    "Reading environment variables requires `--allow-comptime-env-vars`".into();
+                CustomDiagnostic::simple_error(msg, secondary, location.span)
+            }
+            InterpreterError::CannotCoerceToField { typ, location } => {
+                let msg = format!("Cannot convert a value of type `{typ}` to a Field");
+                let secondary = "Only integers, booleans and Fields can be converted".into();
+                CustomDiagnostic::simple_error(msg, secondary, location.span)
+            }
+            InterpreterError::NextPowerOfTwoOutOfRange { value, location } => {
+                let msg = format!("The next power of two of {value} does not fit in a Field");
+                let max_exponent = FieldElement::max_num_bits() - 1;
+                let secondary = format!("The largest power of two in a Field is 2^{max_exponent}");
+                CustomDiagnostic::simple_error(msg, secondary, location.span)
+            }
+            InterpreterError::FreshIdentPrefixShouldBeAnIdent { prefix, location } => {
+                let msg = "The prefix of a fresh identifier needs to be a valid identifier (one word beginning with a letter)".to_string();
+                let secondary = format!("`{prefix}` is not a valid identifier");
+                CustomDiagnostic::simple_error(msg, secondary, location.span)
+            }
         }
     }
 }
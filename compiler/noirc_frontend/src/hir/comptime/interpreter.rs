@@ -46,6 +46,17 @@ mod builtin;
 mod foreign;
 mod unquote;
 
+/// The default maximum number of nested comptime function and closure calls. Runaway recursion
+/// is reported as an error once this is reached instead of running until the compiler is out of
+/// memory.
+const DEFAULT_RECURSION_LIMIT: usize = 500;
+
+/// Each nested call takes roughly 70KiB of stack in both debug and release builds, which is more
+/// than a thread's stack can hold for deep recursion. When less than `STACK_RED_ZONE` bytes are
+/// left before a call, the call runs on a newly allocated stack of `STACK_SEGMENT_SIZE` bytes.
+const STACK_RED_ZONE: usize = 1024 * 1024;
+const STACK_SEGMENT_SIZE: usize = 8 * 1024 * 1024;
+
 #[allow(unused)]
 pub struct Interpreter<'local, 'interner> {
     /// To expand macros the Interpreter needs access to the Elaborator
@@ -62,6 +73,9 @@ pub struct Interpreter<'local, 'interner> {
     /// multiple times. Without this map, when one of these inner functions exits we would
     /// unbind the generic completely instead of resetting it to its previous binding.
     bound_generics: Vec<HashMap<TypeVariable, (Type, Kind)>>,
+
+    /// The maximum number of nested comptime function calls.
+    recursion_limit: usize,
 }
 
 #[allow(unused)]
@@ -73,7 +87,20 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
     ) -> Self {
         let bound_generics = Vec::new();
         let in_loop = false;
-        Self { elaborator, crate_id, current_function, bound_generics, in_loop }
+        let recursion_limit = DEFAULT_RECURSION_LIMIT;
+        Self { elaborator, crate_id, current_function, bound_generics, in_loop, recursion_limit }
+    }
+
+    /// Sets the maximum number of nested comptime function calls, after which
+    /// `InterpreterError::RecursionLimitReached` is returned.
+    pub fn set_recursion_limit(&mut self, limit: usize) {
+        self.recursion_limit = limit;
+    }
+
+    /// Reports a warning alongside the elaborator's other diagnostics. Unlike an
+    /// `InterpreterError` returned as an `Err`, this does not stop interpretation.
+    pub(crate) fn push_warning(&mut self, warning: InterpreterError) {
+        self.elaborator.errors.push(warning.into_compilation_error_pair());
     }
 
     pub(crate) fn call_function(
@@ -83,6 +110,8 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
         mut instantiation_bindings: TypeBindings,
         location: Location,
     ) -> IResult<Value> {
+        self.check_recursion_limit(location)?;
+
         let trait_method = self.elaborator.interner.get_trait_method_id(function);
 
         // To match the monomorphizer, we need to call follow_bindings on each of
@@ -106,7 +135,9 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
         self.remember_bindings(&instantiation_bindings, &impl_bindings);
         self.elaborator.interpreter_call_stack.push_back(location);
 
-        let result = self.call_function_inner(function, arguments, location);
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            self.call_function_inner(function, arguments, location)
+        });
 
         self.elaborator.interpreter_call_stack.pop_back();
         undo_instantiation_bindings(impl_bindings);
@@ -115,6 +146,14 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
         result
     }
 
+    fn check_recursion_limit(&self, location: Location) -> IResult<()> {
+        if self.elaborator.interpreter_call_stack.len() >= self.recursion_limit {
+            let limit = self.recursion_limit;
+            return Err(InterpreterError::RecursionLimitReached { limit, location });
+        }
+        Ok(())
+    }
+
     fn call_function_inner(
         &mut self,
         function: FuncId,
@@ -135,6 +174,12 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
             return self.call_special(function, arguments, return_type, location);
         }
 
+        // The stdlib's `sort_via` on arrays, which `sort` also calls, is evaluated natively.
+        // This is much faster than interpreting its quicksort and keeps comptime sorting stable.
+        if self.elaborator.interner.is_array_sort_via(function) {
+            return builtin::array_sort_via(self, arguments, location);
+        }
+
         // Don't change the current function scope if we're in a #[use_callers_scope] function.
         // This will affect where `Expression::resolve`, `Quoted::as_type`, and similar functions resolve.
         let mut old_function = self.current_function;
@@ -231,7 +276,13 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
             self.call_builtin(&builtin, arguments, return_type, location)
         } else if let Some(foreign) = func_attrs.foreign() {
             let foreign = foreign.clone();
-            foreign::call_foreign(self.elaborator.interner, &foreign, arguments, location)
+            foreign::call_foreign(
+                self.elaborator.interner,
+                &foreign,
+                arguments,
+                return_type,
+                location,
+            )
         } else if let Some(oracle) = func_attrs.oracle() {
             if oracle == "print" {
                 self.print_oracle(arguments)
@@ -257,12 +308,18 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
         module_scope: ModuleId,
         call_location: Location,
     ) -> IResult<Value> {
+        self.check_recursion_limit(call_location)?;
+
         // Set the closure's scope to that of the function it was originally evaluated in
         let old_module = self.elaborator.replace_module(module_scope);
         let old_function = std::mem::replace(&mut self.current_function, function_scope);
+        self.elaborator.interpreter_call_stack.push_back(call_location);
 
-        let result = self.call_closure_inner(closure, environment, arguments, call_location);
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            self.call_closure_inner(closure, environment, arguments, call_location)
+        });
 
+        self.elaborator.interpreter_call_stack.pop_back();
         self.current_function = old_function;
         self.elaborator.replace_module(old_module);
         result
@@ -329,6 +386,17 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
         self.elaborator.interner.comptime_scopes.pop();
     }
 
+    /// The number of comptime function calls currently being evaluated.
+    pub fn current_depth(&self) -> usize {
+        self.elaborator.interpreter_call_stack.len()
+    }
+
+    /// The variables bound in the function currently being evaluated, from the outermost
+    /// scope to the innermost one. Comptime globals are not included.
+    pub fn local_bindings(&self) -> impl Iterator<Item = (&DefinitionId, &Value)> {
+        self.elaborator.interner.comptime_scopes.iter().skip(1).flat_map(|scope| scope.iter())
+    }
+
     fn current_scope_mut(&mut self) -> &mut HashMap<DefinitionId, Value> {
         // the global scope is always at index zero, so this is always Some
         self.elaborator.interner.comptime_scopes.last_mut().unwrap()
@@ -645,19 +713,7 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
                     consuming = false;
 
                     if let Some(value) = values.pop_front() {
-                        // When interpolating a quoted value inside a format string, we don't include the
-                        // surrounding `quote {` ... `}` as if we are unquoting the quoted value inside the string.
-                        if let Value::Quoted(tokens) = value {
-                            for (index, token) in tokens.iter().enumerate() {
-                                if index > 0 {
-                                    result.push(' ');
-                                }
-                                result
-                                    .push_str(&token.display(self.elaborator.interner).to_string());
-                            }
-                        } else {
-                            result.push_str(&value.display(self.elaborator.interner).to_string());
-                        }
+                        result.push_str(&value.display_interpolated(self.elaborator.interner));
                     }
                 }
                 other if !consuming => {
@@ -909,7 +965,17 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
     fn evaluate_infix(&mut self, infix: HirInfixExpression, id: ExprId) -> IResult<Value> {
         let lhs_value = self.evaluate(infix.lhs)?;
         let rhs_value = self.evaluate(infix.rhs)?;
+        self.evaluate_infix_with_values(infix, lhs_value, rhs_value, id)
+    }
 
+    /// Applies the operator of `infix` to its already evaluated operands.
+    fn evaluate_infix_with_values(
+        &mut self,
+        infix: HirInfixExpression,
+        lhs_value: Value,
+        rhs_value: Value,
+        id: ExprId,
+    ) -> IResult<Value> {
         if self.elaborator.interner.get_selected_impl_for_expression(id).is_some() {
             return self.evaluate_overloaded_infix(infix, lhs_value, rhs_value, id);
         }
@@ -1327,7 +1393,7 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
         index: Value,
         location: Location,
     ) -> IResult<(Vector<Value>, usize)> {
-        let collection = match array {
+        let collection = match array.expand_byte_string() {
             Value::Array(array, _) => array,
             Value::Slice(array, _) => array,
             value => {
@@ -1453,6 +1519,28 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
         }
     }
 
+    /// Calls a function or closure value, for example one passed as an argument to a builtin.
+    pub(super) fn call_function_value(
+        &mut self,
+        function: Value,
+        arguments: Vec<(Value, Location)>,
+        location: Location,
+    ) -> IResult<Value> {
+        match function {
+            Value::Function(function_id, _, bindings) => {
+                let bindings = unwrap_rc(bindings);
+                self.call_function(function_id, arguments, bindings, location)
+            }
+            Value::Closure(closure, env, _, function_scope, module_scope) => {
+                self.call_closure(closure, env, arguments, function_scope, module_scope, location)
+            }
+            value => {
+                let typ = value.get_type().into_owned();
+                Err(InterpreterError::NonFunctionCalled { typ, location })
+            }
+        }
+    }
+
     fn unify_without_binding(&mut self, actual: &Type, expected: &Type, location: Location) {
         self.elaborator.unify_without_applying_bindings(actual, expected, location.file, || {
             TypeCheckError::TypeMismatch {
@@ -1677,15 +1765,30 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
     }
 
     fn evaluate_constrain(&mut self, constrain: HirConstrainStatement) -> IResult<Value> {
-        match self.evaluate(constrain.0)? {
+        // The operands of a comparison are kept so they can be shown if the constraint fails
+        let (condition, operands) = match self.elaborator.interner.expression(&constrain.0) {
+            HirExpression::Infix(infix) if infix.operator.kind.is_comparator() => {
+                let lhs = self.evaluate(infix.lhs)?;
+                let rhs = self.evaluate(infix.rhs)?;
+                let operands = Some((lhs.clone(), rhs.clone()));
+                (self.evaluate_infix_with_values(infix, lhs, rhs, constrain.0)?, operands)
+            }
+            _ => (self.evaluate(constrain.0)?, None),
+        };
+
+        match condition {
             Value::Bool(true) => Ok(Value::Unit),
             Value::Bool(false) => {
                 let location = self.elaborator.interner.expr_location(&constrain.0);
                 let message = constrain.2.and_then(|expr| self.evaluate(expr).ok());
                 let message =
                     message.map(|value| value.display(self.elaborator.interner).to_string());
+                let operands = operands.map(|(lhs, rhs)| {
+                    let interner = &*self.elaborator.interner;
+                    (lhs.display(interner).to_string(), rhs.display(interner).to_string())
+                });
                 let call_stack = self.elaborator.interpreter_call_stack.clone();
-                Err(InterpreterError::FailingConstraint { location, message, call_stack })
+                Err(InterpreterError::FailingConstraint { location, message, operands, call_stack })
             }
             value => {
                 let location = self.elaborator.interner.expr_location(&constrain.0);
@@ -1742,7 +1845,7 @@ impl<'local, 'interner> Interpreter<'local, 'interner> {
                 }
             }
             HirLValue::Index { array, index, typ: _, location } => {
-                let array_value = self.evaluate_lvalue(&array)?;
+                let array_value = self.evaluate_lvalue(&array)?.expand_byte_string();
                 let index = self.evaluate(index)?;
 
                 let constructor = match &array_value {
@@ -61,12 +61,22 @@ impl<'local, 'context> Interpreter<'local, 'context> {
             "array_as_str_unchecked" => array_as_str_unchecked(interner, arguments, location),
             "array_len" => array_len(interner, arguments, location),
             "assert_constant" => Ok(Value::Bool(true)),
+            "assert_types_eq" => assert_types_eq(arguments, location),
+            "as_field" => as_field(arguments, location),
             "as_slice" => as_slice(interner, arguments, location),
+            "bit_width" => bit_width(arguments, location),
+            "comptime_fold" => comptime_fold(self, arguments, location),
+            "comptime_map" => comptime_map(self, arguments, return_type, location),
             "ctstring_eq" => ctstring_eq(arguments, location),
+            "ctstring_format" => ctstring_format(interner, arguments, location),
             "ctstring_hash" => ctstring_hash(arguments, location),
+            "current_function_name" => current_function_name(self, arguments, location),
+            "current_module_path" => current_module_path(self, arguments, location),
             "derive_pedersen_generators" => {
                 derive_generators(interner, arguments, return_type, location)
             }
+            "emit_warning" => emit_warning(self, arguments, location),
+            "env_var" => env_var(self, arguments, return_type, location),
             "expr_as_array" => expr_as_array(interner, arguments, return_type, location),
             "expr_as_assert" => expr_as_assert(interner, arguments, return_type, location),
             "expr_as_assert_eq" => expr_as_assert_eq(interner, arguments, return_type, location),
@@ -111,8 +121,10 @@ impl<'local, 'context> Interpreter<'local, 'context> {
             "expr_resolve" => expr_resolve(self, arguments, location),
             "is_unconstrained" => Ok(Value::Bool(true)),
             "field_less_than" => field_less_than(arguments, location),
+            "fields_of" => fields_of(arguments, location),
             "fmtstr_as_ctstring" => fmtstr_as_ctstring(interner, arguments, location),
             "fmtstr_quoted_contents" => fmtstr_quoted_contents(interner, arguments, location),
+            "fresh_ident" => fresh_ident(interner, arguments, location),
             "fresh_type_variable" => fresh_type_variable(interner),
             "function_def_add_attribute" => function_def_add_attribute(self, arguments, location),
             "function_def_body" => function_def_body(interner, arguments, location),
@@ -155,6 +167,9 @@ impl<'local, 'context> Interpreter<'local, 'context> {
             "modulus_le_bits" => modulus_le_bits(arguments, location),
             "modulus_le_bytes" => modulus_le_bytes(arguments, location),
             "modulus_num_bits" => modulus_num_bits(arguments, location),
+            "next_power_of_two" => next_power_of_two(arguments, location),
+            "parse_field" => parse_field(interner, arguments, return_type, location),
+            "parse_int" => parse_int(interner, arguments, return_type, location),
             "quoted_as_expr" => quoted_as_expr(interner, arguments, return_type, location),
             "quoted_as_module" => quoted_as_module(self, arguments, return_type, location),
             "quoted_as_trait_constraint" => quoted_as_trait_constraint(self, arguments, location),
@@ -168,6 +183,7 @@ impl<'local, 'context> Interpreter<'local, 'context> {
             "slice_push_back" => slice_push_back(interner, arguments, location),
             "slice_push_front" => slice_push_front(interner, arguments, location),
             "slice_remove" => slice_remove(interner, arguments, location, call_stack),
+            "static_assert" => static_assert(interner, arguments, location, call_stack),
             "str_as_bytes" => str_as_bytes(interner, arguments, location),
             "str_as_ctstring" => str_as_ctstring(interner, arguments, location),
             "struct_def_add_attribute" => struct_def_add_attribute(interner, arguments, location),
@@ -183,8 +199,10 @@ impl<'local, 'context> Interpreter<'local, 'context> {
             "struct_def_module" => struct_def_module(self, arguments, location),
             "struct_def_name" => struct_def_name(interner, arguments, location),
             "struct_def_set_fields" => struct_def_set_fields(interner, arguments, location),
-            "to_be_radix" => to_be_radix(arguments, return_type, location),
-            "to_le_radix" => to_le_radix(arguments, return_type, location),
+            "to_be_bits" => to_be_bits(arguments, return_type, location, call_stack),
+            "to_be_radix" => to_be_radix(arguments, return_type, location, call_stack),
+            "to_le_bits" => to_le_bits(arguments, return_type, location, call_stack),
+            "to_le_radix" => to_le_radix(arguments, return_type, location, call_stack),
             "trait_constraint_eq" => trait_constraint_eq(arguments, location),
             "trait_constraint_hash" => trait_constraint_hash(arguments, location),
             "trait_def_as_trait_constraint" => {
@@ -216,6 +234,7 @@ impl<'local, 'context> Interpreter<'local, 'context> {
             "type_is_field" => type_is_field(arguments, location),
             "type_is_unit" => type_is_unit(arguments, location),
             "type_of" => type_of(arguments, location),
+            "type_size_in_fields" => type_size_in_fields(arguments, return_type, location),
             "typed_expr_as_function_definition" => {
                 typed_expr_as_function_definition(interner, arguments, return_type, location)
             }
@@ -247,6 +266,7 @@ fn failing_constraint<T>(
 ) -> IResult<T> {
     Err(InterpreterError::FailingConstraint {
         message: Some(message.into()),
+        operands: None,
         location,
         call_stack: call_stack.clone(),
     })
@@ -259,7 +279,7 @@ fn array_len(
 ) -> IResult<Value> {
     let (argument, argument_location) = check_one_argument(arguments, location)?;
 
-    match argument {
+    match argument.expand_byte_string() {
         Value::Array(values, _) | Value::Slice(values, _) => Ok(Value::U32(values.len() as u32)),
         value => {
             let type_var = Box::new(interner.next_type_variable());
@@ -290,7 +310,7 @@ fn as_slice(
 ) -> IResult<Value> {
     let (array, array_location) = check_one_argument(arguments, location)?;
 
-    match array {
+    match array.expand_byte_string() {
         Value::Array(values, Type::Array(_, typ)) => Ok(Value::Slice(values, Type::Slice(typ))),
         value => {
             let type_var = Box::new(interner.next_type_variable());
@@ -301,6 +321,122 @@ fn as_slice(
     }
 }
 
+// fn sort_via<Env>(self, ordering: fn[Env](T, T) -> bool) -> Self
+pub(super) fn array_sort_via(
+    interpreter: &mut Interpreter,
+    arguments: Vec<(Value, Location)>,
+    location: Location,
+) -> IResult<Value> {
+    let (collection, (ordering, ordering_location)) = check_two_arguments(arguments, location)?;
+    let (values, typ) = get_array_or_slice(interpreter.elaborator.interner, collection)?;
+
+    let mut in_order = |lhs: &Value, rhs: &Value| {
+        let arguments = vec![(lhs.clone(), location), (rhs.clone(), location)];
+        let result = interpreter.call_function_value(ordering.clone(), arguments, location)?;
+        get_bool((result, ordering_location))
+    };
+    let values = merge_sort(values, &mut in_order)?;
+
+    // Like at runtime, fail if `ordering` doesn't hold for the sorted elements, for example
+    // when using `<` on an array with equal elements.
+    for (lhs, rhs) in values.iter().zip(values.iter().skip(1)) {
+        if !in_order(lhs, rhs)? {
+            let message = "Array has not been sorted correctly according to `ordering`.";
+            return failing_constraint(
+                message,
+                location,
+                &interpreter.elaborator.interpreter_call_stack,
+            );
+        }
+    }
+    Ok(array_or_slice(values, typ))
+}
+
+// fn comptime_map<U, Env>(self, f: fn[Env](T) -> U) -> [U; N], for both arrays and slices
+fn comptime_map(
+    interpreter: &mut Interpreter,
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+) -> IResult<Value> {
+    let (collection, (function, _)) = check_two_arguments(arguments, location)?;
+    let (values, _) = get_array_or_slice(interpreter.elaborator.interner, collection)?;
+
+    let mut mapped = Vector::new();
+    for value in values {
+        let arguments = vec![(value, location)];
+        mapped.push_back(interpreter.call_function_value(function.clone(), arguments, location)?);
+    }
+    Ok(array_or_slice(mapped, return_type.follow_bindings()))
+}
+
+// fn comptime_fold<U, Env>(self, accumulator: U, f: fn[Env](U, T) -> U) -> U,
+// for both arrays and slices
+fn comptime_fold(
+    interpreter: &mut Interpreter,
+    arguments: Vec<(Value, Location)>,
+    location: Location,
+) -> IResult<Value> {
+    let (collection, (mut accumulator, _), (function, _)) =
+        check_three_arguments(arguments, location)?;
+    let (values, _) = get_array_or_slice(interpreter.elaborator.interner, collection)?;
+
+    for value in values {
+        let arguments = vec![(accumulator, location), (value, location)];
+        accumulator = interpreter.call_function_value(function.clone(), arguments, location)?;
+    }
+    Ok(accumulator)
+}
+
+/// A stable merge sort where `in_order(a, b)` tells whether `a` may be placed before `b`.
+///
+/// If `in_order` isn't a total order the result is still a permutation of `values`,
+/// only in an unspecified (but deterministic) order.
+fn merge_sort(
+    mut values: Vector<Value>,
+    in_order: &mut impl FnMut(&Value, &Value) -> IResult<bool>,
+) -> IResult<Vector<Value>> {
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+
+    let right = values.split_off(values.len() / 2);
+    let mut left = merge_sort(values, in_order)?;
+    let mut right = merge_sort(right, in_order)?;
+
+    let mut sorted = Vector::new();
+    while let (Some(lhs), Some(rhs)) = (left.front(), right.front()) {
+        let next = if in_order(lhs, rhs)? { left.pop_front() } else { right.pop_front() };
+        sorted.push_back(next.unwrap());
+    }
+    sorted.append(left);
+    sorted.append(right);
+    Ok(sorted)
+}
+
+fn get_array_or_slice(
+    interner: &NodeInterner,
+    (value, location): (Value, Location),
+) -> IResult<(Vector<Value>, Type)> {
+    match value.expand_byte_string() {
+        Value::Array(values, typ) | Value::Slice(values, typ) => Ok((values, typ)),
+        value => {
+            let type_var = Box::new(interner.next_type_variable());
+            let expected = Type::Array(type_var.clone(), type_var);
+            let actual = value.get_type().into_owned();
+            Err(InterpreterError::TypeMismatch { expected, actual, location })
+        }
+    }
+}
+
+/// Rebuilds the array or slice value of the given type out of its elements.
+fn array_or_slice(values: Vector<Value>, typ: Type) -> Value {
+    match typ {
+        Type::Slice(_) => Value::Slice(values, typ),
+        _ => Value::Array(values, typ),
+    }
+}
+
 fn slice_push_back(
     interner: &NodeInterner,
     arguments: Vec<(Value, Location)>,
@@ -313,6 +449,260 @@ fn slice_push_back(
     Ok(Value::Slice(values, typ))
 }
 
+// fn fields_of<T>(value: T) -> [(Quoted, Type)]
+fn fields_of(arguments: Vec<(Value, Location)>, location: Location) -> IResult<Value> {
+    let (value, value_location) = check_one_argument(arguments, location)?;
+    let Some(field_types) = value.struct_field_types() else {
+        let typ = value.get_type().into_owned();
+        return Err(InterpreterError::NonTupleOrStructInMemberAccess {
+            typ,
+            location: value_location,
+        });
+    };
+
+    let fields = field_types
+        .into_iter()
+        .map(|(name, typ)| {
+            let name = Value::Quoted(Rc::new(vec![Token::Ident(name)]));
+            Value::Tuple(vec![name, Value::Type(typ)])
+        })
+        .collect();
+
+    let typ = Type::Slice(Box::new(Type::Tuple(vec![
+        Type::Quoted(QuotedType::Quoted),
+        Type::Quoted(QuotedType::Type),
+    ])));
+    Ok(Value::Slice(fields, typ))
+}
+
+// fn emit_warning<let N: u32>(message: str<N>)
+fn emit_warning(
+    interpreter: &mut Interpreter,
+    arguments: Vec<(Value, Location)>,
+    location: Location,
+) -> IResult<Value> {
+    let message = check_one_argument(arguments, location)?;
+    let message = get_str(interpreter.elaborator.interner, message)?.to_string();
+    interpreter.push_warning(InterpreterError::ComptimeWarning { message, location });
+    Ok(Value::Unit)
+}
+
+// fn env_var<let N: u32>(name: str<N>) -> Option<CtString>
+fn env_var(
+    interpreter: &Interpreter,
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+) -> IResult<Value> {
+    let name = check_one_argument(arguments, location)?;
+    let name = get_str(interpreter.elaborator.interner, name)?;
+
+    if !interpreter.elaborator.allow_comptime_env_vars {
+        return Err(InterpreterError::EnvVarsNotAllowed { name: name.to_string(), location });
+    }
+
+    let value = std::env::var(name.as_str()).ok();
+    let value = value.map(|value| Value::CtString(Rc::new(value)));
+    option(return_type, value, location.span)
+}
+
+// fn as_field<T>(value: T) -> Field
+fn as_field(arguments: Vec<(Value, Location)>, location: Location) -> IResult<Value> {
+    let (value, location) = check_one_argument(arguments, location)?;
+
+    let field = match value {
+        Value::Field(value) => value,
+        Value::Bool(value) | Value::U1(value) => FieldElement::from(value),
+        Value::U8(value) => FieldElement::from(value as u128),
+        Value::U16(value) => FieldElement::from(value as u128),
+        Value::U32(value) => FieldElement::from(value as u128),
+        Value::U64(value) => FieldElement::from(value as u128),
+        Value::I8(value) => FieldElement::from(value as i128),
+        Value::I16(value) => FieldElement::from(value as i128),
+        Value::I32(value) => FieldElement::from(value as i128),
+        Value::I64(value) => FieldElement::from(value as i128),
+        value => {
+            let typ = value.get_type().into_owned();
+            return Err(InterpreterError::CannotCoerceToField { typ, location });
+        }
+    };
+    Ok(Value::Field(field))
+}
+
+// fn bit_width(n: Field) -> u32
+fn bit_width(arguments: Vec<(Value, Location)>, location: Location) -> IResult<Value> {
+    let value = get_field(check_one_argument(arguments, location)?)?;
+    Ok(Value::U32(value.num_bits()))
+}
+
+// fn next_power_of_two(n: Field) -> Field
+fn next_power_of_two(arguments: Vec<(Value, Location)>, location: Location) -> IResult<Value> {
+    let (value, location) = check_one_argument(arguments, location)?;
+    let value = get_field((value, location))?;
+
+    if value.is_zero() {
+        return Ok(Value::Field(FieldElement::one()));
+    }
+
+    let exponent = (value - FieldElement::one()).num_bits();
+    if exponent >= FieldElement::max_num_bits() {
+        return Err(InterpreterError::NextPowerOfTwoOutOfRange { value, location });
+    }
+
+    let power = BigUint::from(1u32) << exponent;
+    Ok(Value::Field(FieldElement::from_be_bytes_reduce(&power.to_bytes_be())))
+}
+
+// fn parse_field<let N: u32>(s: str<N>) -> Option<Field>
+fn parse_field(
+    interner: &NodeInterner,
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+) -> IResult<Value> {
+    let input = check_one_argument(arguments, location)?;
+    let input = get_str(interner, input)?;
+
+    let value = parse_integer_literal(&input).and_then(|(value, is_negative)| {
+        if value >= FieldElement::modulus() {
+            return None;
+        }
+        let field = FieldElement::from_be_bytes_reduce(&value.to_bytes_be());
+        Some(Value::Field(if is_negative { -field } else { field }))
+    });
+    option(return_type, value, location.span)
+}
+
+// fn parse_int<T, let N: u32>(s: str<N>) -> Option<T>
+fn parse_int(
+    interner: &NodeInterner,
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+) -> IResult<Value> {
+    let input = check_one_argument(arguments, location)?;
+    let input = get_str(interner, input)?;
+
+    let typ = extract_option_generic_type(return_type.clone()).follow_bindings();
+    let Type::Integer(signedness, bit_size) = typ else {
+        return Err(InterpreterError::TypeUnsupported { typ, location });
+    };
+
+    let value = parse_integer_literal(&input).and_then(|(value, is_negative)| {
+        let value = i128::try_from(&value).ok()?;
+        let value = if is_negative { -value } else { value };
+
+        let bits = bit_size.bit_size() as u32;
+        let (min, max) = match signedness {
+            Signedness::Unsigned => (0, (1i128 << bits) - 1),
+            Signedness::Signed => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+        };
+        if value < min || value > max {
+            return None;
+        }
+
+        Some(match (signedness, bit_size) {
+            (Signedness::Unsigned, IntegerBitSize::One) => Value::U1(value == 1),
+            (Signedness::Unsigned, IntegerBitSize::Eight) => Value::U8(value as u8),
+            (Signedness::Unsigned, IntegerBitSize::Sixteen) => Value::U16(value as u16),
+            (Signedness::Unsigned, IntegerBitSize::ThirtyTwo) => Value::U32(value as u32),
+            (Signedness::Unsigned, IntegerBitSize::SixtyFour) => Value::U64(value as u64),
+            (Signedness::Signed, IntegerBitSize::One) => Value::I8(value as i8),
+            (Signedness::Signed, IntegerBitSize::Eight) => Value::I8(value as i8),
+            (Signedness::Signed, IntegerBitSize::Sixteen) => Value::I16(value as i16),
+            (Signedness::Signed, IntegerBitSize::ThirtyTwo) => Value::I32(value as i32),
+            (Signedness::Signed, IntegerBitSize::SixtyFour) => Value::I64(value as i64),
+        })
+    });
+    option(return_type, value, location.span)
+}
+
+/// Parses a number the same way the lexer parses integer literals: decimal digits, or
+/// hexadecimal digits following `0x`, optionally separated by single underscores.
+/// A leading `-` is also accepted.
+///
+/// Returns the absolute value of the number and whether it is negative, or `None` if the
+/// input is malformed.
+fn parse_integer_literal(input: &str) -> Option<(BigUint, bool)> {
+    let (is_negative, input) = match input.strip_prefix('-') {
+        Some(input) => (true, input),
+        None => (false, input),
+    };
+    let (radix, digits) = match input.strip_prefix("0x") {
+        Some(digits) => (16, digits),
+        None => (10, input),
+    };
+
+    let is_well_formed = !digits.is_empty()
+        && !digits.starts_with('_')
+        && !digits.ends_with('_')
+        && !digits.contains("__")
+        && digits.chars().all(|char| char == '_' || char.is_digit(radix));
+    if !is_well_formed {
+        return None;
+    }
+
+    let digits = digits.replace('_', "");
+    let value = BigUint::parse_bytes(digits.as_bytes(), radix)?;
+    Some((value, is_negative))
+}
+
+// fn current_function_name() -> CtString
+fn current_function_name(
+    interpreter: &Interpreter,
+    arguments: Vec<(Value, Location)>,
+    location: Location,
+) -> IResult<Value> {
+    check_argument_count(0, &arguments, location)?;
+
+    // Builtins don't change the current function, so this is the function calling this builtin
+    let name = interpreter.current_function.map_or_else(String::new, |func_id| {
+        interpreter.elaborator.interner.function_name(&func_id).to_string()
+    });
+    Ok(Value::CtString(Rc::new(name)))
+}
+
+// fn current_module_path() -> CtString
+fn current_module_path(
+    interpreter: &Interpreter,
+    arguments: Vec<(Value, Location)>,
+    location: Location,
+) -> IResult<Value> {
+    check_argument_count(0, &arguments, location)?;
+
+    let module_id = match interpreter.current_function {
+        Some(func_id) => interpreter.elaborator.interner.function_module(func_id),
+        None => interpreter.elaborator.module_id(),
+    };
+    let module = interpreter.elaborator.get_module(module_id);
+    let def_map = &interpreter.elaborator.def_maps[&module_id.krate];
+    let path = def_map.get_module_path_with_separator(module_id.local_id.0, module.parent, "::");
+    Ok(Value::CtString(Rc::new(path)))
+}
+
+// fn static_assert<let N: u32>(predicate: bool, message: str<N>)
+fn static_assert(
+    interner: &NodeInterner,
+    arguments: Vec<(Value, Location)>,
+    location: Location,
+    call_stack: &im::Vector<Location>,
+) -> IResult<Value> {
+    let (predicate, message) = check_two_arguments(arguments, location)?;
+    let predicate = get_bool(predicate)?;
+    let message = get_str(interner, message)?;
+
+    if predicate {
+        Ok(Value::Unit)
+    } else {
+        let message = message.to_string();
+        Err(InterpreterError::StaticAssertFailed {
+            message,
+            location,
+            call_stack: call_stack.clone(),
+        })
+    }
+}
+
 fn str_as_bytes(
     interner: &NodeInterner,
     arguments: Vec<(Value, Location)>,
@@ -321,12 +711,7 @@ fn str_as_bytes(
     let string = check_one_argument(arguments, location)?;
     let string = get_str(interner, string)?;
 
-    let bytes: im::Vector<Value> = string.bytes().map(Value::U8).collect();
-    let byte_array_type = Type::Array(
-        Box::new(Type::Constant(bytes.len().into(), Kind::u32())),
-        Box::new(Type::Integer(Signedness::Unsigned, IntegerBitSize::Eight)),
-    );
-    Ok(Value::Array(bytes, byte_array_type))
+    Ok(Value::ByteString(Rc::new(string.as_bytes().to_vec())))
 }
 
 // fn str_as_ctstring(self) -> CtString
@@ -773,34 +1158,71 @@ fn quoted_tokens(arguments: Vec<(Value, Location)>, location: Location) -> IResu
     ))
 }
 
-fn to_be_radix(
+fn to_be_bits(
     arguments: Vec<(Value, Location)>,
     return_type: Type,
     location: Location,
+    call_stack: &im::Vector<Location>,
 ) -> IResult<Value> {
-    let le_radix_limbs = to_le_radix(arguments, return_type, location)?;
+    let le_bits = to_le_bits(arguments, return_type, location, call_stack)?;
+    Ok(reverse_array(le_bits))
+}
 
-    let Value::Array(limbs, typ) = le_radix_limbs else {
-        unreachable!("`to_le_radix` should always return an array");
-    };
-    let be_radix_limbs = limbs.into_iter().rev().collect();
+fn to_le_bits(
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+    call_stack: &im::Vector<Location>,
+) -> IResult<Value> {
+    let value = check_one_argument(arguments, location)?;
+    let value = get_field(value)?;
+
+    let bits = to_le_radix_limbs(value, 2, return_type, location, call_stack)?;
+    let bits = vecmap(bits, |bit| Value::U1(bit == 1));
+    Ok(Value::Array(bits.into(), Type::Integer(Signedness::Unsigned, IntegerBitSize::One)))
+}
 
-    Ok(Value::Array(be_radix_limbs, typ))
+fn to_be_radix(
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+    call_stack: &im::Vector<Location>,
+) -> IResult<Value> {
+    let le_radix_limbs = to_le_radix(arguments, return_type, location, call_stack)?;
+    Ok(reverse_array(le_radix_limbs))
 }
 
 fn to_le_radix(
     arguments: Vec<(Value, Location)>,
     return_type: Type,
     location: Location,
+    call_stack: &im::Vector<Location>,
 ) -> IResult<Value> {
     let (value, radix) = check_two_arguments(arguments, location)?;
 
     let value = get_field(value)?;
     let radix = get_u32(radix)?;
+
+    let limbs = to_le_radix_limbs(value, radix, return_type, location, call_stack)?;
+    let limbs = vecmap(limbs, Value::U8);
+    Ok(Value::Array(limbs.into(), Type::Integer(Signedness::Unsigned, IntegerBitSize::Eight)))
+}
+
+/// Decomposes `value` into as many little endian digits of the given radix as the length
+/// of the array `return_type`, padding with zeros.
+///
+/// Like at runtime, this fails if `value` doesn't fit in that many digits.
+fn to_le_radix_limbs(
+    value: FieldElement,
+    radix: u32,
+    return_type: Type,
+    location: Location,
+    call_stack: &im::Vector<Location>,
+) -> IResult<Vec<u8>> {
     let limb_count = if let Type::Array(length, _) = return_type {
         if let Type::Constant(limb_count, kind) = *length {
             if kind.unifies(&Kind::u32()) {
-                limb_count
+                limb_count.to_u128() as usize
             } else {
                 return Err(InterpreterError::TypeAnnotationsNeededForMethodCall { location });
             }
@@ -813,15 +1235,21 @@ fn to_le_radix(
 
     // Decompose the integer into its radix digits in little endian form.
     let decomposed_integer = compute_to_radix_le(value, radix);
-    let decomposed_integer =
-        vecmap(0..limb_count.to_u128() as usize, |i| match decomposed_integer.get(i) {
-            Some(digit) => Value::U8(*digit),
-            None => Value::U8(0),
-        });
-    Ok(Value::Array(
-        decomposed_integer.into(),
-        Type::Integer(Signedness::Unsigned, IntegerBitSize::Eight),
-    ))
+    let significant_digits =
+        decomposed_integer.iter().rposition(|digit| *digit != 0).map_or(0, |index| index + 1);
+    if significant_digits > limb_count {
+        let message = format!("Field failed to decompose into specified {limb_count} limbs");
+        return failing_constraint(message, location, call_stack);
+    }
+
+    Ok(vecmap(0..limb_count, |i| decomposed_integer.get(i).copied().unwrap_or(0)))
+}
+
+fn reverse_array(array: Value) -> Value {
+    let Value::Array(elements, typ) = array else {
+        unreachable!("Expected an array to reverse");
+    };
+    Value::Array(elements.into_iter().rev().collect(), typ)
 }
 
 fn compute_to_radix_le(field: FieldElement, radix: u32) -> Vec<u8> {
@@ -1011,6 +1439,17 @@ fn type_eq(arguments: Vec<(Value, Location)>, location: Location) -> IResult<Val
     eq_item(arguments, location, get_type)
 }
 
+// fn assert_types_eq(a: Type, b: Type)
+fn assert_types_eq(arguments: Vec<(Value, Location)>, location: Location) -> IResult<Value> {
+    let (a, b) = check_two_arguments(arguments, location)?;
+    let expected = get_type(a)?;
+    let actual = get_type(b)?;
+    if expected != actual {
+        return Err(InterpreterError::TypeMismatch { expected, actual, location });
+    }
+    Ok(Value::Unit)
+}
+
 // fn type_hash(_t: Type) -> Field
 fn type_hash(arguments: Vec<(Value, Location)>, location: Location) -> IResult<Value> {
     hash_item(arguments, location, get_type)
@@ -1082,6 +1521,17 @@ fn type_is_unit(arguments: Vec<(Value, Location)>, location: Location) -> IResul
     Ok(Value::Bool(matches!(typ, Type::Unit)))
 }
 
+// fn size_in_fields(self) -> Option<u32>
+fn type_size_in_fields(
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+) -> IResult<Value> {
+    type_as_or_err(arguments, return_type, location, |typ| {
+        Ok(typ.try_field_count(&location).map(Value::U32))
+    })
+}
+
 // fn type_of<T>(x: T) -> Type
 fn type_of(arguments: Vec<(Value, Location)>, location: Location) -> IResult<Value> {
     let (value, _) = check_one_argument(arguments, location)?;
@@ -2222,6 +2672,30 @@ fn fmtstr_quoted_contents(
     Ok(Value::Quoted(Rc::new(tokens)))
 }
 
+// fn fresh_ident<let N: u32>(prefix: str<N>) -> CtString
+fn fresh_ident(
+    interner: &mut NodeInterner,
+    arguments: Vec<(Value, Location)>,
+    location: Location,
+) -> IResult<Value> {
+    let prefix = check_one_argument(arguments, location)?;
+    let prefix_location = prefix.1;
+    let prefix = get_str(interner, prefix)?;
+
+    if !matches!(lex(&prefix).as_slice(), [Token::Ident(_)]) {
+        return Err(InterpreterError::FreshIdentPrefixShouldBeAnIdent {
+            prefix,
+            location: prefix_location,
+        });
+    }
+
+    // The name only uses identifier characters so that it lexes back into a single identifier
+    let name = format!("{prefix}__fresh_{}", interner.fresh_ident_count);
+    interner.fresh_ident_count += 1;
+
+    Ok(Value::CtString(Rc::new(name)))
+}
+
 // fn fresh_type_variable() -> Type
 fn fresh_type_variable(interner: &NodeInterner) -> IResult<Value> {
     Ok(Value::Type(interner.next_type_variable_with_kind(Kind::Any)))
@@ -2793,6 +3267,68 @@ fn ctstring_hash(arguments: Vec<(Value, Location)>, location: Location) -> IResu
     hash_item(arguments, location, get_ctstring)
 }
 
+// fn format<let N: u32, T>(format_string: str<N>, args: T) -> CtString
+fn ctstring_format(
+    interner: &NodeInterner,
+    arguments: Vec<(Value, Location)>,
+    location: Location,
+) -> IResult<Value> {
+    let (format_string, args) = check_two_arguments(arguments, location)?;
+    let format_location = format_string.1;
+    let format_string = get_str(interner, format_string)?;
+
+    // A tuple provides one argument per field, any other value is a single argument
+    let mut args = match args.0 {
+        Value::Unit => Vec::new(),
+        Value::Tuple(fields) => fields,
+        other => vec![other],
+    }
+    .into_iter();
+    let args_count = args.len();
+
+    let invalid = |message: String| InterpreterError::InvalidFormatString {
+        message,
+        location: format_location,
+    };
+
+    let mut result = String::new();
+    let mut placeholders = 0;
+    let mut chars = format_string.chars().peekable();
+    while let Some(character) = chars.next() {
+        match character {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' if chars.peek() == Some(&'}') => {
+                chars.next();
+                placeholders += 1;
+                let Some(arg) = args.next() else {
+                    let message = format!(
+                        "{placeholders} placeholders but only {args_count} arguments were given"
+                    );
+                    return Err(invalid(message));
+                };
+                result.push_str(&arg.display_interpolated(interner));
+            }
+            '{' => return Err(invalid("`{` must be followed by `}` or escaped as `{{`".into())),
+            '}' => return Err(invalid("unmatched `}`, escape it as `}}`".into())),
+            other => result.push(other),
+        }
+    }
+
+    if placeholders < args_count {
+        let message = format!("{placeholders} placeholders but {args_count} arguments were given");
+        return Err(invalid(message));
+    }
+
+    Ok(Value::CtString(Rc::new(result)))
+}
+
 fn derive_generators(
     interner: &mut NodeInterner,
     arguments: Vec<(Value, Location)>,
@@ -3,6 +3,7 @@ use std::{hash::Hasher, rc::Rc};
 
 use acvm::FieldElement;
 use noirc_errors::Location;
+use rustc_hash::FxHashMap as HashMap;
 
 use crate::hir::comptime::display::tokens_to_string;
 use crate::hir::comptime::value::add_token_spans;
@@ -83,7 +84,7 @@ pub(crate) fn get_array(
     interner: &NodeInterner,
     (value, location): (Value, Location),
 ) -> IResult<(im::Vector<Value>, Type)> {
-    match value {
+    match value.expand_byte_string() {
         Value::Array(values, typ) => Ok((values, typ)),
         value => {
             let type_var = Box::new(interner.next_type_variable());
@@ -148,6 +149,33 @@ pub(crate) fn get_tuple(
     }
 }
 
+pub(crate) fn get_struct_fields(
+    (value, location): (Value, Location),
+) -> IResult<(HashMap<Rc<String>, Value>, Type)> {
+    match value {
+        Value::Struct(fields, typ) => Ok((fields, typ)),
+        value => {
+            let typ = value.get_type().into_owned();
+            Err(InterpreterError::NonTupleOrStructInMemberAccess { typ, location })
+        }
+    }
+}
+
+pub(crate) fn get_struct_field(
+    field_name: &str,
+    fields: &HashMap<Rc<String>, Value>,
+    struct_type: &Type,
+    location: Location,
+) -> IResult<Value> {
+    fields.get(&field_name.to_string()).cloned().ok_or_else(|| {
+        InterpreterError::ExpectedStructToHaveField {
+            typ: struct_type.clone(),
+            field_name: field_name.to_string(),
+            location,
+        }
+    })
+}
+
 pub(crate) fn get_field((value, location): (Value, Location)) -> IResult<FieldElement> {
     match value {
         Value::Field(value) => Ok(value),
@@ -1,5 +1,6 @@
 use acvm::{
-    acir::BlackBoxFunc, blackbox_solver::BlackBoxFunctionSolver, AcirField, BlackBoxResolutionError,
+    acir::BlackBoxFunc, blackbox_solver::BlackBoxFunctionSolver, AcirField,
+    BlackBoxResolutionError, FieldElement,
 };
 use bn254_blackbox_solver::Bn254BlackBoxSolver;
 use im::Vector;
@@ -9,21 +10,32 @@ use noirc_errors::Location;
 use crate::{
     hir::comptime::{errors::IResult, InterpreterError, Value},
     node_interner::NodeInterner,
+    Type,
 };
 
 use super::builtin::builtin_helpers::{
-    check_one_argument, check_two_arguments, get_array, get_field, get_u32, get_u64,
+    check_one_argument, check_two_arguments, get_array, get_bool, get_field, get_struct_field,
+    get_struct_fields, get_u32, get_u64, get_u8,
 };
 
 pub(super) fn call_foreign(
     interner: &mut NodeInterner,
     name: &str,
     arguments: Vec<(Value, Location)>,
+    return_type: Type,
     location: Location,
 ) -> IResult<Value> {
     match name {
         "poseidon2_permutation" => poseidon2_permutation(interner, arguments, location),
         "keccakf1600" => keccakf1600(interner, arguments, location),
+        "blake2s" => {
+            hash_bytes(interner, arguments, return_type, location, acvm::blackbox_solver::blake2s)
+        }
+        "blake3" => {
+            hash_bytes(interner, arguments, return_type, location, acvm::blackbox_solver::blake3)
+        }
+        "embedded_curve_add" => embedded_curve_add(arguments, return_type, location),
+        "multi_scalar_mul" => multi_scalar_mul(interner, arguments, return_type, location),
         _ => {
             let item = format!("Comptime evaluation for builtin function {name}");
             Err(InterpreterError::Unimplemented { item, location })
@@ -98,3 +110,209 @@ fn keccakf1600(
     let array: Vector<Value> = result_lanes.into_iter().map(Value::U64).collect();
     Ok(Value::Array(array, typ))
 }
+
+// blake2s<let N: u32>(input: [u8; N]) -> [u8; 32]
+// blake3<let N: u32>(input: [u8; N]) -> [u8; 32]
+fn hash_bytes(
+    interner: &mut NodeInterner,
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+    hash: fn(&[u8]) -> Result<[u8; 32], BlackBoxResolutionError>,
+) -> IResult<Value> {
+    let input = check_one_argument(arguments, location)?;
+    let input_location = input.1;
+
+    let (input, _) = get_array(interner, input)?;
+    let input = try_vecmap(input, |byte| get_u8((byte, input_location)))?;
+
+    let digest = hash(&input).map_err(|error| InterpreterError::BlackBoxError(error, location))?;
+
+    let array = digest.into_iter().map(Value::U8).collect();
+    Ok(Value::Array(array, return_type))
+}
+
+// embedded_curve_add(point1: EmbeddedCurvePoint, point2: EmbeddedCurvePoint) -> [Field; 3]
+fn embedded_curve_add(
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+) -> IResult<Value> {
+    let (point1, point2) = check_two_arguments(arguments, location)?;
+
+    let point1 = get_embedded_curve_point(point1)?;
+    let point2 = get_embedded_curve_point(point2)?;
+
+    let (x, y, is_infinite) = bn254_blackbox_solver::embedded_curve_add(point1, point2)
+        .map_err(|error| InterpreterError::BlackBoxError(error, location))?;
+
+    let array = [x, y, is_infinite].into_iter().map(Value::Field).collect();
+    Ok(Value::Array(array, return_type))
+}
+
+// multi_scalar_mul<let N: u32>(
+//     points: [EmbeddedCurvePoint; N],
+//     scalars: [EmbeddedCurveScalar; N],
+// ) -> [Field; 3]
+fn multi_scalar_mul(
+    interner: &mut NodeInterner,
+    arguments: Vec<(Value, Location)>,
+    return_type: Type,
+    location: Location,
+) -> IResult<Value> {
+    let (points, scalars) = check_two_arguments(arguments, location)?;
+    let points_location = points.1;
+    let scalars_location = scalars.1;
+
+    let (points, _) = get_array(interner, points)?;
+    let (scalars, _) = get_array(interner, scalars)?;
+
+    let mut point_fields = Vec::with_capacity(points.len() * 3);
+    for point in points {
+        point_fields.extend(get_embedded_curve_point((point, points_location))?);
+    }
+
+    let mut scalars_lo = Vec::with_capacity(scalars.len());
+    let mut scalars_hi = Vec::with_capacity(scalars.len());
+    for scalar in scalars {
+        let (lo, hi) = get_embedded_curve_scalar((scalar, scalars_location))?;
+        scalars_lo.push(lo);
+        scalars_hi.push(hi);
+    }
+
+    let (x, y, is_infinite) =
+        bn254_blackbox_solver::multi_scalar_mul(&point_fields, &scalars_lo, &scalars_hi)
+            .map_err(|error| InterpreterError::BlackBoxError(error, location))?;
+
+    let array = [x, y, is_infinite].into_iter().map(Value::Field).collect();
+    Ok(Value::Array(array, return_type))
+}
+
+/// Returns the `[x, y, is_infinite]` representation of an `EmbeddedCurvePoint` struct value,
+/// erroring if the point is not on the embedded curve.
+fn get_embedded_curve_point((value, location): (Value, Location)) -> IResult<[FieldElement; 3]> {
+    let (fields, typ) = get_struct_fields((value, location))?;
+
+    let x = get_field((get_struct_field("x", &fields, &typ, location)?, location))?;
+    let y = get_field((get_struct_field("y", &fields, &typ, location)?, location))?;
+    let is_infinite = match get_struct_field("is_infinite", &fields, &typ, location)? {
+        // Points created by `derive_generators` store this flag as a `Field`
+        Value::Field(is_infinite) => is_infinite.is_one(),
+        value => get_bool((value, location))?,
+    };
+
+    if !is_infinite && !is_on_embedded_curve(x, y) {
+        return Err(InterpreterError::InvalidCurvePoint { x, y, location });
+    }
+
+    Ok([x, y, FieldElement::from(is_infinite)])
+}
+
+/// Returns the `(lo, hi)` limbs of an `EmbeddedCurveScalar` struct value.
+fn get_embedded_curve_scalar(
+    (value, location): (Value, Location),
+) -> IResult<(FieldElement, FieldElement)> {
+    let (fields, typ) = get_struct_fields((value, location))?;
+
+    let lo = get_field((get_struct_field("lo", &fields, &typ, location)?, location))?;
+    let hi = get_field((get_struct_field("hi", &fields, &typ, location)?, location))?;
+    Ok((lo, hi))
+}
+
+/// The embedded curve (Grumpkin) is defined by `y^2 = x^3 - 17`.
+fn is_on_embedded_curve(x: FieldElement, y: FieldElement) -> bool {
+    y * y == x * x * x - FieldElement::from(17_u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::{AcirField, FieldElement};
+    use noirc_errors::Location;
+
+    use crate::{
+        hir::comptime::{InterpreterError, Value},
+        node_interner::NodeInterner,
+        Type,
+    };
+
+    use super::{embedded_curve_add, multi_scalar_mul};
+
+    fn generator() -> (FieldElement, FieldElement) {
+        let x = FieldElement::one();
+        let y =
+            FieldElement::from_hex("0x2cf135e7506a45d632d270d45f1181294833fc48d823f272c").unwrap();
+        (x, y)
+    }
+
+    fn point(x: FieldElement, y: FieldElement) -> (Value, Location) {
+        let mut fields = rustc_hash::FxHashMap::default();
+        fields.insert("x".to_string().into(), Value::Field(x));
+        fields.insert("y".to_string().into(), Value::Field(y));
+        fields.insert("is_infinite".to_string().into(), Value::Bool(false));
+        (Value::Struct(fields, Type::Unit), Location::dummy())
+    }
+
+    fn scalar(lo: u128, hi: u128) -> Value {
+        let mut fields = rustc_hash::FxHashMap::default();
+        fields.insert("lo".to_string().into(), Value::Field(lo.into()));
+        fields.insert("hi".to_string().into(), Value::Field(hi.into()));
+        Value::Struct(fields, Type::Unit)
+    }
+
+    fn result_type() -> Type {
+        Type::Array(
+            Box::new(Type::Constant(3_u32.into(), crate::Kind::u32())),
+            Box::new(Type::FieldElement),
+        )
+    }
+
+    fn expect_point(result: Value, x: &str, y: &str) {
+        let expected = vec![
+            Value::Field(FieldElement::from_hex(x).unwrap()),
+            Value::Field(FieldElement::from_hex(y).unwrap()),
+            Value::Field(FieldElement::zero()),
+        ];
+        assert_eq!(result, Value::Array(expected.into(), result_type()));
+    }
+
+    const DOUBLED_GENERATOR: (&str, &str) = (
+        "0x06ce1b0827aafa85ddeb49cdaa36306d19a74caa311e13d46d8bc688cdbffffe",
+        "0x1c122f81a3a14964909ede0ba2a6855fc93faf6fa1a788bf467be7e7a43f80ac",
+    );
+
+    const TRIPLED_GENERATOR: (&str, &str) = (
+        "0x2941b0928df1b9480273773b36397da3e495430a2a7a3857661bc7a446c94f4d",
+        "0x13ae7e938c892308bef0f45ee7386daa2d3b447349a7d0a11b5aa4cfbe69072c",
+    );
+
+    #[test]
+    fn doubles_point() {
+        let (x, y) = generator();
+        let arguments = vec![point(x, y), point(x, y)];
+
+        let result = embedded_curve_add(arguments, result_type(), Location::dummy()).unwrap();
+        expect_point(result, DOUBLED_GENERATOR.0, DOUBLED_GENERATOR.1);
+    }
+
+    #[test]
+    fn multiplies_point_by_scalar() {
+        let (x, y) = generator();
+        let points = Value::Array(vec![point(x, y).0].into(), Type::Unit);
+        let scalars = Value::Array(vec![scalar(3, 0)].into(), Type::Unit);
+        let arguments = vec![(points, Location::dummy()), (scalars, Location::dummy())];
+
+        let mut interner = NodeInterner::default();
+        let result =
+            multi_scalar_mul(&mut interner, arguments, result_type(), Location::dummy()).unwrap();
+        expect_point(result, TRIPLED_GENERATOR.0, TRIPLED_GENERATOR.1);
+    }
+
+    #[test]
+    fn errors_on_point_not_on_curve() {
+        let (x, y) = generator();
+        let arguments = vec![point(x, y), point(x, y + FieldElement::one())];
+
+        let error = embedded_curve_add(arguments, result_type(), Location::dummy()).unwrap_err();
+        assert!(matches!(error, InterpreterError::InvalidCurvePoint { .. }));
+    }
+}
@@ -2,21 +2,41 @@
 
 use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::rc::Rc;
 
+use acvm::{AcirField, FieldElement};
 use fm::{FileId, FileManager};
 use noirc_arena::Index;
-use noirc_errors::Location;
+use noirc_errors::{CustomDiagnostic, Location};
 
 use super::errors::InterpreterError;
 use super::value::Value;
+use crate::ast::{IntegerBitSize, Signedness};
 use crate::elaborator::Elaborator;
-use crate::hir::def_collector::dc_crate::DefCollector;
+use crate::hir::def_collector::dc_crate::{CompilationError, DefCollector};
 use crate::hir::def_collector::dc_mod::collect_defs;
 use crate::hir::def_map::{CrateDefMap, LocalModuleId, ModuleData};
 use crate::hir::{Context, ParsedFiles};
-use crate::parse_program;
+use crate::node_interner::NodeInterner;
+use crate::{parse_program, Kind, Type};
+
+#[derive(Default)]
+struct InterpretOptions {
+    recursion_limit: Option<usize>,
+    /// Treat the program as part of the stdlib so that it may declare `#[builtin]` functions
+    is_stdlib: bool,
+    allow_comptime_env_vars: bool,
+}
+
+fn interpret_helper(src: &str, recursion_limit: Option<usize>) -> Result<Value, InterpreterError> {
+    interpret_with_options(src, InterpretOptions { recursion_limit, ..Default::default() }).0
+}
 
-fn interpret_helper(src: &str) -> Result<Value, InterpreterError> {
+/// Interprets `src`, also returning the diagnostics the interpreter reported without failing.
+fn interpret_with_options(
+    src: &str,
+    options: InterpretOptions,
+) -> (Result<Value, InterpreterError>, Vec<CompilationError>) {
     let file = FileId::default();
 
     // Can't use Index::test_new here for some reason, even with #[cfg(test)].
@@ -37,8 +57,13 @@ fn interpret_helper(src: &str) -> Result<Value, InterpreterError> {
     let parsed_files = ParsedFiles::new();
     let mut context = Context::new(file_manager, parsed_files);
     context.def_interner.populate_dummy_operator_traits();
+    context.allow_comptime_env_vars = options.allow_comptime_env_vars;
 
-    let krate = context.crate_graph.add_crate_root(FileId::dummy());
+    let krate = if options.is_stdlib {
+        context.crate_graph.add_crate_root_and_stdlib(FileId::dummy())
+    } else {
+        context.crate_graph.add_crate_root(FileId::dummy())
+    };
 
     let (module, errors) = parse_program(src);
     assert_eq!(errors.len(), 0);
@@ -56,19 +81,37 @@ fn interpret_helper(src: &str) -> Result<Value, InterpreterError> {
     assert_eq!(elaborator.errors.len(), 0);
 
     let mut interpreter = elaborator.setup_interpreter();
+    if let Some(limit) = options.recursion_limit {
+        interpreter.set_recursion_limit(limit);
+    }
 
     let no_location = Location::dummy();
-    interpreter.call_function(main, Vec::new(), HashMap::new(), no_location)
+    let result = interpreter.call_function(main, Vec::new(), HashMap::new(), no_location);
+    let errors = elaborator.errors.drain(..).map(|(error, _)| error).collect();
+    (result, errors)
 }
 
 fn interpret(src: &str) -> Value {
-    interpret_helper(src).unwrap_or_else(|error| {
+    interpret_helper(src, None).unwrap_or_else(|error| {
         panic!("Expected interpreter to exit successfully, but found {error:?}")
     })
 }
 
 fn interpret_expect_error(src: &str) -> InterpreterError {
-    interpret_helper(src).expect_err("Expected interpreter to error")
+    interpret_helper(src, None).expect_err("Expected interpreter to error")
+}
+
+/// Interprets `src` as if it was part of the stdlib, which may define methods on primitive types.
+fn interpret_stdlib(src: &str) -> Value {
+    let options = InterpretOptions { is_stdlib: true, ..Default::default() };
+    interpret_with_options(src, options).0.unwrap_or_else(|error| {
+        panic!("Expected interpreter to exit successfully, but found {error:?}")
+    })
+}
+
+fn interpret_stdlib_expect_error(src: &str) -> InterpreterError {
+    let options = InterpretOptions { is_stdlib: true, ..Default::default() };
+    interpret_with_options(src, options).0.expect_err("Expected interpreter to error")
 }
 
 #[test]
@@ -236,6 +279,83 @@ fn for_loop_with_continue() {
     assert_eq!(result, Value::U64(11));
 }
 
+#[test]
+fn for_loop_accumulating_into_slice() {
+    let program = "
+    comptime fn main() -> pub u32 {
+        let mut acc = &[];
+        for i in 0 .. 5 {
+            acc = push_back(acc, i);
+        }
+
+        // 1234 followed by the slice length
+        let mut digits = 0;
+        for i in 0 .. 5 {
+            digits = digits * 10 + acc[i];
+        }
+        digits * 10 + len(acc)
+    }
+
+    #[builtin(slice_push_back)]
+    comptime fn push_back<T>(_slice: [T], _elem: T) -> [T] {}
+
+    #[builtin(array_len)]
+    comptime fn len<T>(_slice: [T]) -> u32 {}
+    ";
+    let result = interpret_stdlib(program);
+    assert_eq!(result, Value::U32(12345));
+}
+
+#[test]
+fn stdlib_array_sort_via_is_evaluated_natively_and_stable() {
+    let program = "
+    comptime fn main() -> pub u32 {
+        let descending = [3, 1, 2, 1].sort_via(|a: u32, b: u32| a >= b);
+        let pairs = [(2, 0), (1, 1), (2, 2), (1, 3)];
+        let by_first = pairs.sort_via(|a: (u32, u32), b: (u32, u32)| a.0 <= b.0);
+
+        // 3211 followed by the second elements of the sorted pairs
+        let mut digits = 0;
+        for i in 0 .. 4 {
+            digits = digits * 10 + descending[i];
+        }
+        for i in 0 .. 4 {
+            digits = digits * 10 + by_first[i].1;
+        }
+        digits
+    }
+
+    impl<T, let N: u32> [T; N] {
+        // The interpreter sorts the array itself instead of evaluating this body
+        pub fn sort_via<Env>(self, _ordering: fn[Env](T, T) -> bool) -> Self {
+            self
+        }
+    }
+    ";
+    let result = interpret_stdlib(program);
+    assert_eq!(result, Value::U32(32111302));
+}
+
+#[test]
+fn stdlib_array_sort_via_fails_if_ordering_does_not_hold_for_sorted_array() {
+    let program = "
+    comptime fn main() -> pub [u32; 3] {
+        [2, 1, 2].sort_via(|a: u32, b: u32| a < b)
+    }
+
+    impl<T, let N: u32> [T; N] {
+        pub fn sort_via<Env>(self, _ordering: fn[Env](T, T) -> bool) -> Self {
+            self
+        }
+    }
+    ";
+    let error = interpret_stdlib_expect_error(program);
+    let InterpreterError::FailingConstraint { message: Some(message), .. } = error else {
+        panic!("Expected sorting with `<` to fail on equal elements, got {error:?}");
+    };
+    assert_eq!(message, "Array has not been sorted correctly according to `ordering`.");
+}
+
 #[test]
 fn assert() {
     let program = "comptime fn main() {
@@ -254,6 +374,18 @@ fn assert_fail() {
     assert!(matches!(result, InterpreterError::FailingConstraint { .. }));
 }
 
+#[test]
+fn assert_fail_shows_compared_values() {
+    let program = "comptime fn main() {
+        let x = 3;
+        assert(x + 1 == 2 * x);
+    }";
+    let error = interpret_expect_error(program);
+    let diagnostic = CustomDiagnostic::from(&error);
+    assert_eq!(diagnostic.message, "Assertion failed");
+    assert_eq!(diagnostic.secondaries[0].message, "Left side is `4`, right side is `6`");
+}
+
 #[test]
 fn lambda() {
     let program = "comptime fn main() -> pub u8 {
@@ -264,6 +396,20 @@ fn lambda() {
     assert!(matches!(result, Value::U8(2)));
 }
 
+#[test]
+fn lambda_mutating_captured_reference() {
+    let program = "comptime fn main() -> pub u8 {
+        let mut counter = 0;
+        let counter_ref = &mut counter;
+        let increment = || { *counter_ref += 1; };
+        increment();
+        increment();
+        counter
+    }";
+    let result = interpret(program);
+    assert_eq!(result, Value::U8(2));
+}
+
 #[test]
 fn non_deterministic_recursion() {
     let program = "
@@ -282,6 +428,328 @@ fn non_deterministic_recursion() {
     assert_eq!(result, Value::U64(55));
 }
 
+#[test]
+fn runaway_recursion_reaches_recursion_limit() {
+    let program = "
+    comptime fn main() -> pub u64 {
+        count_up(1)
+    }
+
+    comptime fn count_up(x: u64) -> u64 {
+        if x == 0 {
+            x
+        } else {
+            count_up(x + 1)
+        }
+    }";
+    let error = interpret_expect_error(program);
+    assert!(matches!(error, InterpreterError::RecursionLimitReached { .. }));
+
+    let error = interpret_helper(program, Some(8)).expect_err("Expected interpreter to error");
+    assert!(matches!(error, InterpreterError::RecursionLimitReached { limit: 8, .. }));
+}
+
+#[test]
+fn runaway_recursion_through_closure_reaches_recursion_limit() {
+    let program = "
+    comptime fn main() -> pub u64 {
+        count_up(1)
+    }
+
+    comptime fn count_up(x: u64) -> u64 {
+        let next = |y: u64| if y == 0 { y } else { count_up(y + 1) };
+        next(x)
+    }";
+    let error = interpret_expect_error(program);
+    assert!(matches!(error, InterpreterError::RecursionLimitReached { .. }));
+}
+
+#[test]
+fn deep_recursion_within_default_recursion_limit() {
+    let program = "
+    comptime fn main() -> pub u64 {
+        count_down(100)
+    }
+
+    comptime fn count_down(x: u64) -> u64 {
+        if x == 0 {
+            0
+        } else {
+            1 + count_down(x - 1)
+        }
+    }";
+    assert_eq!(interpret(program), Value::U64(100));
+}
+
+#[test]
+fn errors_have_stable_codes() {
+    let program = "
+    comptime fn main() -> pub u64 {
+        count_up(1)
+    }
+
+    comptime fn count_up(x: u64) -> u64 {
+        if x == 0 {
+            x
+        } else {
+            count_up(x + 1)
+        }
+    }";
+    let error = interpret_expect_error(program);
+    assert_eq!(error.code(), "comptime::recursion_limit_reached");
+
+    let program = "comptime fn main() {
+        assert(1 == 2);
+    }";
+    let error = interpret_expect_error(program);
+    assert_eq!(error.code(), "comptime::failing_constraint");
+}
+
+#[test]
+fn errors_report_their_location() {
+    let source_at = |program: &str, error: InterpreterError| {
+        let span = error.location().expect("Expected error to have a location").span;
+        program[span.start() as usize..span.end() as usize].to_string()
+    };
+
+    let program = "comptime fn main() {
+        assert(1 == 2);
+    }";
+    let error = interpret_expect_error(program);
+    assert_eq!(source_at(program, error), "1 == 2");
+
+    let program = "comptime fn main() -> pub Field {
+        let array = [1, 2, 3];
+        array[5]
+    }";
+    let error = interpret_expect_error(program);
+    assert!(matches!(error, InterpreterError::IndexOutOfBounds { .. }));
+    assert_eq!(source_at(program, error), "array[5]");
+
+    assert_eq!(InterpreterError::Break.location(), None);
+    assert_eq!(InterpreterError::Continue.location(), None);
+}
+
+#[test]
+fn emit_warning_collects_a_warning() {
+    let program = "
+    comptime fn main() -> pub Field {
+        emit_warning(\"something looks off\");
+        1
+    }
+
+    #[builtin(emit_warning)]
+    comptime fn emit_warning<let N: u32>(_message: str<N>) {}
+    ";
+    let options = InterpretOptions { is_stdlib: true, ..Default::default() };
+    let (result, errors) = interpret_with_options(program, options);
+    assert_eq!(
+        result.expect("Expected interpreter to exit successfully"),
+        Value::Field(1u128.into())
+    );
+
+    assert_eq!(errors.len(), 1);
+    let CompilationError::InterpreterError(InterpreterError::ComptimeWarning { message, .. }) =
+        &errors[0]
+    else {
+        panic!("Expected a comptime warning, got {:?}", errors[0]);
+    };
+    assert_eq!(message, "something looks off");
+
+    let diagnostic = CustomDiagnostic::from(&errors[0]);
+    assert!(diagnostic.is_warning());
+}
+
+#[test]
+fn as_field_converts_integers() {
+    let program = "
+    comptime fn main() -> pub Field {
+        let x: u32 = 7;
+        as_field(x) + as_field(true)
+    }
+
+    #[builtin(as_field)]
+    comptime fn as_field<T>(_value: T) -> Field {}
+    ";
+    let result = interpret_stdlib(program);
+    assert_eq!(result, Value::Field(8u128.into()));
+}
+
+#[test]
+fn as_field_of_array_is_an_error() {
+    let program = "
+    comptime fn main() -> pub Field {
+        as_field([1, 2])
+    }
+
+    #[builtin(as_field)]
+    comptime fn as_field<T>(_value: T) -> Field {}
+    ";
+    let error = interpret_stdlib_expect_error(program);
+    assert!(matches!(error, InterpreterError::CannotCoerceToField { .. }));
+    assert_eq!(error.code(), "comptime::cannot_coerce_to_field");
+}
+
+const PARSE_BUILTINS: &str = "
+    struct Option<T> {
+        _is_some: bool,
+        _value: T,
+    }
+
+    #[builtin(parse_field)]
+    comptime fn parse_field<let N: u32>(_s: str<N>) -> Option<Field> {}
+
+    #[builtin(parse_int)]
+    comptime fn parse_int<T, let N: u32>(_s: str<N>) -> Option<T> {}
+    ";
+
+#[test]
+fn parse_field_and_parse_int_accept_decimal_and_hex() {
+    let program = format!(
+        "
+    comptime fn main() -> pub Field {{
+        let a: Option<u8> = parse_int(\"200\");
+        let b: Option<i64> = parse_int(\"-0x1_f\");
+        let c = parse_field(\"-1\");
+        assert(a._is_some & b._is_some & c._is_some);
+        assert_eq(b._value, -31);
+        a._value as Field + c._value
+    }}
+    {PARSE_BUILTINS}"
+    );
+    let result = interpret_stdlib(&program);
+    assert_eq!(result, Value::Field(199u128.into()));
+}
+
+#[test]
+fn parse_field_and_parse_int_return_none_on_overflow() {
+    let modulus = FieldElement::modulus().to_str_radix(10);
+    let program = format!(
+        "
+    comptime fn main() -> pub bool {{
+        let a: Option<u8> = parse_int(\"256\");
+        let b: Option<u32> = parse_int(\"-1\");
+        let c: Option<i8> = parse_int(\"128\");
+        let d: Option<i8> = parse_int(\"-128\");
+        let e = parse_field(\"{modulus}\");
+        !(a._is_some | b._is_some | c._is_some | e._is_some) & d._is_some
+    }}
+    {PARSE_BUILTINS}"
+    );
+    let result = interpret_stdlib(&program);
+    assert_eq!(result, Value::Bool(true));
+}
+
+#[test]
+fn parse_field_and_parse_int_return_none_on_malformed_input() {
+    let program = format!(
+        "
+    comptime fn main() -> pub bool {{
+        let a: Option<u8> = parse_int(\"12a\");
+        let b: Option<u8> = parse_int(\"\");
+        let c = parse_field(\"0x\");
+        let d = parse_field(\"1__0\");
+        let e = parse_field(\" 1\");
+        a._is_some | b._is_some | c._is_some | d._is_some | e._is_some
+    }}
+    {PARSE_BUILTINS}"
+    );
+    let result = interpret_stdlib(&program);
+    assert_eq!(result, Value::Bool(false));
+}
+
+#[test]
+fn bit_width_of_integers() {
+    let program = "
+    comptime fn main() -> pub bool {
+        assert_eq(bit_width(1), 1);
+        assert_eq(bit_width(7), 3);
+        assert_eq(bit_width(8), 4);
+        assert_eq(bit_width(0), 0);
+        true
+    }
+
+    #[builtin(bit_width)]
+    comptime fn bit_width(_n: Field) -> u32 {}
+    ";
+    let result = interpret_stdlib(program);
+    assert_eq!(result, Value::Bool(true));
+}
+
+#[test]
+fn next_power_of_two_of_integers() {
+    let program = "
+    comptime fn main() -> pub bool {
+        assert_eq(next_power_of_two(1), 1);
+        assert_eq(next_power_of_two(7), 8);
+        assert_eq(next_power_of_two(8), 8);
+        assert_eq(next_power_of_two(0), 1);
+        true
+    }
+
+    #[builtin(next_power_of_two)]
+    comptime fn next_power_of_two(_n: Field) -> Field {}
+    ";
+    let result = interpret_stdlib(program);
+    assert_eq!(result, Value::Bool(true));
+}
+
+#[test]
+fn next_power_of_two_out_of_field_range_is_an_error() {
+    let program = "
+    comptime fn main() -> pub Field {
+        // The largest field element, `p - 1`, written as a subtraction since
+        // negative `Field` literals are evaluated without their sign
+        next_power_of_two(0 - 1)
+    }
+
+    #[builtin(next_power_of_two)]
+    comptime fn next_power_of_two(_n: Field) -> Field {}
+    ";
+    let error = interpret_stdlib_expect_error(program);
+    assert!(matches!(error, InterpreterError::NextPowerOfTwoOutOfRange { .. }));
+}
+
+#[test]
+fn current_function_name_is_the_name_of_the_caller() {
+    let program = "
+    comptime fn main() -> pub CtString {
+        log_location()
+    }
+
+    comptime fn log_location() -> CtString {
+        current_function_name()
+    }
+
+    #[builtin(current_function_name)]
+    comptime fn current_function_name() -> CtString {}
+    ";
+    let result = interpret_stdlib(program);
+    assert_eq!(result, Value::CtString(Rc::new("log_location".to_string())));
+}
+
+#[test]
+fn current_module_path_is_the_path_of_the_caller() {
+    let program = "
+    comptime fn main() -> pub CtString {
+        foo::bar::log_location()
+    }
+
+    mod foo {
+        pub mod bar {
+            pub comptime fn log_location() -> CtString {
+                crate::current_module_path()
+            }
+        }
+    }
+
+    #[builtin(current_module_path)]
+    comptime fn current_module_path() -> CtString {}
+    ";
+    let result = interpret_stdlib(program);
+    assert_eq!(result, Value::CtString(Rc::new("foo::bar".to_string())));
+}
+
 #[test]
 fn generic_functions() {
     let program = "
@@ -296,3 +764,87 @@ fn generic_functions() {
     let result = interpret(program);
     assert_eq!(result, Value::U8(2));
 }
+
+#[test]
+fn byte_string_of_printable_ascii_displays_as_string() {
+    let interner = NodeInterner::default();
+    let byte_string = |bytes: &[u8]| Value::ByteString(Rc::new(bytes.to_vec()));
+
+    let text = byte_string(b"say \"hi\"");
+    assert_eq!(text.display(&interner).to_string(), r#""say \"hi\"""#);
+
+    let binary = byte_string(&[104, 105, 0]);
+    assert_eq!(binary.display(&interner).to_string(), "[104, 105, 0]");
+
+    // Plain byte arrays keep displaying as arrays, even if their bytes are printable
+    let typ = Type::Array(
+        Box::new(Type::Constant(2_u32.into(), Kind::u32())),
+        Box::new(Type::Integer(Signedness::Unsigned, IntegerBitSize::Eight)),
+    );
+    let array = Value::Array([Value::U8(104), Value::U8(105)].into_iter().collect(), typ);
+    assert_eq!(array.display(&interner).to_string(), "[104, 105]");
+}
+
+#[test]
+fn env_var_reads_environment_variable_when_allowed() {
+    let program = "
+    comptime fn main() -> pub (Option<CtString>, Option<CtString>) {
+        (env_var(\"NOIR_COMPTIME_ENV_VAR_TEST_SET\"), env_var(\"NOIR_COMPTIME_ENV_VAR_TEST_UNSET\"))
+    }
+
+    struct Option<T> {
+        _is_some: bool,
+        _value: T,
+    }
+
+    #[builtin(env_var)]
+    comptime fn env_var<let N: u32>(_name: str<N>) -> Option<CtString> {}
+    ";
+    std::env::set_var("NOIR_COMPTIME_ENV_VAR_TEST_SET", "some value");
+    std::env::remove_var("NOIR_COMPTIME_ENV_VAR_TEST_UNSET");
+
+    let options =
+        InterpretOptions { is_stdlib: true, allow_comptime_env_vars: true, ..Default::default() };
+    let (result, errors) = interpret_with_options(program, options);
+    assert!(errors.is_empty());
+
+    let Ok(Value::Tuple(options)) = result else {
+        panic!("Expected a tuple, got {result:?}");
+    };
+    let option_fields = |option: &Value| {
+        let Value::Struct(fields, _) = option else {
+            panic!("Expected an Option, got {option:?}");
+        };
+        (fields[&"_is_some".to_string()].clone(), fields[&"_value".to_string()].clone())
+    };
+    let (set_is_some, set_value) = option_fields(&options[0]);
+    assert_eq!(set_is_some, Value::Bool(true));
+    assert_eq!(set_value, Value::CtString(Rc::new("some value".to_string())));
+
+    let (unset_is_some, _) = option_fields(&options[1]);
+    assert_eq!(unset_is_some, Value::Bool(false));
+}
+
+#[test]
+fn env_var_errors_when_not_allowed() {
+    let program = "
+    comptime fn main() -> pub Option<CtString> {
+        env_var(\"NOIR_COMPTIME_ENV_VAR_TEST_NOT_ALLOWED\")
+    }
+
+    struct Option<T> {
+        _is_some: bool,
+        _value: T,
+    }
+
+    #[builtin(env_var)]
+    comptime fn env_var<let N: u32>(_name: str<N>) -> Option<CtString> {}
+    ";
+    std::env::set_var("NOIR_COMPTIME_ENV_VAR_TEST_NOT_ALLOWED", "some value");
+
+    let error = interpret_stdlib_expect_error(program);
+    assert!(matches!(
+        error,
+        InterpreterError::EnvVarsNotAllowed { name, .. } if name == "NOIR_COMPTIME_ENV_VAR_TEST_NOT_ALLOWED"
+    ));
+}
@@ -46,6 +46,9 @@ pub enum Value {
     String(Rc<String>),
     FormatString(Rc<String>, Type),
     CtString(Rc<String>),
+    /// A `[u8; N]` array holding the bytes of a string, such as the result of `str::as_bytes`.
+    /// It's displayed as a string when all of its bytes are printable.
+    ByteString(Rc<Vec<u8>>),
     Function(FuncId, Type, Rc<TypeBindings>),
 
     // Closures also store their original scope (function & module)
@@ -124,6 +127,7 @@ impl Value {
                 Type::String(Box::new(length))
             }
             Value::FormatString(_, typ) => return Cow::Borrowed(typ),
+            Value::ByteString(bytes) => byte_array_type(bytes.len()),
             Value::Function(_, typ, _) => return Cow::Borrowed(typ),
             Value::Closure(_, _, typ, ..) => return Cow::Borrowed(typ),
             Value::Tuple(fields) => {
@@ -156,6 +160,34 @@ impl Value {
         })
     }
 
+    /// Turns a byte string into the `[u8; N]` array it stands for, leaving other values as
+    /// they are.
+    pub(crate) fn expand_byte_string(self) -> Value {
+        match self {
+            Value::ByteString(bytes) => {
+                let typ = byte_array_type(bytes.len());
+                Value::Array(bytes.iter().copied().map(Value::U8).collect(), typ)
+            }
+            other => other,
+        }
+    }
+
+    /// Returns the name and type of each field of a struct value, in the order the fields are
+    /// declared rather than the arbitrary order of the value's field map. Generic field types
+    /// are instantiated with the struct's generic arguments.
+    ///
+    /// Returns `None` if this isn't a struct value.
+    pub(crate) fn struct_field_types(&self) -> Option<Vec<(String, Type)>> {
+        let Value::Struct(_, typ) = self else {
+            return None;
+        };
+        let Type::Struct(struct_type, generics) = typ.follow_bindings() else {
+            return None;
+        };
+        let fields = struct_type.borrow().get_fields(&generics);
+        Some(fields)
+    }
+
     pub(crate) fn into_expression(
         self,
         interner: &mut NodeInterner,
@@ -211,6 +243,9 @@ impl Value {
             Value::FormatString(value, _) => {
                 ExpressionKind::Literal(Literal::Str(unwrap_rc(value)))
             }
+            value @ Value::ByteString(_) => {
+                return value.expand_byte_string().into_expression(interner, location);
+            }
             Value::Function(id, typ, bindings) => {
                 let id = interner.function_definition_id(id);
                 let impl_kind = ImplKind::NotATraitMethod;
@@ -358,6 +393,9 @@ impl Value {
             Value::FormatString(value, _) => {
                 HirExpression::Literal(HirLiteral::Str(unwrap_rc(value)))
             }
+            value @ Value::ByteString(_) => {
+                return value.expand_byte_string().into_hir_expression(interner, location);
+            }
             Value::Function(id, typ, bindings) => {
                 let id = interner.function_definition_id(id);
                 let impl_kind = ImplKind::NotATraitMethod;
@@ -538,6 +576,14 @@ impl Value {
     }
 }
 
+/// The type of a `[u8; N]` array of the given length.
+pub(crate) fn byte_array_type(length: usize) -> Type {
+    Type::Array(
+        Box::new(Type::Constant(length.into(), Kind::u32())),
+        Box::new(Type::Integer(Signedness::Unsigned, IntegerBitSize::Eight)),
+    )
+}
+
 /// Unwraps an Rc value without cloning the inner value if the reference count is 1. Clones otherwise.
 pub(crate) fn unwrap_rc<T: Clone>(rc: Rc<T>) -> T {
     Rc::try_unwrap(rc).unwrap_or_else(|rc| (*rc).clone())
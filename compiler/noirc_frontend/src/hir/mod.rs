@@ -52,6 +52,10 @@ pub struct Context<'file_manager, 'parsed_files> {
     pub parsed_files: Cow<'parsed_files, ParsedFiles>,
 
     pub package_build_path: PathBuf,
+
+    /// Whether comptime code may read the compiler's environment variables through
+    /// `std::meta::env_var`.
+    pub allow_comptime_env_vars: bool,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -73,6 +77,7 @@ impl Context<'_, '_> {
             debug_instrumenter: DebugInstrumenter::default(),
             parsed_files: Cow::Owned(parsed_files),
             package_build_path: PathBuf::default(),
+            allow_comptime_env_vars: false,
         }
     }
 
@@ -90,6 +95,7 @@ impl Context<'_, '_> {
             debug_instrumenter: DebugInstrumenter::default(),
             parsed_files: Cow::Borrowed(parsed_files),
             package_build_path: PathBuf::default(),
+            allow_comptime_env_vars: false,
         }
     }
 
@@ -1329,28 +1329,32 @@ impl Type {
 
     /// Returns the number of field elements required to represent the type once encoded.
     pub fn field_count(&self, location: &Location) -> u32 {
+        self.try_field_count(location).expect("This type cannot exist as a parameter to main")
+    }
+
+    /// Same as `field_count`, but returns `None` instead of panicking if the type can't
+    /// be encoded, for example because it contains a slice or an array of unknown length.
+    pub fn try_field_count(&self, location: &Location) -> Option<u32> {
         match self {
-            Type::FieldElement | Type::Integer { .. } | Type::Bool => 1,
+            Type::FieldElement | Type::Integer { .. } | Type::Bool => Some(1),
             Type::Array(size, typ) => {
-                let length = size
-                    .evaluate_to_u32(location.span)
-                    .expect("Cannot have variable sized arrays as a parameter to main");
+                let length = size.evaluate_to_u32(location.span).ok()?;
                 let typ = typ.as_ref();
-                length * typ.field_count(location)
+                Some(length * typ.try_field_count(location)?)
             }
             Type::Struct(def, args) => {
                 let struct_type = def.borrow();
                 let fields = struct_type.get_fields(args);
-                fields.iter().fold(0, |acc, (_, field_type)| acc + field_type.field_count(location))
-            }
-            Type::CheckedCast { to, .. } => to.field_count(location),
-            Type::Alias(def, generics) => def.borrow().get_type(generics).field_count(location),
-            Type::Tuple(fields) => {
-                fields.iter().fold(0, |acc, field_typ| acc + field_typ.field_count(location))
-            }
-            Type::String(size) => size
-                .evaluate_to_u32(location.span)
-                .expect("Cannot have variable sized strings as a parameter to main"),
+                fields.iter().try_fold(0, |acc, (_, field_type)| {
+                    Some(acc + field_type.try_field_count(location)?)
+                })
+            }
+            Type::CheckedCast { to, .. } => to.try_field_count(location),
+            Type::Alias(def, generics) => def.borrow().get_type(generics).try_field_count(location),
+            Type::Tuple(fields) => fields
+                .iter()
+                .try_fold(0, |acc, field_typ| Some(acc + field_typ.try_field_count(location)?)),
+            Type::String(size) => size.evaluate_to_u32(location.span).ok(),
             Type::FmtString(_, _)
             | Type::Unit
             | Type::TypeVariable(_)
@@ -1363,7 +1367,7 @@ impl Type {
             | Type::Quoted(_)
             | Type::Slice(_)
             | Type::InfixExpr(..)
-            | Type::Error => unreachable!("This type cannot exist as a parameter to main"),
+            | Type::Error => None,
         }
     }
 
@@ -168,6 +168,9 @@ pub struct NodeInterner {
     /// The `Ordering` type is a semi-builtin type that is the result of the comparison traits.
     ordering_type: Option<Type>,
 
+    /// The stdlib's `sort_via` method on arrays, which the comptime interpreter evaluates natively.
+    array_sort_via: Option<FuncId>,
+
     /// Map from ExprId (referring to a Function/Method call) to its corresponding TypeBindings,
     /// filled out during type checking from instantiated variables. Used during monomorphization
     /// to map call site types back onto function parameter types, and undo this binding as needed.
@@ -269,6 +272,11 @@ pub struct NodeInterner {
     /// share the same global values.
     pub(crate) comptime_scopes: Vec<HashMap<DefinitionId, comptime::Value>>,
 
+    /// The number of identifiers created so far by the `fresh_ident` comptime builtin.
+    /// Since a new NodeInterner is created for each compilation, identifiers are numbered
+    /// the same way each time a program is compiled.
+    pub(crate) fresh_ident_count: usize,
+
     /// Captures the documentation comments for each module, struct, trait, function, etc.
     pub(crate) doc_comments: HashMap<ReferenceId, Vec<String>>,
 }
@@ -654,6 +662,7 @@ impl Default for NodeInterner {
             infix_operator_traits: HashMap::default(),
             prefix_operator_traits: HashMap::default(),
             ordering_type: None,
+            array_sort_via: None,
             instantiation_bindings: HashMap::default(),
             field_indices: HashMap::default(),
             next_type_variable_id: std::cell::Cell::new(0),
@@ -675,6 +684,7 @@ impl Default for NodeInterner {
             reference_modules: HashMap::default(),
             auto_import_names: HashMap::default(),
             comptime_scopes: vec![HashMap::default()],
+            fresh_ident_count: 0,
             trait_impl_associated_types: HashMap::default(),
             doc_comments: HashMap::default(),
         }
@@ -1995,6 +2005,17 @@ impl NodeInterner {
         self.prefix_operator_traits.insert(operator, trait_id);
     }
 
+    /// Remember the given stdlib method as the `sort_via` method on arrays if that's what it is.
+    pub fn try_add_array_sort_via(&mut self, self_type: &Type, method_id: FuncId) {
+        if matches!(self_type, Type::Array(..)) && self.function_name(&method_id) == "sort_via" {
+            self.array_sort_via = Some(method_id);
+        }
+    }
+
+    pub fn is_array_sort_via(&self, func_id: FuncId) -> bool {
+        self.array_sort_via == Some(func_id)
+    }
+
     pub fn is_operator_trait(&self, trait_id: TraitId) -> bool {
         self.infix_operator_traits.values().any(|id| *id == trait_id)
             || self.prefix_operator_traits.values().any(|id| *id == trait_id)